@@ -0,0 +1,84 @@
+//! Generates a corpus of random inputs along with their encodings, in the format read by
+//! `tests/conformance.rs`, for differential testing against other implementations:
+//!
+//! ```none
+//! $ cargo run --example golden -- DIR [COUNT] [SEED]
+//! ```
+//!
+//! For every input, `DIR/NAME.in` holds the raw data, and `DIR/NAME.ev1` and `DIR/NAME.ev2` its
+//! encodings with version 1 and 2. The inputs cover all lengths up to a few chunks, and then
+//! random lengths; the same seed always produces the same corpus.
+//!
+//! A corpus whose encodings were replaced with the output of another implementation is checked
+//! against this one by `tests/conformance.rs`, built with the `conformance` feature, reading it
+//! from the directory named by the `ECOJI_VECTORS` environment variable; see
+//! `tests/vectors/README.md`.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use ecoji::emojis::VERSIONS;
+
+/// A xorshift generator, so that the corpus doesn't depend on the algorithms of a random number
+/// crate.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+fn generate(dir: &Path, count: usize, seed: u64) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut random = Random(seed.max(1));
+    for i in 0..count {
+        let len = if i <= 20 {
+            i
+        } else {
+            (random.next() % 4096) as usize
+        };
+        let input = random.bytes(len);
+
+        let name = format!("random{:04}", i);
+        fs::write(dir.join(&name).with_extension("in"), &input)?;
+        for version in VERSIONS.iter() {
+            let encoded = version.encode_to_string(&mut input.as_slice())?;
+            let extension = format!("ev{}", version.VERSION_NUMBER);
+            fs::write(dir.join(&name).with_extension(extension), encoded)?;
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let parsed = match args.as_slice() {
+        [dir] => Some((dir, Ok(100), Ok(1))),
+        [dir, count] => Some((dir, count.parse(), Ok(1))),
+        [dir, count, seed] => Some((dir, count.parse(), seed.parse())),
+        _ => None,
+    };
+    let (dir, count, seed) = match parsed {
+        Some((dir, Ok(count), Ok(seed))) => (dir, count, seed),
+        _ => {
+            eprintln!("usage: golden DIR [COUNT] [SEED]");
+            process::exit(64);
+        }
+    };
+
+    if let Err(e) = generate(Path::new(dir), count, seed) {
+        eprintln!("golden: {}: {}", dir, e);
+        process::exit(74);
+    }
+}
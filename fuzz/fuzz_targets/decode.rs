@@ -0,0 +1,17 @@
+//! Decoding arbitrary bytes may fail, but must not panic, and the output may not be larger than
+//! the input.
+
+#![no_main]
+
+use ecoji::emojis::VERSIONS;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for version in VERSIONS.iter() {
+        if let Ok(decoded) = version.decode_to_vec(&mut &data[..]) {
+            assert!(decoded.len() <= data.len());
+        }
+        let _ = version.decode_strict(&mut &data[..], &mut std::io::sink());
+        let _ = version.decode_partial(data);
+    }
+});
@@ -0,0 +1,19 @@
+//! Whatever decodes successfully decodes to the same data again after being reencoded, so that
+//! normalizing encoded data doesn't change it.
+
+#![no_main]
+
+use ecoji::emojis::VERSIONS;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for version in VERSIONS.iter() {
+        let decoded = match version.decode_to_vec(&mut &data[..]) {
+            Ok(decoded) => decoded,
+            Err(_) => continue,
+        };
+        let reencoded = version.encode_to_string(&mut decoded.as_slice()).unwrap();
+        let redecoded = version.decode_to_vec(&mut reencoded.as_bytes()).unwrap();
+        assert_eq!(redecoded, decoded);
+    }
+});
@@ -0,0 +1,23 @@
+//! Encoding arbitrary data with either version and decoding it with either version gives the
+//! original data back, and the encoding is canonical.
+
+#![no_main]
+
+use ecoji::emojis::VERSIONS;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for version in VERSIONS.iter() {
+        let encoded = version.encode_to_string(&mut &data[..]).unwrap();
+        for decoder in VERSIONS.iter() {
+            let decoded = decoder.decode_to_vec(&mut encoded.as_bytes()).unwrap();
+            assert_eq!(decoded, data);
+        }
+
+        let mut decoded = Vec::new();
+        version
+            .decode_strict(&mut encoded.as_bytes(), &mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+    }
+});
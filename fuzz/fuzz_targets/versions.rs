@@ -0,0 +1,37 @@
+//! Both versions encode the same input to the same symbols, differing only in the glyphs and in
+//! version 2 leaving out the padding which follows the first padding character.
+
+#![no_main]
+
+use ecoji::emojis::Version;
+use ecoji::{VERSION1, VERSION2};
+use libfuzzer_sys::fuzz_target;
+
+/// Identifies the symbol independently of the version: by its index in the alphabet, or for a
+/// padding character, by its kind.
+fn symbol(v: &Version, c: char) -> Result<usize, Option<usize>> {
+    if c == v.PADDING {
+        Err(None)
+    } else if v.is_padding_4(c) {
+        Err((0..4).find(|&bits| v.padding_4(bits) == c))
+    } else {
+        Ok(v.EMOJIS_REV[&c])
+    }
+}
+
+fn symbols(v: &Version, data: &[u8]) -> Vec<Result<usize, Option<usize>>> {
+    let encoded = v.encode_to_string(&mut &data[..]).unwrap();
+    encoded.chars().map(|c| symbol(v, c)).collect()
+}
+
+fuzz_target!(|data: &[u8]| {
+    let v1 = symbols(&VERSION1, data);
+    let v2 = symbols(&VERSION2, data);
+
+    let first_padding = v1
+        .iter()
+        .position(Result::is_err)
+        .map_or(v1.len(), |i| i + 1);
+    assert_eq!(v2, v1[..first_padding]);
+    assert_eq!(v1.len(), (data.len() + 4) / 5 * 4);
+});
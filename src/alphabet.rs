@@ -0,0 +1,142 @@
+//! Fast membership checks for the symbols of the alphabets.
+
+use crate::emojis::{Version, VERSION1, VERSION2};
+use crate::tables::{V1_PADDING, V2_PADDING};
+
+/// The number of code points covered by each block of the set.
+const BLOCK_SIZE: u32 = 0x1000;
+
+/// The first code points of the blocks which contain all the symbols of both versions.
+const BLOCKS: [u32; 2] = [0x2000, 0x1f000];
+
+/// A precomputed set of alphabet symbols, with a membership check which is much faster than the
+/// lookup in [`Version::EMOJIS_REV`](emojis/struct.Version.html#structfield.EMOJIS_REV).
+///
+/// The set is a bitset over the two blocks of 4096 code points which contain all the symbols,
+/// so a character outside of them is rejected with a single comparison. This makes it suitable
+/// for skipping large amounts of text which are not encoded, e.g. when scanning for encoded runs
+/// or ignoring garbage.
+///
+/// # Examples
+///
+/// ```
+/// let set = ecoji::VERSION1.alphabet_set();
+///
+/// assert!("👖📸🎈☕".chars().all(|c| set.contains(c)));
+/// assert!(!set.contains('x'));
+/// assert!(ecoji::AlphabetSet::all().contains('⛵'));
+/// assert!(!set.contains('⛵'));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct AlphabetSet {
+    bits: [[u64; BLOCK_SIZE as usize / 64]; BLOCKS.len()],
+}
+
+static V1_SET: AlphabetSet = AlphabetSet::empty()
+    .with(&VERSION1.EMOJIS)
+    .with(&V1_PADDING);
+
+static V2_SET: AlphabetSet = AlphabetSet::empty()
+    .with(&VERSION2.EMOJIS)
+    .with(&V2_PADDING);
+
+static ALL_SET: AlphabetSet = AlphabetSet::empty()
+    .with(&VERSION1.EMOJIS)
+    .with(&V1_PADDING)
+    .with(&VERSION2.EMOJIS)
+    .with(&V2_PADDING);
+
+impl AlphabetSet {
+    const fn empty() -> AlphabetSet {
+        AlphabetSet {
+            bits: [[0; BLOCK_SIZE as usize / 64]; BLOCKS.len()],
+        }
+    }
+
+    const fn with(mut self, chars: &[char]) -> AlphabetSet {
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i] as u32;
+            let block = match AlphabetSet::block(c) {
+                Some(block) => block,
+                None => panic!("Symbol outside of the blocks of the set"),
+            };
+            let offset = c - BLOCKS[block];
+            self.bits[block][offset as usize / 64] |= 1 << (offset % 64);
+            i += 1;
+        }
+        self
+    }
+
+    const fn block(c: u32) -> Option<usize> {
+        let mut block = 0;
+        while block < BLOCKS.len() {
+            if c >= BLOCKS[block] && c < BLOCKS[block] + BLOCK_SIZE {
+                return Some(block);
+            }
+            block += 1;
+        }
+        None
+    }
+
+    /// Returns the set of the symbols of both versions, including the padding symbols.
+    pub fn all() -> &'static AlphabetSet {
+        &ALL_SET
+    }
+
+    /// Checks whether the character is in the set.
+    #[inline]
+    pub fn contains(&self, c: char) -> bool {
+        match AlphabetSet::block(c as u32) {
+            Some(block) => {
+                let offset = c as u32 - BLOCKS[block];
+                self.bits[block][offset as usize / 64] & (1 << (offset % 64)) != 0
+            }
+            None => false,
+        }
+    }
+}
+
+impl std::fmt::Debug for AlphabetSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let len: u32 = self.bits.iter().flatten().map(|b| b.count_ones()).sum();
+        f.debug_struct("AlphabetSet").field("len", &len).finish()
+    }
+}
+
+impl Version {
+    /// Returns the set of the symbols of this version, including the padding symbols, which
+    /// [`is_valid_alphabet_char`](#method.is_valid_alphabet_char) checks against.
+    pub fn alphabet_set(&self) -> &'static AlphabetSet {
+        match self.VERSION_NUMBER {
+            1 => &V1_SET,
+            2 => &V2_SET,
+            _ => panic!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AlphabetSet;
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_matches_alphabets() {
+        for c in (0..0x20100).filter_map(char::from_u32) {
+            let members = VERSIONS.map(|v| v.is_padding(c) || v.EMOJIS_REV.contains_key(&c));
+            for (v, member) in VERSIONS.iter().zip(members) {
+                assert_eq!(v.alphabet_set().contains(c), member, "{:?}", c);
+            }
+            assert_eq!(AlphabetSet::all().contains(c), members[0] || members[1]);
+        }
+    }
+
+    quickcheck! {
+        fn contains_matches_alphabets(c: char) -> bool {
+            VERSIONS.iter().all(|v| {
+                v.alphabet_set().contains(c) == (v.is_padding(c) || v.EMOJIS_REV.contains_key(&c))
+            })
+        }
+    }
+}
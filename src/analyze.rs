@@ -0,0 +1,406 @@
+//! Statistics of the symbols in encoded text.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::decode::is_line_break;
+use crate::emojis::{Version, VERSION1, VERSION2, VERSIONS};
+
+/// The versions whose alphabets text belongs to, as determined by
+/// [`Version::classify`](emojis/struct.Version.html#method.classify).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Classification {
+    /// Some symbols belong only to the alphabet of version 1, and the rest to both.
+    OnlyV1,
+    /// Some symbols belong only to the alphabet of version 2, and the rest to both.
+    OnlyV2,
+    /// Every symbol belongs to both alphabets, so the text may be encoded with either version.
+    Ambiguous,
+    /// Some symbols belong only to the alphabet of version 1 and some only to that of version 2,
+    /// which the decoder accepts when switching versions, but no encoder produces.
+    Mixed,
+    /// The symbol at the given byte offset belongs to neither alphabet.
+    Invalid {
+        /// The byte offset of the first symbol which belongs to neither alphabet.
+        first_bad_offset: usize,
+    },
+}
+
+/// How sure [`Detection::version_number`](struct.Detection.html#method.version_number) is of the
+/// version it names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Confidence {
+    /// No version could be told: the text contains symbols of neither alphabet, or only ones
+    /// which belong to both, as short inputs often do.
+    None,
+    /// The text mixes symbols which only belong to either version, and the version is the one
+    /// with more of them.
+    Low,
+    /// Some symbols only belong to the version, and the rest belong to both alphabets, so the
+    /// text can't be of the other version.
+    Certain,
+}
+
+/// The symbols which tell the version of encoded text, counted by
+/// [`Version::detect`](emojis/struct.Version.html#method.detect).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Detection {
+    symbols: usize,
+    exclusive: [usize; 2],
+    invalid: usize,
+    first_bad_offset: Option<usize>,
+}
+
+impl Detection {
+    /// Returns the number of symbols checked, i.e. characters other than line breaks.
+    pub fn symbols(&self) -> usize {
+        self.symbols
+    }
+
+    /// Returns the number of symbols which belong only to the alphabet of the version with the
+    /// given number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the version number is not 1 or 2.
+    pub fn exclusive_symbols(&self, version_number: usize) -> usize {
+        assert!(
+            (1..=2).contains(&version_number),
+            "Invalid version number {}",
+            version_number
+        );
+        self.exclusive[version_number - 1]
+    }
+
+    /// Returns the number of symbols which belong to both alphabets.
+    pub fn shared_symbols(&self) -> usize {
+        self.symbols - self.exclusive[0] - self.exclusive[1] - self.invalid
+    }
+
+    /// Returns the number of symbols which belong to neither alphabet.
+    pub fn invalid_symbols(&self) -> usize {
+        self.invalid
+    }
+
+    /// Returns the classification of the text, as
+    /// [`Version::classify`](emojis/struct.Version.html#method.classify) does.
+    pub fn classification(&self) -> Classification {
+        if let Some(first_bad_offset) = self.first_bad_offset {
+            return Classification::Invalid { first_bad_offset };
+        }
+        match self.exclusive.map(|n| n > 0) {
+            [false, false] => Classification::Ambiguous,
+            [true, false] => Classification::OnlyV1,
+            [false, true] => Classification::OnlyV2,
+            [true, true] => Classification::Mixed,
+        }
+    }
+
+    /// Returns the number of the version the text most likely belongs to, if any can be told.
+    pub fn version_number(&self) -> Option<usize> {
+        if self.invalid > 0 {
+            return None;
+        }
+        match self.exclusive[0].cmp(&self.exclusive[1]) {
+            Ordering::Greater => Some(1),
+            Ordering::Less => Some(2),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// Returns how sure [`version_number`](#method.version_number) is of the version.
+    pub fn confidence(&self) -> Confidence {
+        match (self.version_number(), self.classification()) {
+            (None, _) => Confidence::None,
+            (Some(_), Classification::Mixed) => Confidence::Low,
+            (Some(_), _) => Confidence::Certain,
+        }
+    }
+}
+
+/// Statistics of encoded text, computed by [`analyze`](fn.analyze.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncodingStats {
+    symbols: usize,
+    frequencies: BTreeMap<char, usize>,
+    padding: usize,
+    exclusive: [usize; 2],
+    invalid: usize,
+    lines: usize,
+}
+
+impl EncodingStats {
+    /// Returns the number of symbols, i.e. characters other than line breaks.
+    pub fn symbols(&self) -> usize {
+        self.symbols
+    }
+
+    /// Returns the number of occurrences of every distinct symbol.
+    pub fn frequencies(&self) -> &BTreeMap<char, usize> {
+        &self.frequencies
+    }
+
+    /// Returns the number of padding symbols of either version.
+    pub fn padding(&self) -> usize {
+        self.padding
+    }
+
+    /// Returns the numbers of the versions whose alphabet contains all the symbols; none if the
+    /// text mixes the versions or contains invalid symbols.
+    pub fn version_numbers(&self) -> Vec<usize> {
+        if self.invalid > 0 {
+            return Vec::new();
+        }
+        VERSIONS
+            .iter()
+            .filter(|v| self.exclusive[2 - v.VERSION_NUMBER] == 0)
+            .map(|v| v.VERSION_NUMBER)
+            .collect()
+    }
+
+    /// Returns the number of symbols which belong only to the alphabet of the version with the
+    /// given number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the version number is not 1 or 2.
+    pub fn exclusive_symbols(&self, version_number: usize) -> usize {
+        assert!(
+            (1..=2).contains(&version_number),
+            "Invalid version number {}",
+            version_number
+        );
+        self.exclusive[version_number - 1]
+    }
+
+    /// Checks whether the text contains symbols which belong only to version 1 as well as ones
+    /// which belong only to version 2.
+    pub fn mixed_versions(&self) -> bool {
+        self.exclusive.iter().all(|&n| n > 0)
+    }
+
+    /// Returns the number of symbols which belong to neither alphabet.
+    pub fn invalid_symbols(&self) -> usize {
+        self.invalid
+    }
+
+    /// Checks whether the text is broken into several lines; a final line break alone does not
+    /// count.
+    pub fn wrapped(&self) -> bool {
+        self.lines > 1
+    }
+}
+
+/// Computes statistics of the symbols in the encoded text, for both versions, without decoding
+/// it, e.g. to find out why another implementation does not accept it.
+///
+/// # Examples
+///
+/// ```
+/// let stats = ecoji::analyze("👖📸🎈☕\n👖📸🎈☕\n");
+///
+/// assert_eq!(stats.symbols(), 8);
+/// assert_eq!(stats.frequencies()[&'👖'], 2);
+/// assert_eq!(stats.padding(), 2);
+/// assert_eq!(stats.version_numbers(), [1, 2]);
+/// assert!(!stats.mixed_versions());
+/// assert!(stats.wrapped());
+/// ```
+pub fn analyze(input: &str) -> EncodingStats {
+    let mut stats = EncodingStats {
+        symbols: 0,
+        frequencies: BTreeMap::new(),
+        padding: 0,
+        exclusive: [0; 2],
+        invalid: 0,
+        lines: 0,
+    };
+
+    let mut line_started = false;
+    for c in input.chars() {
+        if is_line_break(c) {
+            line_started = false;
+            continue;
+        }
+        if !line_started {
+            stats.lines += 1;
+            line_started = true;
+        }
+
+        stats.symbols += 1;
+        *stats.frequencies.entry(c).or_insert(0) += 1;
+        if VERSIONS.iter().any(|v| v.is_padding(c)) {
+            stats.padding += 1;
+        }
+        match VERSIONS.map(|v| v.is_valid_alphabet_char(c)) {
+            [true, true] => {}
+            [true, false] => stats.exclusive[0] += 1,
+            [false, true] => stats.exclusive[1] += 1,
+            [false, false] => stats.invalid += 1,
+        }
+    }
+    stats
+}
+
+impl Version {
+    /// Determines which alphabets the symbols of the text belong to, ignoring line breaks.
+    ///
+    /// About half of the symbols are shared by both versions, so a single symbol rarely tells
+    /// the version; this looks at all of them, like the decoder does when it switches to the
+    /// other version at the first symbol which only belongs to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecoji::emojis::Version;
+    /// use ecoji::Classification;
+    ///
+    /// assert_eq!(Version::classify("👖📸🎈☕"), Classification::Ambiguous);
+    /// assert_eq!(Version::classify("👶😲⛵👅🍉🧴🦪🦮"), Classification::OnlyV2);
+    /// assert_eq!(
+    ///     Version::classify("👖📸x☕"),
+    ///     Classification::Invalid { first_bad_offset: 8 }
+    /// );
+    /// ```
+    pub fn classify(input: &str) -> Classification {
+        Self::detect(input).classification()
+    }
+
+    /// Counts the symbols of the text which tell its version, ignoring line breaks, and tells
+    /// how sure that makes the version.
+    ///
+    /// Since about half of the symbols belong to both alphabets, short inputs often can't be
+    /// told apart; tools choosing between the versions automatically should check the
+    /// [`confidence`](../struct.Detection.html#method.confidence) rather than assume either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecoji::emojis::Version;
+    /// use ecoji::Confidence;
+    ///
+    /// let detection = Version::detect("👶😲⛵👅🍉🧴🦪🦮");
+    /// assert_eq!(detection.symbols(), 8);
+    /// assert_eq!(detection.version_number(), Some(2));
+    /// assert_eq!(detection.confidence(), Confidence::Certain);
+    ///
+    /// let detection = Version::detect("👖📸🎈☕");
+    /// assert_eq!(detection.shared_symbols(), 4);
+    /// assert_eq!(detection.version_number(), None);
+    /// assert_eq!(detection.confidence(), Confidence::None);
+    /// ```
+    pub fn detect(input: &str) -> Detection {
+        let mut detection = Detection {
+            symbols: 0,
+            exclusive: [0; 2],
+            invalid: 0,
+            first_bad_offset: None,
+        };
+        for (offset, c) in input.char_indices() {
+            if is_line_break(c) {
+                continue;
+            }
+            detection.symbols += 1;
+            match (
+                VERSION1.is_valid_alphabet_char(c),
+                VERSION2.is_valid_alphabet_char(c),
+            ) {
+                (true, true) => {}
+                (true, false) => detection.exclusive[0] += 1,
+                (false, true) => detection.exclusive[1] += 1,
+                (false, false) => {
+                    detection.invalid += 1;
+                    detection.first_bad_offset.get_or_insert(offset);
+                }
+            }
+        }
+        detection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze, Classification, Confidence};
+    use crate::emojis::{Version, VERSION1, VERSION2};
+
+    #[test]
+    fn test_analyze() {
+        let v1 = VERSION1.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+        let v2 = VERSION2.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+
+        let stats = analyze(&v1);
+        assert_eq!(stats.version_numbers(), [1]);
+        assert!(stats.exclusive_symbols(1) > 0);
+        assert_eq!(stats.exclusive_symbols(2), 0);
+        assert!(!stats.wrapped());
+
+        let stats = analyze(&format!("{}\r\n{}x", v1, v2));
+        assert_eq!(stats.symbols(), 17);
+        assert_eq!(stats.frequencies().values().sum::<usize>(), 17);
+        assert!(stats.mixed_versions());
+        assert_eq!(stats.invalid_symbols(), 1);
+        assert!(stats.version_numbers().is_empty());
+        assert!(stats.wrapped());
+
+        let stats = analyze("");
+        assert_eq!(stats.version_numbers(), [1, 2]);
+        assert!(!stats.wrapped());
+    }
+
+    #[test]
+    fn test_classify() {
+        let v1 = VERSION1.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+        let v2 = VERSION2.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+
+        assert_eq!(Version::classify(&v1), Classification::OnlyV1);
+        assert_eq!(Version::classify(&v2), Classification::OnlyV2);
+        assert_eq!(Version::classify(""), Classification::Ambiguous);
+        assert_eq!(
+            Version::classify(&format!("{}\n{}", v1, v2)),
+            Classification::Mixed
+        );
+        assert_eq!(
+            Version::classify(&format!("{}\r\n{}x", v1, v2)),
+            Classification::Invalid {
+                first_bad_offset: v1.len() + v2.len() + 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let v1 = VERSION1.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+        let v2 = VERSION2.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+
+        let detection = Version::detect(&v1);
+        assert_eq!(detection.symbols(), 8);
+        assert_eq!(
+            detection.exclusive_symbols(1) + detection.shared_symbols(),
+            8
+        );
+        assert_eq!(detection.version_number(), Some(1));
+        assert_eq!(detection.confidence(), Confidence::Certain);
+
+        let detection = Version::detect(&format!("{}\n{}{}", v2, v1, v2));
+        assert_eq!(detection.symbols(), 24);
+        assert_eq!(detection.classification(), Classification::Mixed);
+        assert_eq!(detection.version_number(), Some(2));
+        assert_eq!(detection.confidence(), Confidence::Low);
+
+        let detection = Version::detect("");
+        assert_eq!(detection.version_number(), None);
+        assert_eq!(detection.confidence(), Confidence::None);
+
+        let detection = Version::detect(&format!("x{}y", v2));
+        assert_eq!(detection.invalid_symbols(), 2);
+        assert_eq!(detection.confidence(), Confidence::None);
+        assert_eq!(
+            detection.classification(),
+            Classification::Invalid {
+                first_bad_offset: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Encoding and decoding of fixed-size values without allocating.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::ops::Deref;
+use std::slice;
+use std::str;
+
+use crate::emojis::Version;
+use crate::error::DecodeErrorKind;
+use crate::DEFAULT_VERSION;
+
+/// The encoded text of a fixed-size value of `N` bytes, held inline, returned by
+/// [`Version::encode_array`](emojis/struct.Version.html#method.encode_array).
+///
+/// Its capacity is derived from `N` at compile time, so that it fits the text encoded with
+/// either version, and it dereferences to the text as a `str`. It is `Copy`, and is compared and
+/// hashed by the text.
+///
+/// # Examples
+///
+/// ```
+/// let uuid = [0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f,
+///             0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f, 0xe0, 0xc8];
+/// let encoded = ecoji::encode_array(&uuid);
+///
+/// assert_eq!(encoded.chars().count(), 16);
+/// assert_eq!(ecoji::decode_string(&*encoded).unwrap(), uuid);
+/// assert_eq!(encoded.to_string(), ecoji::encode_bytes(uuid));
+/// ```
+#[derive(Clone, Copy)]
+pub struct EncodedArray<const N: usize> {
+    buf: Buffer<N>,
+    len: usize,
+}
+
+/// Room for the text encoding `N` bytes: at most 4 symbols of at most 4 bytes each for every
+/// started chunk of 5 bytes, which is at most `16 * (N + 4) / 5 <= 4 * N + 16` bytes.
+///
+/// It only consists of bytes, so with the C representation the arrays are laid out one after
+/// another without any padding, and the whole buffer can be viewed as a single slice.
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Buffer<const N: usize> {
+    head: [[u8; 4]; N],
+    tail: [u8; 16],
+}
+
+impl<const N: usize> Buffer<N> {
+    fn as_bytes(&self) -> &[u8] {
+        // the buffer consists of `size_of::<Self>()` initialized bytes, see above
+        unsafe {
+            slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        }
+    }
+
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        // see `as_bytes`
+        unsafe {
+            slice::from_raw_parts_mut(self as *mut Self as *mut u8, std::mem::size_of::<Self>())
+        }
+    }
+}
+
+impl<const N: usize> EncodedArray<N> {
+    /// Returns the encoded text.
+    pub fn as_str(&self) -> &str {
+        // the buffer is only filled by the encoder, whose output is valid UTF-8
+        unsafe { str::from_utf8_unchecked(&self.buf.as_bytes()[..self.len]) }
+    }
+}
+
+impl<const N: usize> Deref for EncodedArray<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for EncodedArray<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for EncodedArray<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for EncodedArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for EncodedArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> PartialEq for EncodedArray<N> {
+    fn eq(&self, other: &EncodedArray<N>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for EncodedArray<N> {}
+
+impl<const N: usize> PartialEq<str> for EncodedArray<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for EncodedArray<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> Hash for EncodedArray<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Version {
+    /// Encodes a fixed-size value, like a UUID, a hash or a key, into text held inline, without
+    /// allocating. The encoded text is the same as that of [`encode`](#method.encode).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let encoded = ecoji::VERSION2.encode_array(b"input data");
+    ///
+    /// assert_eq!(encoded, "👶😲⛵👅🍉🧴🦪🦮");
+    /// ```
+    pub fn encode_array<const N: usize>(&self, input: &[u8; N]) -> EncodedArray<N> {
+        let mut encoded = EncodedArray {
+            buf: Buffer {
+                head: [[0; 4]; N],
+                tail: [0; 16],
+            },
+            len: 0,
+        };
+        encoded.len = self
+            .encode_into(input, encoded.buf.as_bytes_mut())
+            .expect("Encoded array is too small");
+        encoded
+    }
+
+    /// Decodes text encoding a fixed-size value, like [`decode`](#method.decode) does, into an
+    /// array without allocating.
+    ///
+    /// Fails under the same conditions as `decode`, and with an error of the
+    /// `io::ErrorKind::InvalidData` kind if the decoded data is not exactly `N` bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let value: [u8; 10] = ecoji::VERSION1.decode_array("👶😲🇲👅🍉🔙🌥🌩")?;
+    /// assert_eq!(&value, b"input data");
+    ///
+    /// assert!(ecoji::VERSION1.decode_array::<4>("👶😲🇲👅🍉🔙🌥🌩").is_err());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_array<const N: usize>(&self, input: &str) -> io::Result<[u8; N]> {
+        let mut output = [0; N];
+        match self.decode_into(input, &mut output) {
+            Ok(n) if n == N => Ok(output),
+            Ok(n) => Err(wrong_length(n, N)),
+            Err(e) => match e.kind() {
+                DecodeErrorKind::BufferTooSmall { needed } => Err(wrong_length(*needed, N)),
+                _ => Err(e.into()),
+            },
+        }
+    }
+}
+
+pub(crate) fn wrong_length(len: usize, expected: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Decoded data is {} bytes long instead of {}", len, expected),
+    )
+}
+
+/// Encodes a fixed-size value with the [default version](constant.DEFAULT_VERSION.html) into
+/// text held inline; see
+/// [`Version::encode_array`](emojis/struct.Version.html#method.encode_array).
+pub fn encode_array<const N: usize>(input: &[u8; N]) -> EncodedArray<N> {
+    encode_array_with(DEFAULT_VERSION, input)
+}
+
+/// Encodes a fixed-size value with the given version into text held inline; see
+/// [`Version::encode_array`](emojis/struct.Version.html#method.encode_array).
+pub fn encode_array_with<const N: usize>(version: &Version, input: &[u8; N]) -> EncodedArray<N> {
+    version.encode_array(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::emojis::VERSIONS;
+    use crate::error::DecodeError;
+
+    fn check<const N: usize>(input: [u8; N]) -> bool {
+        VERSIONS.iter().all(|v| {
+            let encoded = v.encode_array(&input);
+            let copy = encoded;
+            encoded == *v.encode_to_string(&mut &input[..]).unwrap()
+                && copy == encoded
+                && v.decode_array(&encoded).ok() == Some(input)
+        })
+    }
+
+    #[test]
+    fn test_encode_array() {
+        assert!(check([]));
+        assert!(check([0x5a]));
+        assert!(check([0xff; 4]));
+        assert!(check([0xff; 6]));
+        assert!(check([0xa5; 64]));
+    }
+
+    #[test]
+    fn test_decode_array_length() {
+        for v in VERSIONS.iter() {
+            let encoded = v.encode_array(b"abcdef");
+            for e in [
+                v.decode_array::<5>(&encoded).unwrap_err(),
+                v.decode_array::<7>(&encoded).unwrap_err(),
+            ] {
+                assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                assert!(DecodeError::from_io(&e).is_none());
+            }
+            let e = v.decode_array::<6>(&encoded[..4]).unwrap_err();
+            assert!(DecodeError::from_io(&e).is_some());
+        }
+    }
+
+    quickcheck! {
+        fn arrays_encode_like_slices(a: u8, b: (u8, u8, u8), c: (u64, u32, u8)) -> bool {
+            let (b1, b2, b3) = b;
+            let (c1, c2, c3) = c;
+            let mut c = [0; 13];
+            c[..8].copy_from_slice(&c1.to_le_bytes());
+            c[8..12].copy_from_slice(&c2.to_le_bytes());
+            c[12] = c3;
+            check([a]) && check([b1, b2, b3]) && check(c)
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{arg, value_parser, ArgMatches, Command};
+use ecoji::emojis::Version;
+use ecoji::{VERSION1, VERSION2};
+
+use crate::split;
+
+/// Exit code for output which doesn't match the input, `EX_SOFTWARE` of `sysexits.h`.
+const EX_SOFTWARE: i32 = 70;
+
+pub fn command() -> Command {
+    Command::new("bench")
+        .about("Measure the encoding and decoding throughput on random data")
+        .long_about(
+            "Measure the encoding and decoding throughput on random data generated in memory,\n\
+             so that no disk or pipe is involved, with the given version and number of threads.\n\
+             The decoded data is compared with the input, and the exit code is non-zero if it\n\
+             differs.",
+        )
+        .arg(
+            arg!(--size <SIZE> "Amount of random data, optionally followed by K, M or G")
+                .value_parser(split::parse_bytes)
+                .default_value("100M"),
+        )
+        .arg(
+            arg!(--version <VERSION> "Version of the alphabet")
+                .value_parser(["1", "2"])
+                .default_value("2"),
+        )
+        .arg(
+            arg!(-t --threads <N> "Number of threads; defaults to the number of CPUs")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .disable_version_flag(true)
+}
+
+pub fn run(matches: &ArgMatches) -> i32 {
+    let size = *matches.get_one::<u64>("size").unwrap() as usize;
+    let version: &Version = match matches.get_one::<String>("version").map(String::as_str) {
+        Some("1") => &VERSION1,
+        _ => &VERSION2,
+    };
+    let threads = match matches.get_one::<u32>("threads") {
+        Some(&threads) => threads as usize,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let pool = match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("ecoji: {}", e);
+            return super::EX_IOERR;
+        }
+    };
+
+    let input = random(size);
+    let mut encoded = Vec::with_capacity(size / 5 * 16 + 16);
+    let mut decoded = Vec::with_capacity(size);
+
+    let (encode_time, result) = pool.install(|| {
+        timed(|| {
+            if threads > 1 {
+                version.encode_parallel(&mut input.as_slice(), &mut encoded)
+            } else {
+                version.encode(&mut input.as_slice(), &mut encoded)
+            }
+        })
+    });
+    if let Err(e) = result {
+        eprintln!("ecoji: encoding failed: {}", e);
+        return EX_SOFTWARE;
+    }
+    let (decode_time, result) = pool.install(|| {
+        timed(|| {
+            if threads > 1 {
+                version.decode_parallel(&mut encoded.as_slice(), &mut decoded)
+            } else {
+                version.decode(&mut encoded.as_slice(), &mut decoded)
+            }
+        })
+    });
+    if let Err(e) = result {
+        eprintln!("ecoji: decoding failed: {}", e);
+        return EX_SOFTWARE;
+    }
+
+    let report = format!(
+        "version {}, {} bytes of random data, {} thread{}{}\n\
+         encode: {}\n\
+         decode: {}\n",
+        version.VERSION_NUMBER,
+        size,
+        threads,
+        if threads == 1 { "" } else { "s" },
+        // Unoptimized builds are many times slower
+        if cfg!(debug_assertions) {
+            ", debug build"
+        } else {
+            ""
+        },
+        throughput(size, encode_time),
+        throughput(size, decode_time),
+    );
+    let code = super::report(io::stdout().write_all(report.as_bytes()));
+    if decoded != input {
+        eprintln!("ecoji: decoded data differs from the input");
+        return EX_SOFTWARE;
+    }
+    code
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    let result = f();
+    (start.elapsed(), result)
+}
+
+/// Describes the time taken to process the given number of bytes of unencoded data.
+fn throughput(size: usize, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        format!("{:.3} s, {:.1} MB/s", seconds, size as f64 / seconds / 1e6)
+    } else {
+        format!("{:.3} s", seconds)
+    }
+}
+
+/// Generates pseudo-random data with xorshift64*, which is fast and random enough for the
+/// encoder to touch every part of the alphabet.
+fn random(size: usize) -> Vec<u8> {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut data = Vec::with_capacity(size + 8);
+    while data.len() < size {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        data.extend_from_slice(&state.wrapping_mul(0x2545_f491_4f6c_dd1d).to_le_bytes());
+    }
+    data.truncate(size);
+    data
+}
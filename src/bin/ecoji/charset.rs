@@ -0,0 +1,215 @@
+//! Conversion of text input and output in other character encodings from and to UTF-8.
+
+use std::io::{self, Read, Write};
+use std::str;
+
+use clap::{arg, ArgMatches, Command};
+use encoding_rs::{Decoder, DecoderResult, Encoder, EncoderResult, Encoding, UTF_16BE, UTF_16LE};
+
+/// Size of the buffers of the converting reader.
+const BUFFER_SIZE: usize = 8 * 1024;
+
+pub fn args(command: Command) -> Command {
+    command
+        .arg(
+            arg!(--"from-encoding" <ENCODING> "When encoding, convert the input text from ENCODING to UTF-8 first")
+                .value_parser(parse_encoding)
+                .conflicts_with_all(["decode", "lines"])
+                .long_help(
+                    "When encoding, treat the input as text in the given character encoding, like \
+                     latin1, utf-16le or shift_jis, and convert it to UTF-8 before encoding it, \
+                     so that the encoded text decodes to the same text everywhere. A byte order \
+                     mark matching the encoding is removed, and input which is not valid in the \
+                     encoding is an error. The encoding names are those of the WHATWG Encoding \
+                     Standard, in which latin1 stands for windows-1252.",
+                ),
+        )
+        .arg(
+            arg!(--"to-encoding" <ENCODING> "When decoding, convert the output text from UTF-8 to ENCODING")
+                .value_parser(parse_encoding)
+                .requires("decode")
+                .conflicts_with("lines")
+                .long_help(
+                    "When decoding, treat the decoded data as UTF-8 text and convert it to the \
+                     given character encoding, the inverse of --from-encoding. Decoded data \
+                     which is not UTF-8 text, or which contains characters the encoding cannot \
+                     represent, is an error.",
+                ),
+        )
+}
+
+fn parse_encoding(s: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(s.as_bytes()).ok_or_else(|| format!("unknown character encoding {}", s))
+}
+
+/// Wraps the reader so that it yields UTF-8 text converted from the encoding requested with
+/// `--from-encoding`, if any.
+pub fn reader<'a, R: Read + 'a>(matches: &ArgMatches, inner: R) -> Box<dyn Read + 'a> {
+    match matches.get_one::<&'static Encoding>("from-encoding") {
+        Some(&encoding) => Box::new(Utf8Reader {
+            inner,
+            decoder: encoding.new_decoder_with_bom_removal(),
+            input: vec![0; BUFFER_SIZE],
+            start: 0,
+            end: 0,
+            eof: false,
+            finished: false,
+            output: vec![0; BUFFER_SIZE],
+            pos: 0,
+            len: 0,
+        }),
+        None => Box::new(inner),
+    }
+}
+
+/// Wraps the writer so that the UTF-8 text written to it is converted to the encoding requested
+/// with `--to-encoding`, if any.
+pub fn writer<'a, W: Write + 'a>(matches: &ArgMatches, inner: W) -> Box<dyn Write + 'a> {
+    match matches.get_one::<&'static Encoding>("to-encoding") {
+        Some(&encoding) => Box::new(Recoder {
+            inner,
+            encoding,
+            encoder: encoding.new_encoder(),
+            carry: Vec::new(),
+        }),
+        None => Box::new(inner),
+    }
+}
+
+/// A reader adapter converting text in another encoding to UTF-8.
+struct Utf8Reader<R> {
+    inner: R,
+    decoder: Decoder,
+    input: Vec<u8>,
+    start: usize,
+    end: usize,
+    eof: bool,
+    /// Whether the decoder has converted the end of the input, after which it can't be used.
+    finished: bool,
+    output: Vec<u8>,
+    pos: usize,
+    len: usize,
+}
+
+impl<R: Read> Read for Utf8Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.len {
+            if self.finished {
+                return Ok(0);
+            }
+            if self.start == self.end && !self.eof {
+                self.start = 0;
+                self.end = self.inner.read(&mut self.input)?;
+                self.eof = self.end == 0;
+            }
+            let (result, read, written) = self.decoder.decode_to_utf8_without_replacement(
+                &self.input[self.start..self.end],
+                &mut self.output,
+                self.eof,
+            );
+            self.start += read;
+            self.pos = 0;
+            self.len = written;
+            match result {
+                DecoderResult::Malformed(..) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Input is not valid {} text", self.decoder.encoding().name()),
+                    ))
+                }
+                DecoderResult::InputEmpty => self.finished = self.eof,
+                DecoderResult::OutputFull => {}
+            }
+        }
+        let n = buf.len().min(self.len - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// A writer adapter converting UTF-8 text to another encoding. Flushing it ends the text, which
+/// must not end in the middle of a character then.
+struct Recoder<W> {
+    inner: W,
+    encoding: &'static Encoding,
+    encoder: Encoder,
+    /// An incomplete UTF-8 sequence at the end of the last write.
+    carry: Vec<u8>,
+}
+
+impl<W: Write> Recoder<W> {
+    fn convert(&mut self, text: &str, last: bool) -> io::Result<()> {
+        // encoding_rs only decodes UTF-16, its encoders produce UTF-8 instead
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let be = self.encoding == UTF_16BE;
+            let bytes: Vec<u8> = text
+                .encode_utf16()
+                .flat_map(|unit| {
+                    if be {
+                        unit.to_be_bytes()
+                    } else {
+                        unit.to_le_bytes()
+                    }
+                })
+                .collect();
+            return self.inner.write_all(&bytes);
+        }
+
+        let mut text = text;
+        let mut buf = [0; BUFFER_SIZE];
+        loop {
+            let (result, read, written) = self
+                .encoder
+                .encode_from_utf8_without_replacement(text, &mut buf, last);
+            self.inner.write_all(&buf[..written])?;
+            text = &text[read..];
+            match result {
+                EncoderResult::InputEmpty => return Ok(()),
+                EncoderResult::OutputFull => {}
+                EncoderResult::Unmappable(c) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Decoded character {:?} cannot be represented in {}",
+                            c,
+                            self.encoding.name()
+                        ),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for Recoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = std::mem::take(&mut self.carry);
+        data.extend_from_slice(buf);
+        let valid = match str::from_utf8(&data) {
+            Ok(text) => text,
+            // An incomplete sequence at the end may be completed by the next write
+            Err(e) if e.error_len().is_none() => {
+                self.carry = data[e.valid_up_to()..].to_vec();
+                str::from_utf8(&data[..e.valid_up_to()]).unwrap()
+            }
+            Err(_) => return Err(not_text()),
+        };
+        self.convert(valid, false)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.carry.is_empty() {
+            return Err(not_text());
+        }
+        self.convert("", true)?;
+        // The encoder can't be used after the last input, but the text may go on
+        self.encoder = self.encoding.new_encoder();
+        self.inner.flush()
+    }
+}
+
+fn not_text() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Decoded data is not UTF-8 text")
+}
@@ -0,0 +1,126 @@
+use std::io::{self, Read, Write};
+
+use clap::{arg, ArgAction, ArgMatches, Command, ValueHint};
+use ecoji::emojis::VERSIONS;
+use ecoji::DecodeError;
+
+use crate::diagnostics::Tracker;
+use crate::json::{self, Object, ToJson};
+
+pub fn command() -> Command {
+    Command::new("check")
+        .about("Verify that the input is valid Ecoji-encoded data")
+        .long_about(
+            "Verify that the input is valid Ecoji-encoded data without writing the decoded data.\n\
+             Prints the version, the number of symbols and the decoded size, or the position of\n\
+             the first error, in which case the exit code is non-zero.",
+        )
+        .arg(
+            arg!(--json "Print the result as a JSON object on standard output, also if the input is invalid")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            arg!(--strict "Also reject input which is not in the canonical form")
+                .action(ArgAction::SetTrue)
+                .long_help(
+                    "Also reject input which decodes, but is not exactly what the encoder \
+                     produces, e.g. with misplaced padding or set unused bits. Such input makes \
+                     the program exit with code 1 rather than 65.",
+                ),
+        )
+        .arg(
+            arg!([FILE] "Input file; standard input is used if omitted or '-'")
+                .value_hint(ValueHint::FilePath),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> i32 {
+    let path = matches.get_one::<String>("FILE").map(String::as_str);
+
+    let mut source = match super::open_input(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("ecoji: {}: {}", path.unwrap_or("-"), e);
+            return super::EX_NOINPUT;
+        }
+    };
+    let mut input = Vec::new();
+    if let Err(e) = source.read_to_end(&mut input) {
+        eprintln!("ecoji: {}: {}", path.unwrap_or("-"), e);
+        return super::EX_IOERR;
+    }
+
+    // The versions whose alphabets contain every symbol of the input
+    let text = String::from_utf8_lossy(&input);
+    let versions: Vec<_> = VERSIONS
+        .iter()
+        .cloned()
+        .filter(|v| symbols(&text).all(|c| v.is_valid_alphabet_char(c)))
+        .collect();
+    let decoder = versions.first().cloned().unwrap_or(VERSIONS[0]);
+
+    let mut tracker = Tracker::new(input.as_slice());
+    let result =
+        super::decoder(decoder, matches.get_flag("strict")).decode(&mut tracker, &mut io::sink());
+    if matches.get_flag("json") {
+        let versions: Vec<_> = versions.iter().map(|v| v.VERSION_NUMBER).collect();
+        let report = Object::new()
+            .field("valid", &result.is_ok())
+            .field("versions", &versions)
+            .field("symbols", &symbols(&text).count())
+            .field("decoded_size", &result.as_ref().ok());
+        let report = match result.as_ref().err().map(|e| (e, DecodeError::from_io(e))) {
+            Some((_, Some(e))) => {
+                report.field("error", &json::decode_error(e, tracker.location(e)))
+            }
+            Some((e, None)) => {
+                eprintln!("ecoji: {}", e);
+                return super::exit_code(e);
+            }
+            None => report,
+        };
+        let printed = super::report(io::stdout().write_all((report.to_json() + "\n").as_bytes()));
+        return match result {
+            Ok(_) => printed,
+            Err(e) => super::exit_code(&e),
+        };
+    }
+
+    match result {
+        Ok(decoded_size) => {
+            let versions: Vec<_> = versions
+                .iter()
+                .map(|v| v.VERSION_NUMBER.to_string())
+                .collect();
+            let version = if versions.is_empty() {
+                "mixed".to_owned()
+            } else {
+                versions.join(", ")
+            };
+            let summary = format!(
+                "version: {}\nsymbols: {}\ndecoded size: {}\n",
+                version,
+                symbols(&text).count(),
+                decoded_size
+            );
+            super::report(io::stdout().write_all(summary.as_bytes()))
+        }
+        Err(e) => {
+            match DecodeError::from_io(&e) {
+                Some(e) => {
+                    eprintln!("invalid: {}", e);
+                    if let Some(description) = tracker.describe(e) {
+                        eprint!("{}", description);
+                    }
+                }
+                None => eprintln!("ecoji: {}", e),
+            }
+            super::exit_code(&e)
+        }
+    }
+}
+
+/// Iterates over the symbols of the encoded text, skipping line breaks.
+pub fn symbols(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars().filter(|&c| c != '\n' && c != '\r')
+}
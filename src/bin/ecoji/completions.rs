@@ -0,0 +1,21 @@
+use std::io::{self, Write};
+
+use clap::{arg, value_parser, ArgMatches, Command};
+use clap_complete::Shell;
+
+pub fn command() -> Command {
+    Command::new("completions")
+        .about("Print a completion script for the given shell to standard output")
+        .arg(
+            arg!(<SHELL> "Shell to generate the completion script for")
+                .value_parser(value_parser!(Shell)),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> i32 {
+    let shell = *matches.get_one::<Shell>("SHELL").unwrap();
+    // The generator panics on write errors, so the script is written to the output separately
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut super::cli(), "ecoji", &mut script);
+    super::report(io::stdout().write_all(&script))
+}
@@ -0,0 +1,270 @@
+//! Defaults for the command line options, taken from the environment and the configuration file.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ecoji::emojis::Version;
+use ecoji::{LineWidth, VERSION1, VERSION2};
+use toml::{Table, Value};
+
+use crate::wrap::parse_width;
+
+/// Settings which apply when the corresponding command line flags are not given.
+#[derive(Default)]
+pub struct Defaults {
+    pub version: Option<&'static Version>,
+    pub wrap: Option<LineWidth>,
+    pub ignore_garbage: Option<bool>,
+}
+
+impl Defaults {
+    /// Loads the defaults from the configuration file, if it exists, and then overrides them
+    /// with the `ECOJI_VERSION` and `ECOJI_WRAP` environment variables, if they are set.
+    ///
+    /// Returns a message describing the problem if any of the settings is invalid.
+    pub fn load() -> Result<Defaults, String> {
+        Defaults::load_from(config_path().as_deref(), |name| env::var(name).ok())
+    }
+
+    /// Loads the defaults like [`Defaults::load`] does, from the given configuration file and
+    /// the environment variables looked up with `var`.
+    fn load_from<F>(path: Option<&Path>, var: F) -> Result<Defaults, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut defaults = match path {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(contents) => {
+                    Defaults::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))?
+                }
+                // a missing directory on the way, or a file in place of one, means there is no
+                // configuration either
+                Err(ref e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+                    ) =>
+                {
+                    Defaults::default()
+                }
+                Err(e) => return Err(format!("{}: {}", path.display(), e)),
+            },
+            None => Defaults::default(),
+        };
+
+        if let Some(version) = var("ECOJI_VERSION") {
+            defaults.version = Some(
+                parse_version(&version)
+                    .ok_or_else(|| format!("ECOJI_VERSION: invalid version '{}'", version))?,
+            );
+        }
+        if let Some(wrap) = var("ECOJI_WRAP") {
+            defaults.wrap = Some(parse_width(&wrap).map_err(|e| format!("ECOJI_WRAP: {}", e))?);
+        }
+
+        Ok(defaults)
+    }
+
+    fn parse(contents: &str) -> Result<Defaults, String> {
+        let table: Table = contents.parse().map_err(|e| format!("{}", e))?;
+
+        let mut defaults = Defaults::default();
+        for (key, value) in &table {
+            match (key.as_str(), value) {
+                ("version", Value::Integer(n)) => {
+                    defaults.version = Some(
+                        parse_version(&n.to_string())
+                            .ok_or_else(|| format!("invalid version {}", n))?,
+                    )
+                }
+                ("version", Value::String(s)) => {
+                    defaults.version =
+                        Some(parse_version(s).ok_or_else(|| format!("invalid version '{}'", s))?)
+                }
+                ("wrap", Value::Integer(n)) if *n >= 0 => {
+                    defaults.wrap = Some(LineWidth::Symbols(*n as usize))
+                }
+                ("wrap", Value::String(s)) if s == "auto" => defaults.wrap = parse_width(s).ok(),
+                ("ignore-garbage", Value::Boolean(b)) => defaults.ignore_garbage = Some(*b),
+                ("version", _) | ("wrap", _) | ("ignore-garbage", _) => {
+                    return Err(format!("invalid value for '{}': {}", key, value))
+                }
+                _ => return Err(format!("unknown setting '{}'", key)),
+            }
+        }
+
+        Ok(defaults)
+    }
+}
+
+fn parse_version(s: &str) -> Option<&'static Version> {
+    match s.trim_start_matches(['v', 'V']) {
+        "1" => Some(&VERSION1),
+        "2" => Some(&VERSION2),
+        _ => None,
+    }
+}
+
+/// Returns the location of the configuration file: `ecoji/config.toml` in the user's
+/// configuration directory.
+fn config_path() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    dir.map(|dir| dir.join("ecoji").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_vars(_: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns an empty temporary directory for the files of the test.
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ecoji-config-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn version_number(defaults: &Defaults) -> Option<usize> {
+        defaults.version.map(|v| v.VERSION_NUMBER)
+    }
+
+    #[test]
+    fn test_parse_version() {
+        for s in ["1", "v1", "V1"] {
+            assert_eq!(parse_version(s).map(|v| v.VERSION_NUMBER), Some(1));
+        }
+        for s in ["2", "v2", "V2"] {
+            assert_eq!(parse_version(s).map(|v| v.VERSION_NUMBER), Some(2));
+        }
+        for s in ["", "3", "v", "version 1", " 1"] {
+            assert!(parse_version(s).is_none());
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let defaults = Defaults::parse("").unwrap();
+        assert!(defaults.version.is_none() && defaults.wrap.is_none());
+        assert!(defaults.ignore_garbage.is_none());
+
+        assert_eq!(
+            version_number(&Defaults::parse("version = 1").unwrap()),
+            Some(1)
+        );
+        assert_eq!(
+            version_number(&Defaults::parse("version = \"v2\"").unwrap()),
+            Some(2)
+        );
+        assert_eq!(
+            Defaults::parse("wrap = 76").unwrap().wrap,
+            Some(LineWidth::Symbols(76))
+        );
+        assert!(matches!(
+            Defaults::parse("wrap = \"auto\"").unwrap().wrap,
+            Some(LineWidth::Columns(_))
+        ));
+        assert_eq!(
+            Defaults::parse("ignore-garbage = true")
+                .unwrap()
+                .ignore_garbage,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (contents, message) in [
+            ("version = 3", "invalid version 3"),
+            ("version = \"x\"", "invalid version 'x'"),
+            ("version = true", "invalid value for 'version': true"),
+            ("wrap = -1", "invalid value for 'wrap': -1"),
+            ("wrap = \"wide\"", "invalid value for 'wrap': \"wide\""),
+            (
+                "ignore-garbage = 1",
+                "invalid value for 'ignore-garbage': 1",
+            ),
+            ("colour = true", "unknown setting 'colour'"),
+        ] {
+            assert_eq!(Defaults::parse(contents).err().as_deref(), Some(message));
+        }
+        assert!(Defaults::parse("version =").is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = temp_dir("load");
+        let path = dir.join("config.toml");
+        fs::write(&path, "version = 1\nwrap = 10\nignore-garbage = true\n").unwrap();
+
+        let defaults = Defaults::load_from(Some(&path), no_vars).unwrap();
+        assert_eq!(version_number(&defaults), Some(1));
+        assert_eq!(defaults.wrap, Some(LineWidth::Symbols(10)));
+        assert_eq!(defaults.ignore_garbage, Some(true));
+
+        // the environment overrides the file
+        let defaults = Defaults::load_from(Some(&path), |name| match name {
+            "ECOJI_VERSION" => Some("2".to_owned()),
+            "ECOJI_WRAP" => Some("0".to_owned()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(version_number(&defaults), Some(2));
+        assert_eq!(defaults.wrap, Some(LineWidth::Symbols(0)));
+        assert_eq!(defaults.ignore_garbage, Some(true));
+
+        let e = Defaults::load_from(Some(&path), |name| {
+            Some(name)
+                .filter(|&name| name == "ECOJI_VERSION")
+                .map(|_| "3".to_owned())
+        });
+        assert_eq!(
+            e.err().as_deref(),
+            Some("ECOJI_VERSION: invalid version '3'")
+        );
+        let e = Defaults::load_from(None, |name| {
+            Some(name)
+                .filter(|&name| name == "ECOJI_WRAP")
+                .map(|_| "x".to_owned())
+        });
+        assert_eq!(
+            e.err().as_deref(),
+            Some("ECOJI_WRAP: invalid line width 'x'")
+        );
+
+        fs::write(&path, "wrap = -1\n").unwrap();
+        let e = Defaults::load_from(Some(&path), no_vars).err().unwrap();
+        assert!(e.starts_with(&path.display().to_string()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_without_file() {
+        let dir = temp_dir("missing");
+        let file = dir.join("file");
+        fs::write(&file, "").unwrap();
+
+        for path in [
+            dir.join("missing").join("config.toml"),
+            file.join("config.toml"),
+        ] {
+            let defaults = Defaults::load_from(Some(&path), no_vars).unwrap();
+            assert!(defaults.version.is_none() && defaults.wrap.is_none());
+        }
+        let defaults = Defaults::load_from(None, no_vars).unwrap();
+        assert!(defaults.ignore_garbage.is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,57 @@
+//! Setup of the Windows console.
+//!
+//! The standard library talks to Windows consoles with the wide-character console APIs,
+//! converting from and to UTF-8, and never translates line endings of the standard streams, so
+//! emojis are displayed properly and decoded data passes through unchanged. The console code
+//! pages are nevertheless switched to UTF-8 while the program runs, so that text reaching the
+//! console in other ways, e.g. through a redirected stream read by another console program, is
+//! not mangled either.
+
+/// Code pages of the console to restore on exit.
+pub struct Saved {
+    #[cfg(windows)]
+    code_pages: Option<(u32, u32)>,
+}
+
+#[cfg(windows)]
+pub fn setup() -> Saved {
+    use windows_sys::Win32::Globalization::CP_UTF8;
+    use windows_sys::Win32::System::Console::{
+        GetConsoleCP, GetConsoleOutputCP, SetConsoleCP, SetConsoleOutputCP,
+    };
+
+    // Both functions return zero if the process has no console
+    let code_pages = unsafe { (GetConsoleCP(), GetConsoleOutputCP()) };
+    if code_pages.0 == 0 || code_pages.1 == 0 {
+        return Saved { code_pages: None };
+    }
+    unsafe {
+        SetConsoleCP(CP_UTF8);
+        SetConsoleOutputCP(CP_UTF8);
+    }
+    Saved {
+        code_pages: Some(code_pages),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn setup() -> Saved {
+    Saved {}
+}
+
+/// Restores the code pages changed by `setup`, which would otherwise stay in effect in the
+/// console after the program exits.
+#[cfg(windows)]
+pub fn restore(saved: Saved) {
+    use windows_sys::Win32::System::Console::{SetConsoleCP, SetConsoleOutputCP};
+
+    if let Some((input, output)) = saved.code_pages {
+        unsafe {
+            SetConsoleCP(input);
+            SetConsoleOutputCP(output);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn restore(_saved: Saved) {}
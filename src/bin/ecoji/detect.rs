@@ -0,0 +1,195 @@
+use std::io::{self, Read, Write};
+
+use clap::{arg, ArgAction, ArgMatches, Command, ValueHint};
+use ecoji::emojis::VERSIONS;
+use ecoji::{DecodeError, EncodingStats};
+
+use crate::check::symbols;
+use crate::diagnostics::Tracker;
+use crate::json::{self, Object, ToJson};
+
+pub fn command() -> Command {
+    Command::new("detect")
+        .about("Describe the Ecoji-encoded data in the input")
+        .long_about(
+            "Describe the Ecoji-encoded data in the input: whether it is valid Ecoji data, the\n\
+             version of the alphabet, the number of symbols and padding symbols, the decoded\n\
+             size, the line width if the data is wrapped, whether it ends with a newline, and\n\
+             whether it is the canonical encoding of the decoded data, i.e. exactly what `ecoji`\n\
+             would produce.",
+        )
+        .arg(arg!(--json "Print the description as a JSON object").action(ArgAction::SetTrue))
+        .arg(
+            arg!([FILE] "Input file; standard input is used if omitted or '-'")
+                .value_hint(ValueHint::FilePath),
+        )
+}
+
+/// What is known about the input.
+struct Description {
+    error: Option<DecodeError>,
+    /// Line and column of the offending character of the error.
+    location: Option<(usize, usize)>,
+    versions: Vec<usize>,
+    stats: EncodingStats,
+    decoded_size: Option<usize>,
+    line_width: LineWidth,
+    trailing_newline: bool,
+    canonical: bool,
+}
+
+enum LineWidth {
+    None,
+    Regular(usize),
+    Irregular,
+}
+
+pub fn run(matches: &ArgMatches) -> i32 {
+    let path = matches.get_one::<String>("FILE").map(String::as_str);
+
+    let mut source = match super::open_input(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("ecoji: {}: {}", path.unwrap_or("-"), e);
+            return super::EX_NOINPUT;
+        }
+    };
+    let mut input = Vec::new();
+    if let Err(e) = source.read_to_end(&mut input) {
+        eprintln!("ecoji: {}: {}", path.unwrap_or("-"), e);
+        return super::EX_IOERR;
+    }
+
+    let description = describe(&input);
+    let output = if matches.get_flag("json") {
+        description.to_json()
+    } else {
+        description.to_text()
+    };
+    super::report(io::stdout().write_all(output.as_bytes()))
+}
+
+fn describe(input: &[u8]) -> Description {
+    let text = String::from_utf8_lossy(input);
+    let stats = ecoji::analyze(&text);
+    let versions: Vec<_> = VERSIONS
+        .iter()
+        .cloned()
+        .filter(|v| stats.version_numbers().contains(&v.VERSION_NUMBER))
+        .collect();
+    let decoder = versions.first().cloned().unwrap_or(VERSIONS[0]);
+
+    let mut tracker = Tracker::new(input);
+    let (error, decoded_size, canonical) =
+        match super::decoder(decoder, false).decode_to_vec(&mut tracker) {
+            Ok(decoded) => {
+                let symbols: String = symbols(&text).collect();
+                let canonical = versions.iter().any(|v| {
+                    v.encode_to_string(&mut decoded.as_slice())
+                        .is_ok_and(|encoded| encoded == symbols)
+                });
+                (None, Some(decoded.len()), canonical)
+            }
+            Err(e) => (DecodeError::from_io(&e).cloned(), None, false),
+        };
+
+    Description {
+        location: error.as_ref().and_then(|e| tracker.location(e)),
+        error,
+        versions: versions.iter().map(|v| v.VERSION_NUMBER).collect(),
+        stats,
+        decoded_size,
+        line_width: line_width(&text),
+        trailing_newline: text.ends_with('\n'),
+        canonical,
+    }
+}
+
+/// Determines whether the text is wrapped, i.e. consists of several lines all of which but the
+/// last one have the same number of symbols.
+fn line_width(text: &str) -> LineWidth {
+    let lines: Vec<usize> = text
+        .strip_suffix('\n')
+        .unwrap_or(text)
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r').chars().count())
+        .collect();
+    match lines.split_last() {
+        Some((_, [])) | None => LineWidth::None,
+        Some((&last, init)) => {
+            let width = init[0];
+            if width > 0 && init.iter().all(|&n| n == width) && last <= width {
+                LineWidth::Regular(width)
+            } else {
+                LineWidth::Irregular
+            }
+        }
+    }
+}
+
+impl Description {
+    fn version(&self) -> String {
+        if self.versions.is_empty() {
+            "mixed".to_owned()
+        } else {
+            let versions: Vec<_> = self.versions.iter().map(usize::to_string).collect();
+            versions.join(", ")
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = match self.error {
+            None => "ecoji: yes\n".to_owned(),
+            Some(ref e) => format!("ecoji: no ({})\n", e),
+        };
+        text += &format!("version: {}\n", self.version());
+        text += &format!("symbols: {}\n", self.stats.symbols());
+        text += &format!("padding symbols: {}\n", self.stats.padding());
+        if let Some(size) = self.decoded_size {
+            text += &format!("decoded size: {}\n", size);
+        }
+        text += &match self.line_width {
+            LineWidth::None => "line width: none\n".to_owned(),
+            LineWidth::Regular(width) => format!("line width: {}\n", width),
+            LineWidth::Irregular => "line width: irregular\n".to_owned(),
+        };
+        text += &format!("trailing newline: {}\n", yes_no(self.trailing_newline));
+        if self.error.is_none() {
+            text += &format!("canonical: {}\n", yes_no(self.canonical));
+        }
+        text
+    }
+
+    fn to_json(&self) -> String {
+        let (line_width, irregular_lines) = match self.line_width {
+            LineWidth::None => (None, false),
+            LineWidth::Regular(width) => (Some(width), false),
+            LineWidth::Irregular => (None, true),
+        };
+        let error = self
+            .error
+            .as_ref()
+            .map(|e| json::decode_error(e, self.location));
+        let description = Object::new()
+            .field("ecoji", &self.error.is_none())
+            .field("error", &error)
+            .field("versions", &self.versions)
+            .field("symbols", &self.stats.symbols())
+            .field("padding_symbols", &self.stats.padding())
+            .field("mixed_versions", &self.stats.mixed_versions())
+            .field("decoded_size", &self.decoded_size)
+            .field("line_width", &line_width)
+            .field("irregular_lines", &irregular_lines)
+            .field("trailing_newline", &self.trailing_newline)
+            .field("canonical", &self.canonical);
+        description.to_json() + "\n"
+    }
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b {
+        "yes"
+    } else {
+        "no"
+    }
+}
@@ -0,0 +1,185 @@
+//! Pinpointing decoding errors in the input stream.
+
+use std::collections::VecDeque;
+use std::env;
+use std::io::{self, IsTerminal, Read};
+
+use ecoji::{DecodeError, DecodeErrorKind};
+
+/// Number of code points to show around the offending character.
+const CONTEXT: usize = 8;
+
+/// A reader adapter which keeps track of the current line and column, in code points, of the
+/// input stream, as well as a few most recently read bytes, so that a decoding error can be
+/// reported with its location and surroundings even when the input can't be read again.
+pub struct Tracker<R> {
+    inner: R,
+    offset: usize,
+    line: usize,
+    column: usize,
+    previous_line_length: usize,
+    recent: VecDeque<u8>,
+}
+
+impl<R: Read> Tracker<R> {
+    pub fn new(inner: R) -> Tracker<R> {
+        Tracker {
+            inner,
+            offset: 0,
+            line: 1,
+            column: 0,
+            previous_line_length: 0,
+            recent: VecDeque::with_capacity(CONTEXT * 4),
+        }
+    }
+
+    fn track(&mut self, b: u8) {
+        self.offset += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.previous_line_length = self.column;
+            self.column = 0;
+        } else if !is_continuation(b) {
+            self.column += 1;
+        }
+
+        if self.recent.len() == CONTEXT * 4 {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(b);
+    }
+
+    /// Reads a few more characters following the current position, up to the end of the line.
+    fn read_ahead(&mut self) -> Vec<u8> {
+        let mut result = Vec::new();
+        let mut chars = 0;
+        let mut buf = [0];
+        while let Ok(1) = self.inner.read(&mut buf) {
+            if buf[0] == b'\n' {
+                break;
+            }
+            if !is_continuation(buf[0]) {
+                chars += 1;
+                if chars > CONTEXT {
+                    break;
+                }
+            }
+            result.push(buf[0]);
+        }
+        result
+    }
+
+    /// Splits the most recently read bytes into the ones preceding the offending character of the
+    /// given error and the ones starting with it, or returns `None` if the decoder has read too
+    /// far past it.
+    fn split_recent(&self, e: &DecodeError) -> Option<(Vec<u8>, Vec<u8>)> {
+        // The offending character has already been consumed by the decoder, and so has the rest
+        // of its chunk if it is not canonical
+        if self.offset < e.offset() || self.offset - e.offset() > self.recent.len() {
+            return None;
+        }
+        let mut before: Vec<u8> = self.recent.iter().cloned().collect();
+        let culprit = before.split_off(before.len() - (self.offset - e.offset()));
+        Some((before, culprit))
+    }
+
+    /// Returns the line and column, both starting at 1, of the offending character of the given
+    /// error, which must have been produced while decoding the data read through this tracker.
+    ///
+    /// Returns `None` if the decoder has read past the offending character, so that its location
+    /// is unknown.
+    pub fn location(&self, e: &DecodeError) -> Option<(usize, usize)> {
+        let (_, culprit) = self.split_recent(e)?;
+        // Only the length of the previous line is known, so the decoder must not have read past
+        // a line break following the offending character
+        match culprit.iter().position(|&b| b == b'\n') {
+            None => {
+                let culprit_chars = culprit.iter().filter(|b| !is_continuation(**b)).count();
+                Some((self.line, self.column - culprit_chars + 1))
+            }
+            Some(0) if culprit.len() == 1 => Some((self.line - 1, self.previous_line_length + 1)),
+            Some(_) => None,
+        }
+    }
+
+    /// Describes the location of the given error, which must have been produced while decoding
+    /// the data read through this tracker, as a few lines of text: the line and column of the
+    /// offending character, the excerpt of the input around it and a caret pointing at it.
+    ///
+    /// Returns `None` if the decoder has read past the offending character, so that its location
+    /// is unknown.
+    pub fn describe(&mut self, e: &DecodeError) -> Option<String> {
+        let (line, column) = self.location(e)?;
+        let (before, consumed) = self.split_recent(e)?;
+
+        let before = excerpt(&before);
+        let before: Vec<char> = before.rsplit('\n').next().unwrap_or("").chars().collect();
+        let before: String = before[before.len().saturating_sub(CONTEXT)..]
+            .iter()
+            .collect();
+        let (culprit, consumed) = match *e.kind() {
+            DecodeErrorKind::InvalidChar(c) if c.is_control() => {
+                (c.escape_default().to_string(), String::new())
+            }
+            DecodeErrorKind::InvalidChar(c)
+            | DecodeErrorKind::MixedVersion { character: c, .. } => (c.to_string(), String::new()),
+            DecodeErrorKind::NotUtf8 => (char::REPLACEMENT_CHARACTER.to_string(), String::new()),
+            // The culprit is valid, and followed by the rest of its chunk
+            _ => {
+                let consumed = excerpt(&consumed);
+                let mut chars = consumed.chars();
+                let culprit = chars.next().map(String::from).unwrap_or_default();
+                (culprit, chars.as_str().to_owned())
+            }
+        };
+        let after = consumed + &excerpt(&self.read_ahead());
+        let (highlight, reset) = if use_color() { (RED, RESET) } else { ("", "") };
+
+        Some(format!(
+            "  at line {}, column {}:\n    {}{}{}{}{}\n    {}{}^{}\n",
+            line,
+            column,
+            before,
+            highlight,
+            culprit,
+            reset,
+            after,
+            " ".repeat(ecoji::display_width(&before)),
+            highlight,
+            reset,
+        ))
+    }
+}
+
+impl<R: Read> Read for Tracker<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        for &b in &buf[..n] {
+            self.track(b);
+        }
+        Ok(n)
+    }
+}
+
+/// Escape sequences highlighting the offending character in red, and resetting the color.
+const RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// Checks whether the error descriptions should be colored: only when they are written to a
+/// terminal and the `NO_COLOR` environment variable is not set to a non-empty value.
+fn use_color() -> bool {
+    io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+fn is_continuation(b: u8) -> bool {
+    b & 0xc0 == 0x80
+}
+
+/// Converts a fragment of the input to a string, skipping partial characters at its start.
+fn excerpt(bytes: &[u8]) -> String {
+    let start = bytes
+        .iter()
+        .position(|b| !is_continuation(*b))
+        .unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[start..]).into_owned()
+}
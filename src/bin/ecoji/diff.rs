@@ -0,0 +1,66 @@
+use std::io::{self, Write};
+
+use clap::{arg, ArgAction, ArgMatches, Command, ValueHint};
+
+/// Exit code for inputs which are valid, but carry different data, like that of `cmp`.
+const EX_DIFFERENT: i32 = 1;
+
+pub fn command() -> Command {
+    Command::new("diff")
+        .about("Compare the data carried by two Ecoji-encoded inputs")
+        .long_about(
+            "Compare the data carried by two Ecoji-encoded inputs, decoding both side by side.\n\
+             The versions of the inputs are detected automatically, and differences in wrapping\n\
+             and other whitespace are ignored. Prints whether the decoded data is the same, or\n\
+             the offset of the first byte which differs, in which case the exit code is 1.",
+        )
+        .arg(arg!(-q --quiet "Print nothing, only set the exit code").action(ArgAction::SetTrue))
+        .arg(
+            arg!(<A> "First input file; standard input is used if '-'")
+                .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+            arg!(<B> "Second input file; standard input is used if '-'")
+                .value_hint(ValueHint::FilePath),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> i32 {
+    let a = matches.get_one::<String>("A").unwrap();
+    let b = matches.get_one::<String>("B").unwrap();
+    if a == "-" && b == "-" {
+        eprintln!("ecoji: only one of the inputs can be the standard input");
+        return super::EX_USAGE;
+    }
+
+    let mut sources = Vec::new();
+    for path in [a, b] {
+        match super::open_input(Some(path)) {
+            Ok(source) => sources.push(source),
+            Err(e) => {
+                eprintln!("ecoji: {}: {}", path, e);
+                return super::EX_NOINPUT;
+            }
+        }
+    }
+    let (b_source, a_source) = (sources.pop().unwrap(), sources.pop().unwrap());
+
+    let offset = match ecoji::first_difference(a_source, b_source) {
+        Ok(offset) => offset,
+        Err(e) => {
+            eprintln!("ecoji: {}", e);
+            return super::exit_code(&e);
+        }
+    };
+    if matches.get_flag("quiet") {
+        return if offset.is_some() { EX_DIFFERENT } else { 0 };
+    }
+    let summary = match offset {
+        Some(offset) => format!("{} and {} differ at offset {}\n", a, b, offset),
+        None => format!("{} and {} carry the same data\n", a, b),
+    };
+    match super::report(io::stdout().write_all(summary.as_bytes())) {
+        0 if offset.is_some() => EX_DIFFERENT,
+        code => code,
+    }
+}
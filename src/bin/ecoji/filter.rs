@@ -0,0 +1,109 @@
+//! Dropping characters which are not a part of the Ecoji alphabet from the input.
+
+use std::io::{self, Read};
+
+use ecoji::AlphabetSet;
+
+/// A reader adapter which passes through only those characters of the UTF-8 input which belong
+/// to one of the Ecoji alphabets. Everything else, including invalid UTF-8 sequences, is skipped
+/// and counted.
+pub struct GarbageFilter<R> {
+    inner: R,
+    carry: Option<u8>,
+    buf: [u8; 4],
+    start: usize,
+    end: usize,
+    ignored: usize,
+}
+
+impl<R: Read> GarbageFilter<R> {
+    pub fn new(inner: R) -> GarbageFilter<R> {
+        GarbageFilter {
+            inner,
+            carry: None,
+            buf: [0; 4],
+            start: 0,
+            end: 0,
+            ignored: 0,
+        }
+    }
+
+    /// Returns the number of characters skipped so far.
+    pub fn ignored(&self) -> usize {
+        self.ignored
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        if let Some(b) = self.carry.take() {
+            return Ok(Some(b));
+        }
+        let mut buf = [0];
+        loop {
+            return match self.inner.read(&mut buf) {
+                Ok(0) => Ok(None),
+                Ok(_) => Ok(Some(buf[0])),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+        }
+    }
+
+    /// Reads the next alphabet character into the buffer, returning `false` on EOF.
+    fn fill(&mut self) -> io::Result<bool> {
+        'chars: loop {
+            let first = match self.read_byte()? {
+                Some(b) => b,
+                None => return Ok(false),
+            };
+            let width = match first {
+                0x00..=0x7f => 1,
+                0xc2..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf4 => 4,
+                _ => {
+                    self.ignored += 1;
+                    continue;
+                }
+            };
+
+            self.buf[0] = first;
+            for i in 1..width {
+                match self.read_byte()? {
+                    Some(b) if b & 0xc0 == 0x80 => self.buf[i] = b,
+                    b => {
+                        // truncated sequence; the byte which interrupted it may start a new one
+                        self.carry = b;
+                        self.ignored += 1;
+                        continue 'chars;
+                    }
+                }
+            }
+
+            let valid = std::str::from_utf8(&self.buf[..width])
+                .ok()
+                .and_then(|s| s.chars().next())
+                .is_some_and(|c| AlphabetSet::all().contains(c));
+            if valid {
+                self.start = 0;
+                self.end = width;
+                return Ok(true);
+            }
+            self.ignored += 1;
+        }
+    }
+}
+
+impl<R: Read> Read for GarbageFilter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.start == self.end && !self.fill()? {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.end - self.start);
+        buf[..n].copy_from_slice(&self.buf[self.start..self.start + n]);
+        self.start += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,48 @@
+//! Encoding data appended to a file as it grows, like `tail -f`.
+
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::Duration;
+
+use ecoji::{emojis::Version, Builder};
+
+/// How long to wait for more data once the end of the file is reached, the same as `tail -f`.
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Encodes the source, and whenever its end is reached, encodes the bytes which don't fill a chunk
+/// and flushes the destination, then waits for more data. Runs until an error occurs.
+///
+/// The chunks encoded at the end are fully padded, even with version 2, so that the output keeps
+/// decoding as a single stream after more data is appended.
+pub fn encode<R: Read, W: Write>(
+    version: &'static Version,
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<()> {
+    let padded = Builder::new().version(version).full_padding(true).build();
+    let mut buf = vec![0; 64 * 1024];
+    // The number of bytes at the start of the buffer which don't fill a chunk yet
+    let mut pending = 0;
+    loop {
+        let n = match source.read(&mut buf[pending..]) {
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if n == 0 {
+            if pending > 0 {
+                padded.encode(&mut &buf[..pending], destination)?;
+                pending = 0;
+            }
+            destination.flush()?;
+            thread::sleep(INTERVAL);
+            continue;
+        }
+
+        pending += n;
+        let complete = pending - pending % 5;
+        version.encode(&mut &buf[..complete], destination)?;
+        buf.copy_within(complete..pending, 0);
+        pending -= complete;
+    }
+}
@@ -0,0 +1,98 @@
+//! Textual representations of the raw, unencoded data.
+
+use std::io::{self, Read, Write};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::engine::GeneralPurpose;
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use ecoji::{HexReader, SkipWhitespace};
+
+/// Representation of the data on the unencoded side: the input when encoding and the output when
+/// decoding.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Raw,
+    Hex,
+    Base64,
+}
+
+impl ValueEnum for Format {
+    fn value_variants<'a>() -> &'a [Format] {
+        &[Format::Raw, Format::Hex, Format::Base64]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Format::Raw => PossibleValue::new("raw").help("Binary data as is"),
+            Format::Hex => PossibleValue::new("hex").help("Hexadecimal digits, two per byte"),
+            Format::Base64 => PossibleValue::new("base64").help("Standard Base64 with padding"),
+        })
+    }
+}
+
+/// Wraps the reader so that it yields the bytes represented in the given format. Whitespace
+/// between the digits of hexadecimal and Base64 input is ignored.
+pub fn reader<'a, R: Read + 'a>(format: Format, inner: R) -> Box<dyn Read + 'a> {
+    match format {
+        Format::Raw => Box::new(inner),
+        Format::Hex => Box::new(HexReader::new(inner)),
+        Format::Base64 => Box::new(base64::read::DecoderReader::new(
+            SkipWhitespace::new(inner),
+            &STANDARD,
+        )),
+    }
+}
+
+/// A writer adapter representing the bytes written through it in the given format.
+pub enum Output<W: Write> {
+    Raw(W),
+    Hex(W),
+    Base64(Box<base64::write::EncoderWriter<'static, GeneralPurpose, W>>),
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(format: Format, inner: W) -> Output<W> {
+        match format {
+            Format::Raw => Output::Raw(inner),
+            Format::Hex => Output::Hex(inner),
+            Format::Base64 => Output::Base64(Box::new(base64::write::EncoderWriter::new(
+                inner, &STANDARD,
+            ))),
+        }
+    }
+
+    /// Writes the final Base64 quantum, if any, and returns the underlying writer.
+    pub fn finish(self) -> io::Result<W> {
+        match self {
+            Output::Raw(inner) | Output::Hex(inner) => Ok(inner),
+            Output::Base64(mut encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Raw(inner) => inner.write(buf),
+            Output::Hex(inner) => {
+                const DIGITS: &[u8; 16] = b"0123456789abcdef";
+                let mut digits = Vec::with_capacity(buf.len() * 2);
+                for &b in buf {
+                    digits.push(DIGITS[(b >> 4) as usize]);
+                    digits.push(DIGITS[(b & 0x0f) as usize]);
+                }
+                inner.write_all(&digits)?;
+                Ok(buf.len())
+            }
+            Output::Base64(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Raw(inner) | Output::Hex(inner) => inner.flush(),
+            Output::Base64(encoder) => encoder.flush(),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Digests of the unencoded data, for verifying that it survived the round trip.
+
+use std::io::{self, Read, Write};
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use sha2::{Digest, Sha256};
+
+/// Hash function to compute the digest with.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha256,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+impl ValueEnum for Algorithm {
+    fn value_variants<'a>() -> &'a [Algorithm] {
+        &[Algorithm::Sha256]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.name()))
+    }
+}
+
+/// A reader or writer adapter computing the digest of the data passed through it, if an algorithm
+/// is given, or just passing the data through otherwise.
+pub struct Hasher<T> {
+    inner: T,
+    state: Option<Sha256>,
+}
+
+impl<T> Hasher<T> {
+    pub fn new(inner: T, algorithm: Option<Algorithm>) -> Hasher<T> {
+        Hasher {
+            inner,
+            state: algorithm.map(|Algorithm::Sha256| Sha256::new()),
+        }
+    }
+
+    /// Returns the digest of the data passed through so far in hexadecimal, if it is computed.
+    pub fn digest(&self) -> Option<String> {
+        let digest = self.state.clone()?.finalize();
+        Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+impl<R: Read> Read for Hasher<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(ref mut state) = self.state {
+            state.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Hasher<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if let Some(ref mut state) = self.state {
+            state.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
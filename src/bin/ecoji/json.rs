@@ -0,0 +1,131 @@
+//! Minimal JSON output for the machine-readable reports.
+
+use ecoji::{DecodeError, DecodeErrorKind, Deviation};
+
+/// A value which can be written as JSON.
+pub trait ToJson {
+    fn to_json(&self) -> String;
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToJson for usize {
+    fn to_json(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToJson for u32 {
+    fn to_json(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> String {
+        (**self).to_json()
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> String {
+        let mut result = String::from("\"");
+        for c in self.chars() {
+            match c {
+                '"' => result += "\\\"",
+                '\\' => result += "\\\\",
+                c if (c as u32) < 0x20 => result += &format!("\\u{:04x}", c as u32),
+                c => result.push(c),
+            }
+        }
+        result.push('"');
+        result
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> String {
+        self.as_str().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> String {
+        match self {
+            Some(value) => value.to_json(),
+            None => "null".to_owned(),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> String {
+        let items: Vec<_> = self.iter().map(ToJson::to_json).collect();
+        format!("[{}]", items.join(","))
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> String {
+        self.as_slice().to_json()
+    }
+}
+
+/// A JSON object built field by field.
+#[derive(Default)]
+pub struct Object {
+    fields: Vec<String>,
+}
+
+impl Object {
+    pub fn new() -> Object {
+        Object::default()
+    }
+
+    pub fn field<T: ToJson + ?Sized>(mut self, name: &str, value: &T) -> Object {
+        self.fields
+            .push(format!("{}:{}", name.to_json(), value.to_json()));
+        self
+    }
+}
+
+impl ToJson for Object {
+    fn to_json(&self) -> String {
+        format!("{{{}}}", self.fields.join(","))
+    }
+}
+
+/// Describes a decoding error, with the line and column of the offending character if known.
+pub fn decode_error(e: &DecodeError, location: Option<(usize, usize)>) -> Object {
+    let (kind, character, deviation) = match *e.kind() {
+        DecodeErrorKind::NotUtf8 => ("not_utf8", None, None),
+        DecodeErrorKind::InvalidChar(c) => ("invalid_char", Some(c.to_string()), None),
+        DecodeErrorKind::UnexpectedEof { .. } => ("unexpected_eof", None, None),
+        DecodeErrorKind::MixedVersion { character, .. } => {
+            ("mixed_version", Some(character.to_string()), None)
+        }
+        DecodeErrorKind::NotCanonical(deviation) => ("not_canonical", None, Some(deviation)),
+        _ => ("other", None, None),
+    };
+    let deviation = deviation.map(|deviation| match deviation {
+        Deviation::MixedVersions => "mixed_versions",
+        Deviation::MisplacedPadding => "misplaced_padding",
+        Deviation::UnusedBits => "unused_bits",
+        Deviation::TrailingData => "trailing_data",
+        _ => "other",
+    });
+    Object::new()
+        .field("code", &e.code().value())
+        .field("kind", kind)
+        .field("deviation", &deviation)
+        .field("message", &e.to_string())
+        .field("character", &character)
+        .field("offset", &e.offset())
+        .field("position", &e.position())
+        .field("line", &location.map(|(line, _)| line))
+        .field("column", &location.map(|(_, column)| column))
+}
@@ -0,0 +1,104 @@
+//! Line-oriented mode, in which every line of the input is a separate record. Records can also
+//! be delimited by NUL bytes instead of newlines.
+
+use std::io::{self, BufRead, Write};
+
+use ecoji::DecodeError;
+
+use crate::filter::GarbageFilter;
+use crate::formats::{self, Format, Output};
+use crate::json::{self, ToJson};
+use crate::Options;
+
+/// Encodes every record of the source, terminated by the delimiter, separately, writing each
+/// encoded record terminated by the delimiter, except for a final record which had none. The
+/// delimiters themselves are not encoded.
+pub fn encode<R: BufRead, W: Write>(
+    options: &Options,
+    format: Format,
+    delimiter: u8,
+    mut source: R,
+    destination: &mut W,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    while let Some(terminated) = next_line(&mut source, delimiter, &mut line)? {
+        options
+            .version
+            .encode(&mut formats::reader(format, line.as_slice()), destination)?;
+        if terminated {
+            destination.write_all(&[delimiter])?;
+        }
+    }
+    destination.flush()
+}
+
+/// Decodes every record of the source, terminated by the delimiter, separately, writing each
+/// decoded record terminated by the delimiter, except for a final record which had none. Returns
+/// the exit code.
+pub fn decode<R: BufRead, W: Write>(
+    options: &Options,
+    format: Format,
+    delimiter: u8,
+    mut source: R,
+    destination: &mut W,
+) -> i32 {
+    let mut line = Vec::new();
+    let mut number = 0usize;
+    loop {
+        let terminated = match next_line(&mut source, delimiter, &mut line) {
+            Ok(Some(terminated)) => terminated,
+            Ok(None) => break,
+            Err(e) => return super::report(Err::<(), _>(e)),
+        };
+        number += 1;
+
+        let mut output = Output::new(format, &mut *destination);
+        let result = if options.ignore_garbage {
+            let mut filter = GarbageFilter::new(line.as_slice());
+            options.decode(&mut filter, &mut output)
+        } else {
+            options.decode(&mut line.as_slice(), &mut output)
+        };
+        if let Some(e) = result.as_ref().err().and_then(DecodeError::from_io) {
+            if options.json {
+                let error = json::decode_error(e, None).field("record", &number);
+                eprintln!("{}", error.to_json());
+            } else {
+                let record = if delimiter == b'\n' { "line" } else { "record" };
+                eprintln!("ecoji: {} {}: {}", record, number, e);
+            }
+            return super::data_error_code(e);
+        }
+        let result = result
+            .and_then(|_| output.finish())
+            .and_then(|destination| {
+                if terminated {
+                    destination.write_all(&[delimiter])
+                } else {
+                    Ok(())
+                }
+            });
+        if result.is_err() {
+            return super::report(result);
+        }
+    }
+    super::report(destination.flush())
+}
+
+/// Reads the next record without its terminating delimiter, returning whether it had one, or
+/// `None` at the end of input.
+fn next_line<R: BufRead>(
+    source: &mut R,
+    delimiter: u8,
+    line: &mut Vec<u8>,
+) -> io::Result<Option<bool>> {
+    line.clear();
+    if source.read_until(delimiter, line)? == 0 {
+        return Ok(None);
+    }
+    let terminated = line.last() == Some(&delimiter);
+    if terminated {
+        line.pop();
+    }
+    Ok(Some(terminated))
+}
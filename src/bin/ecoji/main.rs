@@ -0,0 +1,1039 @@
+extern crate clap;
+extern crate ecoji;
+
+mod bench;
+#[cfg(feature = "encoding")]
+mod charset;
+mod check;
+mod completions;
+mod config;
+mod console;
+mod detect;
+mod diagnostics;
+mod diff;
+mod filter;
+mod follow;
+mod formats;
+mod hash;
+mod json;
+mod lines;
+#[cfg(feature = "progress")]
+mod progress;
+mod selftest;
+mod split;
+mod spool;
+mod stats;
+mod tee;
+mod terminal;
+mod transcode;
+mod wrap;
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+use clap::{arg, crate_version, value_parser, ArgAction, ArgGroup, ArgMatches, Command, ValueHint};
+use ecoji::*;
+use memmap2::Mmap;
+
+use crate::config::Defaults;
+use crate::diagnostics::Tracker;
+use crate::filter::GarbageFilter;
+use crate::formats::{Format, Output};
+use crate::hash::{Algorithm, Hasher};
+use crate::json::ToJson;
+use crate::split::Splitter;
+use crate::spool::Spool;
+use crate::stats::{Counts, Meter};
+use crate::tee::Tee;
+use crate::terminal::{BinaryOutput, TextOnly};
+use crate::wrap::parse_width;
+
+fn cli() -> Command {
+    let command = Command::new("ecoji")
+        .version(crate_version!())
+        .author("Vladimir Matveev <vladimir.matweev@gmail.com>")
+        .about(
+            "Encode or decode data in FILE or standard input as emojis and print results to standard output.\n\
+             A Rust reimplementation of the original Ecoji library and tool (https://github.com/keith-turner/ecoji)."
+        )
+        .arg(
+            arg!([FILE]... "Input files, read one after another in the given order; '-' stands for standard input, which is also used if no files are given")
+                .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+            arg!(--each "Process every input file separately, writing the output next to it")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["string", "output", "split", "dry-run", "tee"])
+                .long_help(
+                    "Process every input file separately, like gzip does, instead of \
+                     concatenating the inputs. When encoding, the output for a file is written to \
+                     a file with the same name and the .ecoji suffix added, and when decoding, \
+                     the .ecoji suffix is removed. Existing files are not overwritten unless \
+                     --force is given. The standard input, given as '-', is processed to the \
+                     standard output.",
+                ),
+        )
+        .arg(
+            arg!(-s --string <STRING> "Encode or decode the given string instead of reading input")
+                .conflicts_with("FILE")
+                .long_help(
+                    "Encode or decode the given string instead of reading input.\n\
+                     The string is used verbatim: unlike with `echo STRING | ecoji`, no trailing \
+                     newline is appended to it before encoding, so `ecoji -s hello` encodes exactly \
+                     five bytes. As usual, no trailing newline is written after the output either.",
+                ),
+        )
+        .arg(arg!(-d --decode "Decode data").action(ArgAction::SetTrue))
+        .arg(
+            arg!(-o --output <OUTPUT> "Write the output to the given file instead of standard output")
+                .value_hint(ValueHint::FilePath)
+                .conflicts_with("split"),
+        )
+        .arg(
+            arg!(--tee <FILE> "Also write the unprocessed input to FILE")
+                .value_hint(ValueHint::FilePath)
+                .long_help(
+                    "Also write the input, exactly as it was read, to FILE, while the result goes \
+                     to the output as usual. This saves reading the input twice, which isn't \
+                     possible with pipes and process substitution, e.g. to keep both the raw and \
+                     the encoded copy of a download.",
+                ),
+        )
+        .arg(
+            arg!(-f --force "Write binary output to a terminal and overwrite files with --each")
+                .action(ArgAction::SetTrue)
+                .long_help(
+                    "When decoding, write the output to a terminal even if it is binary. Without \
+                     this flag, decoding stops with an error as soon as the decoded data turns out \
+                     not to be printable text while standard output is a terminal.\n\
+                     With --each, also overwrite existing output files.",
+                ),
+        )
+        .arg(
+            arg!(-w --wrap <COLS> "When encoding, wrap lines after COLS emojis; 0 disables wrapping (default)")
+                .value_parser(parse_width)
+                .long_help(
+                    "When encoding, wrap lines after COLS emojis; 0 disables wrapping, which is \
+                     the default. With `auto`, lines are as wide as the terminal instead, \
+                     measured in columns taking into account that most emojis are displayed \
+                     double-width, or 76 columns wide if the standard output is not a terminal.",
+                ),
+        )
+        .arg(
+            arg!(-F --follow "When encoding, keep encoding data appended to the input file, like tail -f")
+                .action(ArgAction::SetTrue)
+                .requires("FILE")
+                .conflicts_with_all(["decode", "each", "string", "split", "lines", "dry-run", "mmap"])
+                .long_help(
+                    "When encoding, keep waiting for data appended to the input file, or to the \
+                     last one of several input files, once its end is reached, and encode it as \
+                     it arrives, like `tail -f` does, until interrupted. Whenever the end of the \
+                     file is reached, the data read so far is encoded and the output is flushed, \
+                     so that a consumer can decode it right away; the final chunk is then padded \
+                     to 4 emojis even with version 2, so the output keeps decoding as a single \
+                     stream. The input is always read sequentially.",
+                ),
+        )
+        .arg(
+            arg!(--split <SIZE> "When encoding, write the output to numbered files of at most SIZE bytes")
+                .value_parser(split::parse_size)
+                .conflicts_with("decode")
+                .long_help(
+                    "When encoding, write the output to files of at most SIZE bytes each instead \
+                     of the standard output. SIZE is a number of bytes, optionally followed by K, \
+                     M or G. The files are named with the prefix given by --prefix followed by a \
+                     sequential number, e.g. data.bin.000, data.bin.001 and so on for data.bin. \
+                     The output is only split between chunks and every file is wrapped on its \
+                     own, so that each file can be decoded separately, while decoding all of them \
+                     in order, e.g. with `ecoji -d data.bin.*`, restores the whole input.",
+                ),
+        )
+        .arg(
+            arg!(--prefix <PREFIX> "Names of the files written with --split; defaults to the input file name and a dot")
+                .requires("split"),
+        )
+        .arg(
+            arg!(--"buffer-size" <SIZE> "Size of the buffers for reading input files and writing output files")
+                .value_parser(parse_buffer_size)
+                .default_value("8K")
+                .long_help(
+                    "Size of the buffers for reading input files and writing output files, \
+                     including those written with --each, --split and --tee. SIZE is a number of \
+                     bytes, optionally followed by K, M or G. Larger buffers \
+                     mean fewer system calls, which may speed up processing of huge streams, \
+                     especially on network file systems. The standard input and output are \
+                     buffered by the system and are not affected.",
+                ),
+        )
+        .arg(
+            arg!(-l --lines "Encode or decode every line of the input separately")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["wrap", "split"])
+                .long_help(
+                    "Encode or decode every line of the input separately, writing one line of \
+                     output for each line of input. Newlines are not encoded, but terminate every \
+                     output line, so that encoded records can be mixed with line-oriented tools \
+                     like grep and awk. A last line without a newline gives output without one.",
+                ),
+        )
+        .arg(
+            arg!(-'0' --null "With --lines, separate records with NUL bytes instead of newlines")
+                .action(ArgAction::SetTrue)
+                .requires("lines")
+                .long_help(
+                    "With --lines, separate the records of both the input and the output with NUL \
+                     bytes instead of newlines, so that records containing newlines, like the \
+                     file names printed by `find -print0`, can be encoded and decoded safely.",
+                ),
+        )
+        .arg(
+            arg!(-i --"ignore-garbage" "When decoding, ignore characters outside of the Ecoji alphabet")
+                .action(ArgAction::SetTrue)
+                .overrides_with("no-ignore-garbage"),
+        )
+        .arg(
+            arg!(--"no-ignore-garbage" "When decoding, fail on characters outside of the Ecoji alphabet (default)")
+                .action(ArgAction::SetTrue)
+                .overrides_with("ignore-garbage"),
+        )
+        .arg(
+            arg!(--strict "When decoding, reject input which is not in the canonical form")
+                .action(ArgAction::SetTrue)
+                .requires("decode")
+                .long_help(
+                    "When decoding, also reject input which decodes, but is not exactly what the \
+                     encoder produces: input mixing both versions of the alphabet, misplaced \
+                     padding, set unused bits in the final chunk, or data following a padded \
+                     chunk. Such input makes the program exit with code 1 rather than 65, which \
+                     is used for input which can't be decoded at all. Strict decoding is always \
+                     sequential.",
+                ),
+        )
+        .arg(
+            arg!(--spool "When decoding, write no output unless the whole input decodes")
+                .action(ArgAction::SetTrue)
+                .requires("decode")
+                .conflicts_with("dry-run")
+                .long_help(
+                    "When decoding, hold back the output until the whole input has been decoded \
+                     successfully, so that nothing is written for input which turns out to be \
+                     corrupt or, with --strict, not canonical halfway through. The output is kept \
+                     in memory up to 16 MiB and in a temporary file beyond that.",
+                ),
+        )
+        .arg(
+            arg!(-t --threads <N> "Number of threads to use")
+                .value_parser(value_parser!(u32).range(1..))
+                .long_help(
+                    "Number of threads to encode or decode with. Defaults to the number of logical \
+                     CPUs when the input is a file, and to 1 otherwise.\n\
+                     With more than one thread, the input is read in large blocks, which are split \
+                     into segments processed in parallel. When encoding, segments are a multiple \
+                     of 5 bytes long, and when decoding, they consist of whole groups of 4 emojis, \
+                     so no encoded chunk is ever split and the output is exactly the same as with \
+                     a single thread.",
+                ),
+        )
+        .arg(
+            arg!(--"input-format" <FORMAT> "When encoding, the format of the input data")
+                .value_parser(value_parser!(Format))
+                .default_value("raw")
+                .conflicts_with("decode"),
+        )
+        .arg(
+            arg!(--"output-format" <FORMAT> "When decoding, the format to print the decoded data in")
+                .value_parser(value_parser!(Format))
+                .default_value("raw")
+                .requires("decode"),
+        )
+        .arg(
+            arg!(--hash <ALGORITHM> "Also print the digest of the unencoded data to standard error")
+                .value_parser(value_parser!(Algorithm))
+                .conflicts_with("lines")
+                .long_help(
+                    "Also print the digest of the unencoded data, i.e. the input when encoding and \
+                     the output when decoding, to standard error, or as a JSON object with --json. \
+                     Comparing the digests printed on both ends verifies that the encoded text \
+                     has survived being passed through messaging systems and the like intact.",
+                ),
+        )
+        .arg(
+            arg!(-n --"dry-run" "Print the sizes of the input and the output instead of the output itself")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("split")
+                .long_help(
+                    "Read and process the whole input as usual, but instead of writing the output, \
+                     print the number of bytes and symbols it would consist of, e.g. to check \
+                     whether the encoded data fits into a length-limited message.",
+                ),
+        )
+        .arg(
+            arg!(--mmap "Map input files into memory instead of reading them")
+                .action(ArgAction::SetTrue)
+                .long_help(
+                    "Map input files into memory instead of reading them, which avoids copying \
+                     the data through intermediate buffers and can speed up processing of large \
+                     files. Inputs which are not regular files, like the standard input, are read \
+                     as usual. Mapped inputs are processed in large blocks like with several \
+                     threads, even with a single one. Note that the program may be terminated if an input file is \
+                     truncated by another process while it is being processed.",
+                ),
+        )
+        .arg(
+            arg!(--json "Report decoding errors as JSON objects on standard error")
+                .action(ArgAction::SetTrue)
+                .long_help(
+                    "Report decoding errors as JSON objects on standard error, one per line, \
+                     for consumption by scripts: the kind of the error, the message, the \
+                     offending character, its byte offset and position in the input, and its \
+                     line and column if known. The digests printed with --hash are reported as \
+                     JSON objects too.",
+                ),
+        )
+        .arg(
+            arg!(-v --verbose "Print the settings in effect and statistics to standard error")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            arg!(--v1 "Use version 1")
+                .action(ArgAction::SetTrue)
+                .long_help(
+                    "Use version 1 of the Ecoji alphabet.\n\
+                     Note that version 2 is the default, matching the reference implementation, \
+                     while earlier releases of this tool encoded with version 1 by default. To \
+                     keep producing version 1 output, pass --v1 or set `version = 1` in the \
+                     configuration file. Decoding is not affected: both versions are always \
+                     recognized.",
+                ),
+        )
+        .arg(arg!(--v2 "Use version 2 (default)").action(ArgAction::SetTrue))
+        .group(ArgGroup::new("alphabet").args(["v1", "v2"]))
+        .arg(
+            arg!(--"generate-man" "Print the man page in the roff format and exit")
+                .action(ArgAction::SetTrue)
+                .hide(true),
+        )
+        .after_long_help(
+            "Defaults for the version, wrapping and ignoring garbage can be changed in the \
+             configuration file, ~/.config/ecoji/config.toml (or %APPDATA%\\ecoji\\config.toml \
+             on Windows), with the `version`, `wrap` and `ignore-garbage` settings, e.g.:\n\
+             \n    version = 2\n    wrap = 76\n    ignore-garbage = true\n\n\
+             The `wrap` setting also accepts \"auto\". \
+             The ECOJI_VERSION and ECOJI_WRAP environment variables take precedence over the \
+             configuration file, and command line flags take precedence over both.",
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand(bench::command())
+        .subcommand(check::command())
+        .subcommand(completions::command())
+        .subcommand(detect::command())
+        .subcommand(diff::command())
+        .subcommand(selftest::command())
+        .subcommand(transcode::command());
+    #[cfg(feature = "encoding")]
+    let command = charset::args(command);
+    command
+}
+
+/// Exit code for input which decodes, but is rejected by `--strict` as not canonical. It is
+/// outside of the `sysexits.h` range to tell it apart from input which can't be decoded at all.
+const EX_NOT_CANONICAL: i32 = 1;
+
+/// Exit codes, following the conventions of `sysexits.h`.
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_OSERR: i32 = 71;
+const EX_CANTCREAT: i32 = 73;
+const EX_IOERR: i32 = 74;
+const EX_CONFIG: i32 = 78;
+
+/// Size of the buffers for reading input files in the subcommands, the same as the default of
+/// `--buffer-size` and of the standard library.
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Amount of output kept in memory with `--spool` before it is moved to a temporary file.
+const SPOOL_MEMORY: usize = 16 * 1024 * 1024;
+
+/// Suffix of the files written with `--each` when encoding.
+const SUFFIX: &str = ".ecoji";
+
+/// Exit code of processes killed by `SIGPIPE`, which is how Unix filters usually terminate when
+/// the reading end of their output pipe is closed early.
+const EX_SIGPIPE: i32 = 128 + 13;
+
+fn main() {
+    let matches = cli().get_matches();
+
+    let console = console::setup();
+    let code = match matches.subcommand() {
+        Some(("bench", matches)) => bench::run(matches),
+        Some(("check", matches)) => check::run(matches),
+        Some(("completions", matches)) => completions::run(matches),
+        Some(("detect", matches)) => detect::run(matches),
+        Some(("diff", matches)) => diff::run(matches),
+        Some(("self-test", matches)) => selftest::run(matches),
+        Some(("transcode", matches)) => transcode::run(matches),
+        _ => run(&matches),
+    };
+    console::restore(console);
+    process::exit(code);
+}
+
+/// Settings of the main encoding or decoding operation.
+#[derive(Clone, Copy)]
+struct Options<'a> {
+    version: &'static emojis::Version,
+    wrap: LineWidth,
+    ignore_garbage: bool,
+    /// Whether only the canonical encoding is accepted when decoding.
+    strict: bool,
+    threads: usize,
+    /// Whether the input is processed in large blocks by the parallel encoder and decoder, which
+    /// is also faster for memory-mapped inputs with a single thread.
+    blocks: bool,
+    buffer_size: usize,
+    /// Whether decoding errors are reported as JSON.
+    json: bool,
+    path: Option<&'a str>,
+}
+
+impl Options<'_> {
+    /// Returns the codec to decode with, which starts with the version, skips the line breaks of
+    /// wrapped input and is strict if requested.
+    fn codec(&self) -> Codec {
+        decoder(self.version, self.strict)
+    }
+
+    /// Decodes the source sequentially, in the strict mode if requested.
+    fn decode<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.codec().decode(source, destination)
+    }
+}
+
+/// Returns a codec decoding like the library decoders starting with the version, strict if
+/// requested, but skipping line breaks, so that wrapped input decodes as is.
+fn decoder(version: &'static emojis::Version, strict: bool) -> Codec {
+    Codec::builder()
+        .version(version)
+        .strict(strict)
+        .ignore_line_breaks(true)
+        .build()
+}
+
+fn run(matches: &ArgMatches) -> i32 {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    if matches.get_flag("generate-man") {
+        let result = clap_mangen::Man::new(cli()).render(&mut stdout);
+        return report(result.and_then(|_| stdout.flush()));
+    }
+
+    let defaults = match Defaults::load() {
+        Ok(defaults) => defaults,
+        Err(e) => {
+            eprintln!("ecoji: {}", e);
+            return EX_CONFIG;
+        }
+    };
+
+    let version = if matches.get_flag("v1") {
+        &VERSION1
+    } else if matches.get_flag("v2") {
+        &VERSION2
+    } else {
+        defaults.version.unwrap_or(&VERSION2)
+    };
+    let ignore_garbage = if matches.get_flag("ignore-garbage") {
+        true
+    } else if matches.get_flag("no-ignore-garbage") {
+        false
+    } else {
+        defaults.ignore_garbage.unwrap_or(false)
+    };
+
+    let paths: Vec<&str> = matches
+        .get_many::<String>("FILE")
+        .unwrap_or_default()
+        .map(String::as_str)
+        .collect();
+    // Several inputs are treated like a single stream
+    let path = match paths[..] {
+        [path] => Some(path),
+        _ => None,
+    };
+    let string = matches.get_one::<String>("string");
+    let threads = match matches.get_one::<u32>("threads") {
+        Some(&threads) => threads as usize,
+        None if string.is_none() && is_file(path) => {
+            thread::available_parallelism().map_or(1, |n| n.get())
+        }
+        None => 1,
+    };
+    let mmap = matches.get_flag("mmap");
+    let blocks = threads > 1 || mmap;
+    if blocks {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global();
+        if let Err(e) = pool {
+            eprintln!("ecoji: cannot start worker threads: {}", e);
+            return EX_OSERR;
+        }
+    }
+
+    let options = Options {
+        version,
+        wrap: matches
+            .get_one::<LineWidth>("wrap")
+            .cloned()
+            .or(defaults.wrap)
+            .unwrap_or(LineWidth::Symbols(0)),
+        ignore_garbage,
+        strict: matches.get_flag("strict"),
+        threads,
+        blocks,
+        buffer_size: *matches.get_one::<usize>("buffer-size").unwrap(),
+        json: matches.get_flag("json"),
+        path,
+    };
+
+    if matches.get_flag("each") {
+        return each(matches, &options, &paths, mmap);
+    }
+
+    let source = match string {
+        Some(string) => Box::new(io::Cursor::new(string.clone().into_bytes())),
+        None => match open_inputs(&paths, mmap, options.buffer_size) {
+            Ok(source) => source,
+            Err((path, e)) => {
+                eprintln!("ecoji: {}: {}", path, e);
+                return EX_NOINPUT;
+            }
+        },
+    };
+
+    let source: Box<dyn Read> = match matches.get_one::<String>("tee") {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(Tee::new(
+                source,
+                BufWriter::with_capacity(options.buffer_size, file),
+            )),
+            Err(e) => {
+                eprintln!("ecoji: {}: {}", path, e);
+                return EX_CANTCREAT;
+            }
+        },
+        None => source,
+    };
+
+    let decoding = matches.get_flag("decode");
+    let dry_run = matches.get_flag("dry-run");
+    let start = Instant::now();
+
+    let mut sink = io::sink();
+    let mut file;
+    let mut guarded;
+    let destination: &mut dyn Write = if dry_run {
+        &mut sink
+    } else if let Some(path) = matches.get_one::<String>("output") {
+        file = match File::create(path) {
+            Ok(file) => BufWriter::with_capacity(options.buffer_size, file),
+            Err(e) => {
+                eprintln!("ecoji: {}: {}", path, e);
+                return EX_CANTCREAT;
+            }
+        };
+        &mut file
+    } else if decoding && !matches.get_flag("force") && stdout.is_terminal() {
+        guarded = TextOnly::new(&mut stdout);
+        &mut guarded
+    } else {
+        &mut stdout
+    };
+    let mut spool = None;
+    let destination: &mut dyn Write = if matches.get_flag("spool") {
+        spool.insert(Spool::new(destination, SPOOL_MEMORY))
+    } else {
+        destination
+    };
+    let mut output = Meter::new(destination);
+
+    let (input, data, code) = process(matches, &options, source, &mut output);
+    let output = output.counts();
+    if code != 0 {
+        return code;
+    }
+    let code = report(spool.map_or(Ok(()), Spool::commit));
+    if code != 0 {
+        return code;
+    }
+
+    if matches.get_flag("verbose") {
+        print_stats(matches, &options, input, data, output, start);
+    }
+    if dry_run {
+        let summary = stats::summary(decoding, input, output);
+        return report(stdout.write_all(summary.as_bytes()));
+    }
+    0
+}
+
+/// Encodes or decodes the source into the destination according to the command line flags.
+///
+/// Returns the counts of the read input and of the unencoded data, and the exit code.
+fn process(
+    matches: &ArgMatches,
+    options: &Options,
+    source: Box<dyn Read>,
+    output: &mut Meter<&mut dyn Write>,
+) -> (Counts, Counts, i32) {
+    #[cfg(feature = "progress")]
+    let source = progress::track(source, options.path);
+
+    let input_format = *matches.get_one::<Format>("input-format").unwrap();
+    let output_format = *matches.get_one::<Format>("output-format").unwrap();
+    let decoding = matches.get_flag("decode");
+    let hash = matches.get_one::<Algorithm>("hash").cloned();
+
+    if matches.get_flag("lines") {
+        let delimiter = if matches.get_flag("null") {
+            b'\0'
+        } else {
+            b'\n'
+        };
+        let mut source = Meter::new(source);
+        let records = io::BufReader::with_capacity(options.buffer_size, &mut source);
+        let code = if decoding {
+            lines::decode(options, output_format, delimiter, records, output)
+        } else {
+            report(lines::encode(
+                options,
+                input_format,
+                delimiter,
+                records,
+                output,
+            ))
+        };
+        let data = if decoding {
+            output.counts()
+        } else {
+            source.counts()
+        };
+        (source.counts(), data, code)
+    } else if !decoding {
+        #[cfg(feature = "encoding")]
+        let source = charset::reader(matches, source);
+        let source = Hasher::new(formats::reader(input_format, source), hash);
+        let mut source = Meter::new(source);
+        let result = if let Some(&limit) = matches.get_one::<u64>("split") {
+            let prefix = match matches.get_one::<String>("prefix") {
+                Some(prefix) => prefix.clone(),
+                None => split_prefix(options.path),
+            };
+            let mut destination = Splitter::new(prefix, limit, options.wrap, options.buffer_size);
+            let result = encode(options, &mut source, &mut destination);
+            result.and_then(|_| destination.finish())
+        } else if matches.get_flag("follow") {
+            let mut destination = LineWrapper::new(output, options.wrap);
+            follow::encode(options.version, &mut source, &mut destination)
+        } else {
+            let mut destination = LineWrapper::new(output, options.wrap);
+            let result = encode(options, &mut source, &mut destination);
+            result
+                .and_then(|_| destination.finish())
+                .and_then(|output| output.flush())
+        };
+        let code = report(result);
+        if code == 0 {
+            print_digest(options, hash, source.get_ref());
+        }
+        (source.counts(), source.counts(), code)
+    } else {
+        let mut source = Meter::new(source);
+        #[cfg(feature = "encoding")]
+        let mut recoded = charset::writer(matches, output);
+        #[cfg(feature = "encoding")]
+        let output = &mut recoded;
+        let mut formatted = Output::new(output_format, output);
+        let mut destination = Meter::new(Hasher::new(&mut formatted, hash));
+        let code = if options.ignore_garbage {
+            let mut source = GarbageFilter::new(&mut source);
+            let code = decode(options, &mut source, &mut destination);
+            if source.ignored() > 0 {
+                eprintln!(
+                    "ecoji: ignored {} characters outside of the Ecoji alphabet",
+                    source.ignored()
+                );
+            }
+            code
+        } else {
+            decode(options, &mut source, &mut destination)
+        };
+        let data = destination.counts();
+        if code == 0 {
+            print_digest(options, hash, destination.get_ref());
+        }
+        let code = match code {
+            0 => report(formatted.finish().and_then(|output| output.flush())),
+            code => code,
+        };
+        (source.counts(), data, code)
+    }
+}
+
+fn print_stats(
+    matches: &ArgMatches,
+    options: &Options,
+    input: Counts,
+    data: Counts,
+    output: Counts,
+    start: Instant,
+) {
+    let decoding = matches.get_flag("decode");
+    let encoded = if decoding { input } else { output };
+    let chunked = !matches.get_flag("lines");
+    stats::print(options, decoding, data, encoded, chunked, start.elapsed());
+}
+
+/// Prints the digest of the unencoded data, if requested, to the standard error.
+fn print_digest<T>(options: &Options, algorithm: Option<Algorithm>, hasher: &Hasher<T>) {
+    let (algorithm, digest) = match (algorithm, hasher.digest()) {
+        (Some(algorithm), Some(digest)) => (algorithm, digest),
+        _ => return,
+    };
+    let path = options.path.filter(|&path| path != "-");
+    if options.json {
+        let report = json::Object::new()
+            .field("file", &path)
+            .field(algorithm.name(), &digest);
+        eprintln!("{}", report.to_json());
+    } else {
+        match path {
+            Some(path) => eprintln!("ecoji: {}: {} {}", path, algorithm.name(), digest),
+            None => eprintln!("ecoji: {} {}", algorithm.name(), digest),
+        }
+    }
+}
+
+/// Processes every input separately, like gzip does: when encoding, the output for `a.bin` is
+/// written to `a.bin.ecoji`, and when decoding, the output for `a.bin.ecoji` is written to
+/// `a.bin`. The standard input, given as `-`, is processed to the standard output.
+///
+/// Carries on with the remaining inputs if one of them fails, and returns the last error code.
+fn each(matches: &ArgMatches, options: &Options, paths: &[&str], mmap: bool) -> i32 {
+    let mut code = 0;
+    for &path in paths {
+        let options = Options {
+            path: Some(path),
+            ..*options
+        };
+        match each_one(matches, &options, path, mmap) {
+            0 => {}
+            failed => code = failed,
+        }
+    }
+    code
+}
+
+fn each_one(matches: &ArgMatches, options: &Options, path: &str, mmap: bool) -> i32 {
+    let decoding = matches.get_flag("decode");
+    let force = matches.get_flag("force");
+    let start = Instant::now();
+
+    let source = match open_inputs(&[path], mmap, options.buffer_size) {
+        Ok(source) => source,
+        Err((path, e)) => {
+            eprintln!("ecoji: {}: {}", path, e);
+            return EX_NOINPUT;
+        }
+    };
+
+    if path == "-" {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        let mut guarded;
+        let destination: &mut dyn Write = if decoding && !force && stdout.is_terminal() {
+            guarded = TextOnly::new(&mut stdout);
+            &mut guarded
+        } else {
+            &mut stdout
+        };
+        let mut output = Meter::new(destination);
+        let (input, data, code) = process(matches, options, source, &mut output);
+        if code == 0 && matches.get_flag("verbose") {
+            print_stats(matches, options, input, data, output.counts(), start);
+        }
+        return code;
+    }
+
+    let target = if decoding {
+        match path.strip_suffix(SUFFIX) {
+            Some(target) if !target.is_empty() => target.to_owned(),
+            _ => {
+                eprintln!("ecoji: {}: unknown suffix, expected {}", path, SUFFIX);
+                return EX_USAGE;
+            }
+        }
+    } else {
+        format!("{}{}", path, SUFFIX)
+    };
+    if !force && fs::symlink_metadata(&target).is_ok() {
+        eprintln!(
+            "ecoji: {}: already exists; use --force to overwrite it",
+            target
+        );
+        return EX_CANTCREAT;
+    }
+    let mut file = match File::create(&target) {
+        Ok(file) => BufWriter::with_capacity(options.buffer_size, file),
+        Err(e) => {
+            eprintln!("ecoji: {}: {}", target, e);
+            return EX_CANTCREAT;
+        }
+    };
+
+    let mut output = Meter::new(&mut file as &mut dyn Write);
+    let (input, data, code) = process(matches, options, source, &mut output);
+    let output = output.counts();
+    drop(file);
+    if code != 0 {
+        // Don't leave incomplete output behind
+        let _ = fs::remove_file(&target);
+        return code;
+    }
+    if matches.get_flag("verbose") {
+        eprintln!("ecoji: {} -> {}", path, target);
+        print_stats(matches, options, input, data, output, start);
+    }
+    0
+}
+
+fn encode<R: Read, W: Write>(
+    options: &Options,
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    if options.blocks {
+        options.version.encode_parallel(source, destination)
+    } else {
+        options.version.encode(source, destination)
+    }
+}
+
+/// The default prefix of the files written with `--split`: the name of the input file followed
+/// by a dot, in the current directory.
+fn split_prefix(path: Option<&str>) -> String {
+    let name = path
+        .filter(|&path| path != "-")
+        .and_then(|path| Path::new(path).file_name())
+        .map_or("ecoji".into(), |name| name.to_string_lossy());
+    format!("{}.", name)
+}
+
+fn decode<R: Read, W: Write>(options: &Options, source: R, mut destination: W) -> i32 {
+    let mut source = Tracker::new(source);
+    let result = if options.blocks {
+        options
+            .codec()
+            .decode_parallel(&mut source, &mut destination)
+    } else {
+        options.decode(&mut source, &mut destination)
+    };
+    if let Some(e) = result.as_ref().err().and_then(DecodeError::from_io) {
+        if options.json {
+            let location = source
+                .location(e)
+                .or_else(|| locate(options, e).and_then(|source| source.location(e)));
+            eprintln!("{}", json::decode_error(e, location).to_json());
+        } else {
+            eprintln!("ecoji: {}", e);
+            let description = source
+                .describe(e)
+                .or_else(|| locate(options, e).and_then(|mut source| source.describe(e)));
+            if let Some(description) = description {
+                eprint!("{}", description);
+            }
+        }
+        return data_error_code(e);
+    }
+    report(result.and_then(|_| destination.flush()))
+}
+
+/// Finds the location of an error found by the parallel decoder, which reads too far ahead for
+/// the tracker to know it, by decoding the input file again sequentially. Returns the tracker
+/// stopped at the error.
+fn locate(options: &Options, e: &DecodeError) -> Option<Tracker<Box<dyn Read>>> {
+    if !is_file(options.path) {
+        return None;
+    }
+    let source = open_input(options.path).ok()?;
+    let source: Box<dyn Read> = if options.ignore_garbage {
+        Box::new(GarbageFilter::new(source))
+    } else {
+        source
+    };
+    let mut source = Tracker::new(source);
+    let result = options.decode(&mut source, &mut io::sink());
+    match result.as_ref().err().and_then(DecodeError::from_io) {
+        Some(found) if found == e => Some(source),
+        _ => None,
+    }
+}
+
+/// Prints the error, if any, to the standard error and returns the corresponding exit code.
+///
+/// A closed output pipe is not considered worth a message.
+fn report<T>(result: io::Result<T>) -> i32 {
+    match result {
+        Ok(_) => 0,
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => EX_SIGPIPE,
+        Err(e) => {
+            eprintln!("ecoji: {}", e);
+            exit_code(&e)
+        }
+    }
+}
+
+fn exit_code(e: &io::Error) -> i32 {
+    if BinaryOutput::from_io(e).is_some() {
+        return EX_USAGE;
+    }
+    match DecodeError::from_io(e) {
+        Some(e) => data_error_code(e),
+        None if e.kind() == io::ErrorKind::InvalidData => EX_DATAERR,
+        None => EX_IOERR,
+    }
+}
+
+/// Returns the exit code for a decoding error, which tells input rejected only as not canonical
+/// apart from undecodable input.
+fn data_error_code(e: &DecodeError) -> i32 {
+    match e.kind() {
+        DecodeErrorKind::NotCanonical(_) => EX_NOT_CANONICAL,
+        _ => EX_DATAERR,
+    }
+}
+
+/// Checks whether the input is a regular file, as opposed to the standard input or a pipe.
+fn is_file(path: Option<&str>) -> bool {
+    match path {
+        None | Some("-") => false,
+        Some(path) => fs::metadata(path).is_ok_and(|m| m.is_file()),
+    }
+}
+
+/// Opens the given input files, to be read one after another, or the standard input if there are
+/// none, reading files through buffers of the given size unless they are mapped into memory.
+/// Returns the path of the file which couldn't be opened on failure.
+fn open_inputs<'a>(
+    paths: &[&'a str],
+    mmap: bool,
+    buffer_size: usize,
+) -> Result<Box<dyn Read>, (&'a str, io::Error)> {
+    let open = |path| {
+        let result = if mmap {
+            map_input(path, buffer_size)
+        } else {
+            open_buffered(Some(path), buffer_size)
+        };
+        result.map_err(|e| (path, e))
+    };
+    if let [path] = *paths {
+        return open(path);
+    }
+    if paths.is_empty() {
+        return open_input(None).map_err(|e| ("-", e));
+    }
+    let inputs = paths
+        .iter()
+        .map(|&path| open(path))
+        .collect::<Result<_, _>>()?;
+    Ok(Box::new(Concat { inputs }))
+}
+
+/// A reader yielding the contents of several readers one after another.
+struct Concat {
+    inputs: VecDeque<Box<dyn Read>>,
+}
+
+impl Read for Concat {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(input) = self.inputs.front_mut() {
+            match input.read(buf)? {
+                0 if !buf.is_empty() => {
+                    self.inputs.pop_front();
+                }
+                n => return Ok(n),
+            }
+        }
+        Ok(0)
+    }
+}
+
+/// Maps the given input file into memory, so that it is read without copying it through buffers,
+/// or opens it as usual if it is not a regular file.
+fn map_input(path: &str, buffer_size: usize) -> io::Result<Box<dyn Read>> {
+    if !is_file(Some(path)) {
+        return open_buffered(Some(path), buffer_size);
+    }
+    let file = File::open(path)?;
+    // The mapping is only read from. As with any memory-mapped input, the program may be
+    // terminated if the file is truncated by another process at the same time.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(Box::new(io::Cursor::new(map)))
+}
+
+/// Opens the given input file, or the standard input if the path is absent or `-`.
+fn open_input(path: Option<&str>) -> io::Result<Box<dyn Read>> {
+    open_buffered(path, DEFAULT_BUFFER_SIZE)
+}
+
+/// Opens the given input file to be read through a buffer of the given size, or the standard
+/// input if the path is absent or `-`.
+fn open_buffered(path: Option<&str>, buffer_size: usize) -> io::Result<Box<dyn Read>> {
+    match path {
+        None | Some("-") => Ok(Box::new(io::stdin())),
+        Some(path) => Ok(Box::new(BufReader::with_capacity(
+            buffer_size,
+            File::open(path)?,
+        ))),
+    }
+}
+
+/// Parses the size of the input and output buffers, which must not be zero.
+fn parse_buffer_size(s: &str) -> Result<usize, String> {
+    match split::parse_bytes(s)? {
+        0 => Err("size must not be zero".to_owned()),
+        size => usize::try_from(size).map_err(|_| format!("size '{}' is too large", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_cli() {
+        cli().debug_assert();
+    }
+
+    #[test]
+    fn test_binary_output_is_usage_error() {
+        let e = TextOnly::new(io::sink()).write_all(b"\x00").unwrap_err();
+        assert_eq!(exit_code(&e), EX_USAGE);
+        let e = io::Error::new(io::ErrorKind::InvalidData, "other");
+        assert_eq!(exit_code(&e), EX_DATAERR);
+    }
+}
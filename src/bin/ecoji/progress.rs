@@ -0,0 +1,32 @@
+//! Progress reporting for large inputs.
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+
+/// Wraps the source into a reader drawing a progress bar on the standard error, if the standard
+/// error is a terminal and the source is a regular file whose size is known in advance.
+pub fn track(source: Box<dyn Read>, path: Option<&str>) -> Box<dyn Read> {
+    let size = match path {
+        None | Some("-") => None,
+        Some(path) => fs::metadata(path)
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len()),
+    };
+
+    match size {
+        Some(size) if io::stderr().is_terminal() => {
+            let style = ProgressStyle::with_template(
+                "{bar:40} {bytes}/{total_bytes} ({binary_bytes_per_sec}, {eta} left)",
+            )
+            .unwrap();
+            let bar = ProgressBar::new(size)
+                .with_style(style)
+                .with_finish(ProgressFinish::AndClear);
+            Box::new(bar.wrap_read(source))
+        }
+        _ => source,
+    }
+}
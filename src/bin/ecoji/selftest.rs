@@ -0,0 +1,94 @@
+use std::io::{self, Write};
+
+use clap::{ArgMatches, Command};
+use ecoji::emojis::{Version, VERSIONS};
+
+/// Known encodings: the input and its encodings with versions 1 and 2 respectively.
+///
+/// Covers every length of the final chunk, including the four special padding characters of
+/// 4-byte chunks, as well as the examples from the documentation of Ecoji.
+const VECTORS: &[(&[u8], &str, &str)] = &[
+    (b"", "", ""),
+    (b"a", "👕☕☕☕", "👕☕"),
+    (b"ab", "👖📲☕☕", "👖📲☕"),
+    (b"abc", "👖📸🎈☕", "👖📸🎈☕"),
+    (b"abcd", "👖📸🎦⚜", "👖📸🧈🥷"),
+    (b"abcde", "👖📸🎦🌭", "👖📸🧈🌭"),
+    (b"input data", "👶😲🇲👅🍉🔙🌥🌩", "👶😲⛵👅🍉🧴🦪🦮"),
+    (
+        b"Base1024 emoji encoding is awesome.",
+        "🏗📩🎦🐂🎈🚟🏖🌭💆🙁🖖🏮👦😱🎨🤑👶😱💙🌱💝📘🌥🤒💞🙁🛫🏿",
+        "🧏📩🧈🐂🎈🚟🧎🌭💆🙁🖖🏮👦😱🎨🤑👶😱💙🌱💝📘🦪🤒💞🙁🛫🧧",
+    ),
+    (&[0, 0, 0, 0], "🀄🀄🀄⚜", "🀄🀄🀄🥷"),
+    (&[0, 0, 0, 1], "🀄🀄🀄🏍", "🀄🀄🀄🛼"),
+    (&[0, 0, 0, 2], "🀄🀄🀄📑", "🀄🀄🀄📑"),
+    (&[0, 0, 0, 3], "🀄🀄🀄🙋", "🀄🀄🀄🙋"),
+    (&[0, 0, 0, 0, 0], "🀄🀄🀄🀄", "🀄🀄🀄🀄"),
+    (&[0xff; 5], "🧕🧕🧕🧕", "🧕🧕🧕🧕"),
+    (&[0xff; 10], "🧕🧕🧕🧕🧕🧕🧕🧕", "🧕🧕🧕🧕🧕🧕🧕🧕"),
+];
+
+/// Exit code for a failed self-test, `EX_SOFTWARE` of `sysexits.h`.
+const EX_SOFTWARE: i32 = 70;
+
+pub fn command() -> Command {
+    Command::new("self-test")
+        .about("Check the encoder and decoder against the built-in table of known encodings")
+        .long_about(
+            "Check the encoder and decoder against the built-in table of known encodings.\n\
+             Every input is encoded and the result decoded back with both versions, and the\n\
+             outcome of each check is printed. The exit code is non-zero if any check fails.",
+        )
+}
+
+pub fn run(_matches: &ArgMatches) -> i32 {
+    let mut report = String::new();
+    let mut failed = 0;
+    for &(input, v1, v2) in VECTORS {
+        for (v, expected) in VERSIONS.iter().zip([v1, v2]) {
+            match check(v, input, expected) {
+                Ok(()) => report += &format!("ok   v{} {}\n", v.VERSION_NUMBER, name(input)),
+                Err(problem) => {
+                    failed += 1;
+                    report += &format!("FAIL v{} {}: {}\n", v.VERSION_NUMBER, name(input), problem);
+                }
+            }
+        }
+    }
+    let total = VECTORS.len() * VERSIONS.len();
+    report += &format!("{} passed, {} failed\n", total - failed, failed);
+
+    match super::report(io::stdout().write_all(report.as_bytes())) {
+        0 if failed > 0 => EX_SOFTWARE,
+        code => code,
+    }
+}
+
+fn check(v: &Version, input: &[u8], expected: &str) -> Result<(), String> {
+    let encoded = v
+        .encode_to_string(&mut &input[..])
+        .map_err(|e| format!("encoding failed: {}", e))?;
+    if encoded != expected {
+        return Err(format!("encoded as {}, expected {}", encoded, expected));
+    }
+    let decoded = v
+        .decode_to_vec(&mut expected.as_bytes())
+        .map_err(|e| format!("decoding failed: {}", e))?;
+    if decoded != input {
+        return Err(format!("decoded as {:?}, expected {:?}", decoded, input));
+    }
+    Ok(())
+}
+
+/// Shows the input as quoted text if possible, or as hexadecimal bytes otherwise.
+fn name(input: &[u8]) -> String {
+    match std::str::from_utf8(input) {
+        Ok(s) if !s.chars().any(char::is_control) => format!("{:?}", s),
+        _ => input
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
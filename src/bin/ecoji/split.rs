@@ -0,0 +1,161 @@
+//! Splitting of the encoded output into several files.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use ecoji::{LineWidth, LineWrapper};
+
+/// Number of symbols in an encoded chunk; the output is only ever split between chunks.
+const GROUP: usize = 4;
+
+/// Upper bound of the number of line breaks the wrapper can add around a chunk: one before each
+/// symbol, plus the one terminating the file.
+const SLACK: u64 = GROUP as u64 + 1;
+
+/// The smallest size of a file which can hold a chunk: four 4-byte symbols, plus the line breaks.
+pub const MIN_SIZE: u64 = GROUP as u64 * 4 + SLACK;
+
+/// Parses a size in bytes: a number, optionally followed by one of the `K`, `M` and `G` binary
+/// suffixes.
+pub fn parse_bytes(s: &str) -> Result<u64, String> {
+    let (digits, multiplier) = match s.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => match c.to_ascii_uppercase() {
+            'K' => (&s[..i], 1 << 10),
+            'M' => (&s[..i], 1 << 20),
+            'G' => (&s[..i], 1 << 30),
+            _ => return Err(format!("unknown size suffix '{}'", c)),
+        },
+        _ => (s, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}'", s))
+}
+
+/// Parses the size of the files to split the output into, which must be able to hold a chunk.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let size = parse_bytes(s)?;
+    if size < MIN_SIZE {
+        return Err(format!("size must be at least {} bytes", MIN_SIZE));
+    }
+    Ok(size)
+}
+
+/// A writer which distributes the encoded data written to it among files named with the given
+/// prefix followed by a sequential number, starting a new file whenever the next chunk wouldn't
+/// fit into the current one. Every file is wrapped separately, so that each of them is a valid
+/// encoded stream on its own.
+pub struct Splitter {
+    prefix: String,
+    limit: u64,
+    wrap: LineWidth,
+    buffer_size: usize,
+    files: usize,
+    current: Option<LineWrapper<Counter<BufWriter<File>>>>,
+    group: Vec<u8>,
+    symbols: usize,
+}
+
+impl Splitter {
+    pub fn new(prefix: String, limit: u64, wrap: LineWidth, buffer_size: usize) -> Splitter {
+        Splitter {
+            prefix,
+            limit,
+            wrap,
+            buffer_size,
+            files: 0,
+            current: None,
+            group: Vec::with_capacity(GROUP * 4),
+            symbols: 0,
+        }
+    }
+
+    /// Writes the last chunk and closes the last file. At least one file is always created, even
+    /// if nothing was written.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.write_group()?;
+        let current = match self.current.take() {
+            Some(current) => current,
+            None => self.create()?,
+        };
+        current.finish()?.inner.flush()
+    }
+
+    fn create(&mut self) -> io::Result<LineWrapper<Counter<BufWriter<File>>>> {
+        let name = format!("{}{:03}", self.prefix, self.files);
+        self.files += 1;
+        let file = File::create(&name)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", name, e)))?;
+        Ok(LineWrapper::new(
+            Counter {
+                inner: BufWriter::with_capacity(self.buffer_size, file),
+                written: 0,
+            },
+            self.wrap,
+        ))
+    }
+
+    fn write_group(&mut self) -> io::Result<()> {
+        if self.group.is_empty() {
+            return Ok(());
+        }
+        let slack = if self.wrap.limit() > 0 { SLACK } else { 0 };
+        let size = self.group.len() as u64 + slack;
+        if let Some(ref current) = self.current {
+            if current.get_ref().written + size > self.limit {
+                let current = self.current.take().unwrap();
+                current.finish()?.inner.flush()?;
+            }
+        }
+        if self.current.is_none() {
+            self.current = Some(self.create()?);
+        }
+        self.current.as_mut().unwrap().write_all(&self.group)?;
+        self.group.clear();
+        self.symbols = 0;
+        Ok(())
+    }
+}
+
+impl Write for Splitter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &b in buf {
+            // The first byte of a symbol following a complete group starts a new one
+            if b & 0xc0 != 0x80 {
+                if self.symbols == GROUP {
+                    self.write_group()?;
+                }
+                self.symbols += 1;
+            }
+            self.group.push(b);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.current {
+            Some(ref mut current) => current.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A writer adapter counting the bytes written through it.
+struct Counter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
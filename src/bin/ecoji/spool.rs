@@ -0,0 +1,88 @@
+//! Holding back the decoded output until the whole input has turned out to be valid.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// A writer adapter which keeps everything written to it, in memory up to the given limit and in
+/// a temporary file beyond it, and writes it to the inner writer only once committed. If it is
+/// dropped instead, nothing is written and the temporary file is removed.
+pub struct Spool<W: Write> {
+    inner: W,
+    memory: Vec<u8>,
+    limit: usize,
+    file: Option<BufWriter<File>>,
+    path: Option<PathBuf>,
+}
+
+impl<W: Write> Spool<W> {
+    pub fn new(inner: W, limit: usize) -> Spool<W> {
+        Spool {
+            inner,
+            memory: Vec::new(),
+            limit,
+            file: None,
+            path: None,
+        }
+    }
+
+    /// Writes everything spooled so far to the inner writer and flushes it.
+    pub fn commit(mut self) -> io::Result<()> {
+        match self.file.take() {
+            Some(file) => {
+                let mut file = file.into_inner().map_err(io::IntoInnerError::into_error)?;
+                file.seek(SeekFrom::Start(0))?;
+                io::copy(&mut file, &mut self.inner)?;
+            }
+            None => self.inner.write_all(&self.memory)?,
+        }
+        self.inner.flush()
+    }
+
+    /// Moves the data spooled in memory to a new temporary file.
+    fn spill(&mut self) -> io::Result<&mut BufWriter<File>> {
+        let path = env::temp_dir().join(format!("ecoji-spool-{}", process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        self.path = Some(path);
+        let mut file = BufWriter::new(file);
+        file.write_all(&self.memory)?;
+        self.memory = Vec::new();
+        Ok(self.file.insert(file))
+    }
+}
+
+impl<W: Write> Write for Spool<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let file = match self.file {
+            Some(ref mut file) => file,
+            None if self.memory.len() + buf.len() <= self.limit => {
+                self.memory.extend_from_slice(buf);
+                return Ok(buf.len());
+            }
+            None => self.spill()?,
+        };
+        file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    /// Does nothing, since nothing may reach the inner writer before the spool is committed.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for Spool<W> {
+    fn drop(&mut self) {
+        self.file = None;
+        if let Some(ref path) = self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Statistics printed in the verbose mode.
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use ecoji::LineWidth;
+
+use crate::Options;
+
+/// Amounts of data passed through a [`Meter`].
+#[derive(Clone, Copy, Default)]
+pub struct Counts {
+    pub bytes: u64,
+    /// Number of UTF-8 characters other than line breaks, which is the number of symbols in
+    /// encoded data.
+    pub symbols: u64,
+}
+
+/// A reader or writer adapter counting the bytes and symbols passed through it.
+pub struct Meter<T> {
+    inner: T,
+    counts: Counts,
+}
+
+impl<T> Meter<T> {
+    pub fn new(inner: T) -> Meter<T> {
+        Meter {
+            inner,
+            counts: Counts::default(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn counts(&self) -> Counts {
+        self.counts
+    }
+
+    fn count(&mut self, buf: &[u8]) {
+        self.counts.bytes += buf.len() as u64;
+        self.counts.symbols += buf
+            .iter()
+            .filter(|&&b| b & 0xc0 != 0x80 && b != b'\n' && b != b'\r')
+            .count() as u64;
+    }
+}
+
+impl<R: Read> Read for Meter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Meter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Prints the settings in effect and the statistics of the finished operation to the standard
+/// error. `data` and `encoded` are the counts on the unencoded and the encoded side respectively;
+/// `chunked` tells whether the data was encoded as a whole, rather than line by line, so that
+/// its last chunk is meaningful.
+pub fn print(
+    options: &Options,
+    decoding: bool,
+    data: Counts,
+    encoded: Counts,
+    chunked: bool,
+    elapsed: Duration,
+) {
+    let mut settings = vec![
+        format!("version {}", options.version.VERSION_NUMBER),
+        plural(options.threads as u64, "thread"),
+    ];
+    if decoding {
+        if options.ignore_garbage {
+            settings.push("ignoring garbage".to_owned());
+        }
+    } else {
+        match options.wrap {
+            LineWidth::Symbols(0) => {}
+            LineWidth::Symbols(n) => settings.push(format!("wrapping at {} symbols", n)),
+            LineWidth::Columns(n) => settings.push(format!("wrapping at {} columns", n)),
+        }
+    }
+    eprintln!("ecoji: {}", settings.join(", "));
+
+    let data_line = plural(data.bytes, "byte");
+    let encoded_line = format!(
+        "{} ({})",
+        plural(encoded.symbols, "symbol"),
+        plural(encoded.bytes, "byte")
+    );
+    if decoding {
+        eprintln!("ecoji: read {}", encoded_line);
+        eprintln!("ecoji: wrote {}", data_line);
+    } else {
+        eprintln!("ecoji: read {}", data_line);
+        eprintln!("ecoji: wrote {}", encoded_line);
+    }
+
+    if chunked {
+        let last = (data.bytes % 5) as usize;
+        if last == 0 {
+            eprintln!("ecoji: no padding, all chunks are complete");
+        } else {
+            let padding = match options.version.VERSION_NUMBER {
+                1 => [0, 3, 2, 1, 1][last],
+                _ => 1,
+            };
+            eprintln!(
+                "ecoji: last chunk of {}, {}",
+                plural(last as u64, "byte"),
+                plural(padding, "padding symbol")
+            );
+        }
+    }
+
+    let seconds = elapsed.as_secs_f64();
+    let throughput = if seconds > 0.0 {
+        format!(", {:.1} MB/s", data.bytes as f64 / seconds / 1e6)
+    } else {
+        String::new()
+    };
+    eprintln!("ecoji: took {:.3} s{}", seconds, throughput);
+}
+
+/// Describes the sizes of the input and the output for `--dry-run`.
+pub fn summary(decoding: bool, input: Counts, output: Counts) -> String {
+    let encoded = |counts: Counts| {
+        format!(
+            "{}, {}",
+            plural(counts.symbols, "symbol"),
+            plural(counts.bytes, "byte")
+        )
+    };
+    if decoding {
+        format!(
+            "input: {}\noutput: {}\n",
+            encoded(input),
+            plural(output.bytes, "byte")
+        )
+    } else {
+        format!(
+            "input: {}\noutput: {}\n",
+            plural(input.bytes, "byte"),
+            encoded(output)
+        )
+    }
+}
+
+fn plural(n: u64, noun: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
@@ -0,0 +1,28 @@
+//! Copying the input to a file while it is being processed.
+
+use std::io::{self, Read, Write};
+
+/// A reader adapter which writes everything read through it to another writer, which is flushed
+/// when the end of the input is reached.
+pub struct Tee<R, W> {
+    inner: R,
+    copy: W,
+}
+
+impl<R: Read, W: Write> Tee<R, W> {
+    pub fn new(inner: R, copy: W) -> Tee<R, W> {
+        Tee { inner, copy }
+    }
+}
+
+impl<R: Read, W: Write> Read for Tee<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.copy.flush()?;
+        } else {
+            self.copy.write_all(&buf[..n])?;
+        }
+        Ok(n)
+    }
+}
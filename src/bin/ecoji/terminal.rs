@@ -0,0 +1,106 @@
+//! Protection of terminals from binary output.
+
+use std::error;
+use std::fmt;
+use std::io::{self, Write};
+use std::str;
+
+/// A writer adapter which passes through only text, failing as soon as the data written to it
+/// turns out to be binary: not valid UTF-8, or containing control characters other than
+/// whitespace, which could mess up the state of the terminal.
+pub struct TextOnly<W> {
+    inner: W,
+    /// An incomplete UTF-8 sequence at the end of the last write.
+    carry: Vec<u8>,
+}
+
+impl<W: Write> TextOnly<W> {
+    pub fn new(inner: W) -> TextOnly<W> {
+        TextOnly {
+            inner,
+            carry: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for TextOnly<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = std::mem::take(&mut self.carry);
+        data.extend_from_slice(buf);
+        let valid = match str::from_utf8(&data) {
+            Ok(text) => text,
+            // An incomplete sequence at the end may be completed by the next write
+            Err(e) if e.error_len().is_none() => {
+                self.carry = data[e.valid_up_to()..].to_vec();
+                str::from_utf8(&data[..e.valid_up_to()]).unwrap()
+            }
+            Err(_) => return Err(binary()),
+        };
+        if valid
+            .chars()
+            .any(|c| c.is_control() && !c.is_ascii_whitespace())
+        {
+            return Err(binary());
+        }
+        self.inner.write_all(valid.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    /// Fails if the data ends with an incomplete character, since the output is only flushed
+    /// once all of it is written.
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.carry.is_empty() {
+            return Err(binary());
+        }
+        self.inner.flush()
+    }
+}
+
+/// The error of [`TextOnly`] when the data turns out to be binary, which is a usage error: the
+/// output has to be redirected.
+#[derive(Debug)]
+pub struct BinaryOutput;
+
+impl BinaryOutput {
+    /// Returns the `BinaryOutput` wrapped in the given I/O error, if any.
+    pub fn from_io(e: &io::Error) -> Option<&BinaryOutput> {
+        e.get_ref().and_then(|e| e.downcast_ref())
+    }
+}
+
+impl error::Error for BinaryOutput {}
+
+impl fmt::Display for BinaryOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(
+            "refusing to write binary data to the terminal; use -o FILE or redirect the output \
+             to write it to a file, or --force to write it anyway",
+        )
+    }
+}
+
+fn binary() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, BinaryOutput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_output() {
+        let mut output = Vec::new();
+        let mut text = TextOnly::new(&mut output);
+        text.write_all("abc\n\u{1F44D}".as_bytes()).unwrap();
+        text.write_all(b"\xF0\x9F").unwrap();
+        let e = text.flush().unwrap_err();
+        assert!(BinaryOutput::from_io(&e).is_some());
+
+        let e = TextOnly::new(Vec::new()).write_all(b"\x1B[2J").unwrap_err();
+        assert!(BinaryOutput::from_io(&e).is_some());
+        let e = TextOnly::new(Vec::new()).write_all(b"\xFF").unwrap_err();
+        assert!(BinaryOutput::from_io(&e).is_some());
+        assert!(e.to_string().contains("-o FILE or redirect the output"));
+        assert_eq!(output, "abc\n\u{1F44D}".as_bytes());
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::{self, Write};
+
+use clap::{arg, ArgMatches, Command, ValueHint};
+use ecoji::{DecodeError, EncoderWriter, LineWidth, LineWrapper, VERSION1, VERSION2};
+
+use crate::diagnostics::Tracker;
+use crate::wrap::parse_width;
+
+pub fn command() -> Command {
+    Command::new("transcode")
+        .about("Convert Ecoji-encoded data to another version of the alphabet")
+        .long_about(
+            "Convert Ecoji-encoded data to another version of the alphabet, which is the same as\n\
+             decoding it and encoding the result again, but without an intermediate pipe.\n\
+             The version of the input is detected automatically.",
+        )
+        .arg(
+            arg!(--to <VERSION> "Version to convert the data to")
+                .value_parser(["v1", "v2"])
+                .required(true),
+        )
+        .arg(
+            arg!(-w --wrap <COLS> "Wrap lines after COLS emojis, or at the terminal width with `auto`; 0 disables wrapping (default)")
+                .value_parser(parse_width),
+        )
+        .arg(
+            arg!([FILE] "Input file; standard input is used if omitted or '-'")
+                .value_hint(ValueHint::FilePath),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> i32 {
+    let path = matches.get_one::<String>("FILE").map(String::as_str);
+    let target = match matches.get_one::<String>("to").map(String::as_str) {
+        Some("v2") => &VERSION2,
+        _ => &VERSION1,
+    };
+    let wrap = matches
+        .get_one::<LineWidth>("wrap")
+        .cloned()
+        .unwrap_or(LineWidth::Symbols(0));
+
+    let source = match super::open_input(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("ecoji: {}: {}", path.unwrap_or("-"), e);
+            return super::EX_NOINPUT;
+        }
+    };
+    let mut source = Tracker::new(source);
+
+    let stdout = io::stdout();
+    let mut destination = EncoderWriter::new(target, LineWrapper::new(stdout.lock(), wrap));
+    // The decoder starts with the first version and switches to the second one by itself
+    let result = super::decoder(&VERSION1, false).decode(&mut source, &mut destination);
+    if let Some(e) = result.as_ref().err().and_then(DecodeError::from_io) {
+        eprintln!("ecoji: {}", e);
+        if let Some(description) = source.describe(e) {
+            eprint!("{}", description);
+        }
+        return super::EX_DATAERR;
+    }
+    super::report(
+        result
+            .and_then(|_| destination.finish())
+            .and_then(|wrapper| wrapper.finish())
+            .and_then(|mut stdout| stdout.flush()),
+    )
+}
@@ -0,0 +1,26 @@
+//! Line widths for wrapping the encoded output.
+
+use std::io;
+
+use ecoji::LineWidth;
+use terminal_size::terminal_size_of;
+
+/// Line width used by `--wrap auto` when the standard output is not a terminal.
+const DEFAULT_COLUMNS: usize = 76;
+
+/// Parses a line width: either a number of symbols, or `auto` for the width of the terminal
+/// connected to the standard output.
+pub fn parse_width(s: &str) -> Result<LineWidth, String> {
+    if s == "auto" {
+        return Ok(LineWidth::Columns(terminal_columns()));
+    }
+    s.parse()
+        .map(LineWidth::Symbols)
+        .map_err(|_| format!("invalid line width '{}'", s))
+}
+
+/// Returns the number of columns of the terminal connected to the standard output, or
+/// [`DEFAULT_COLUMNS`] if it isn't a terminal.
+fn terminal_columns() -> usize {
+    terminal_size_of(io::stdout()).map_or(DEFAULT_COLUMNS, |(width, _)| width.0 as usize)
+}
@@ -9,11 +9,47 @@ use std::str;
 #[derive(Debug)]
 pub struct Chars<R> {
     inner: R,
+    offset: usize,
+    position: usize,
 }
 
 impl<R> Chars<R> {
     pub fn new(inner: R) -> Chars<R> {
-        Chars { inner }
+        Chars {
+            inner,
+            offset: 0,
+            position: 0,
+        }
+    }
+
+    /// Continues reading at the given location in the whole input, from which the underlying
+    /// reader starts.
+    pub fn resumed(inner: R, offset: usize, position: usize) -> Chars<R> {
+        Chars {
+            inner,
+            offset,
+            position,
+        }
+    }
+
+    /// Number of bytes consumed from the underlying reader so far.
+    pub fn bytes_read(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of characters returned so far.
+    pub fn chars_read(&self) -> usize {
+        self.position
+    }
+
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
     }
 }
 
@@ -23,12 +59,6 @@ pub enum CharsError {
     Other(Error),
 }
 
-impl CharsError {
-    pub fn into_io(self) -> Error {
-        Error::new(ErrorKind::InvalidData, self)
-    }
-}
-
 impl<R: Read> Iterator for Chars<R> {
     type Item = result::Result<char, CharsError>;
 
@@ -37,8 +67,10 @@ impl<R: Read> Iterator for Chars<R> {
             Ok(b) => b,
             Err(e) => return Some(Err(CharsError::Other(e))),
         };
+        self.offset += 1;
         let width = utf8_char_width(first_byte);
         if width == 1 {
+            self.position += 1;
             return Some(Ok(first_byte as char));
         }
         if width == 0 {
@@ -50,14 +82,20 @@ impl<R: Read> Iterator for Chars<R> {
             while start < width {
                 match self.inner.read(&mut buf[start..width]) {
                     Ok(0) => return Some(Err(CharsError::NotUtf8)),
-                    Ok(n) => start += n,
+                    Ok(n) => {
+                        start += n;
+                        self.offset += n;
+                    }
                     Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
                     Err(e) => return Some(Err(CharsError::Other(e))),
                 }
             }
         }
         Some(match str::from_utf8(&buf[..width]).ok() {
-            Some(s) => Ok(s.chars().next().unwrap()),
+            Some(s) => {
+                self.position += 1;
+                Ok(s.chars().next().unwrap())
+            }
             None => Err(CharsError::NotUtf8),
         })
     }
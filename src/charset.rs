@@ -0,0 +1,122 @@
+//! Encoding of text in other character encodings, converted from and to UTF-8.
+
+use std::io;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+
+use crate::emojis::Version;
+
+impl Version {
+    /// Converts the text in the character encoding with the given label, like `latin1` or
+    /// `utf-16le`, to UTF-8 and encodes the UTF-8 bytes, so that the decoded data is the same text
+    /// regardless of how the input was stored. A byte order mark matching the encoding is
+    /// removed. The labels are those of the [WHATWG Encoding Standard](https://encoding.spec.whatwg.org/),
+    /// where e.g. `latin1` stands for windows-1252.
+    ///
+    /// Returns an error of the `io::ErrorKind::InvalidInput` kind if the label is unknown, and of
+    /// the `io::ErrorKind::InvalidData` kind if the input is not valid in the encoding.
+    ///
+    /// Requires the `encoding` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let utf16: Vec<u8> = "añb".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    /// let encoded = ecoji::VERSION2.encode_text(&utf16, "utf-16le")?;
+    ///
+    /// assert_eq!(encoded, ecoji::encode_bytes_with(&ecoji::VERSION2, "añb"));
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn encode_text(&self, input: &[u8], label: &str) -> io::Result<String> {
+        let encoding = lookup(label)?;
+        let (text, had_errors) = encoding.decode_with_bom_removal(input);
+        if had_errors {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Input is not valid {} text", encoding.name()),
+            ));
+        }
+        self.encode_to_string(&mut text.as_bytes())
+    }
+
+    /// Decodes the input like [`decode_to_string`](#method.decode_to_string) and converts the
+    /// decoded UTF-8 text to the character encoding with the given label, the inverse of
+    /// [`encode_text`](#method.encode_text).
+    ///
+    /// Besides the failure conditions of `decode_to_string`, returns an error of the
+    /// `io::ErrorKind::InvalidInput` kind if the label is unknown, and of the
+    /// `io::ErrorKind::InvalidData` kind if the text cannot be represented in the encoding.
+    ///
+    /// Requires the `encoding` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let encoded = ecoji::encode_bytes("añb");
+    ///
+    /// assert_eq!(ecoji::VERSION1.decode_text(&encoded, "latin1")?, b"a\xf1b");
+    /// assert!(ecoji::VERSION1.decode_text(&ecoji::encode_bytes("ā"), "latin1").is_err());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_text(&self, input: &str, label: &str) -> io::Result<Vec<u8>> {
+        let encoding = lookup(label)?;
+        let text = self.decode_to_string(&mut input.as_bytes())?;
+        // encoding_rs only decodes UTF-16, its encoders produce UTF-8 instead
+        if encoding == UTF_16LE {
+            return Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect());
+        }
+        if encoding == UTF_16BE {
+            return Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect());
+        }
+        let (bytes, _, had_errors) = encoding.encode(&text);
+        if had_errors {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Decoded text cannot be represented in {}", encoding.name()),
+            ));
+        }
+        Ok(bytes.into_owned())
+    }
+}
+
+fn lookup(label: &str) -> io::Result<&'static Encoding> {
+    Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown character encoding {:?}", label),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "Grüße, ♥";
+        for v in VERSIONS.iter() {
+            for label in ["utf-8", "utf-16le", "utf-16be", "gb18030"] {
+                let converted =
+                    v.decode_text(&v.encode_text(text.as_bytes(), "utf-8").unwrap(), label);
+                let encoded = v.encode_text(&converted.unwrap(), label).unwrap();
+                assert_eq!(v.decode_to_string(&mut encoded.as_bytes()).unwrap(), text);
+            }
+        }
+
+        let e = VERSIONS[0].encode_text(b"abc", "ebcdic").unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        let e = VERSIONS[0]
+            .encode_text(b"\xff\xfe\x00", "utf-8")
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,414 @@
+//! Iterators over the chunks of encoded data.
+
+use std::fmt;
+use std::ops::Deref;
+use std::slice;
+use std::str;
+
+use crate::chars::Chars;
+use crate::decode::{is_line_break, Settings, State};
+use crate::emojis::Version;
+use crate::error::DecodeError;
+use crate::tables::PADDING;
+
+/// A single encoded chunk of up to 4 symbols, stored inline.
+///
+/// Dereferences to `str`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodedChunk {
+    buf: [u8; 16],
+    len: usize,
+}
+
+impl EncodedChunk {
+    /// Returns the symbols of the chunk as a string slice.
+    pub fn as_str(&self) -> &str {
+        // encoded output is guaranteed to be valid UTF-8
+        unsafe { str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+}
+
+impl Deref for EncodedChunk {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for EncodedChunk {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for EncodedChunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for EncodedChunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An iterator over the encoded chunks of a byte slice, returned by
+/// [`Version::encode_chunks`](emojis/struct.Version.html#method.encode_chunks).
+#[derive(Debug, Clone)]
+pub struct EncodedChunks<'a> {
+    version: &'a Version,
+    chunks: slice::Chunks<'a, u8>,
+}
+
+impl<'a> Iterator for EncodedChunks<'a> {
+    type Item = EncodedChunk;
+
+    fn next(&mut self) -> Option<EncodedChunk> {
+        let data = self.chunks.next()?;
+        let mut chunk = EncodedChunk {
+            buf: [0; 16],
+            len: 0,
+        };
+        chunk.len = self
+            .version
+            .encode_chunk(data, &mut &mut chunk.buf[..])
+            .expect("Encoded chunk is longer than 16 bytes");
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for EncodedChunks<'a> {}
+
+/// A fallible iterator over the decoded chunks of a string, returned by
+/// [`Version::decode_chunks`](emojis/struct.Version.html#method.decode_chunks).
+#[derive(Debug)]
+pub struct DecodedChunks<'a> {
+    version: &'a Version,
+    decoder: &'a Version,
+    state: State,
+    input: Chars<&'a [u8]>,
+    settings: Settings,
+    failed: bool,
+}
+
+impl<'a> Iterator for DecodedChunks<'a> {
+    type Item = Result<([u8; 5], usize), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.version.next_chunk(
+            &mut self.decoder,
+            self.settings,
+            &mut self.state,
+            &mut self.input,
+        );
+        match result {
+            Ok(chunk) => chunk.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                // Reading a string can only fail to decode
+                let e = DecodeError::from_io(&e).expect("Unexpected I/O error");
+                Some(Err(e.clone()))
+            }
+        }
+    }
+}
+
+/// An iterator over the pieces of encoded text, returned by
+/// [`split_encoded`](fn.split_encoded.html).
+#[derive(Debug, Clone)]
+pub struct SplitEncoded<'a> {
+    rest: &'a str,
+    max_symbols: usize,
+}
+
+impl<'a> Iterator for SplitEncoded<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.rest = self.rest.trim_start_matches(is_line_break);
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let mut symbols = 0;
+        let mut end = self.rest.len();
+        for (i, c) in self.rest.char_indices() {
+            if is_line_break(c) {
+                continue;
+            }
+            if symbols == self.max_symbols {
+                end = i;
+                break;
+            }
+            symbols += 1;
+        }
+        let (piece, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(piece.trim_end_matches(is_line_break))
+    }
+}
+
+/// Splits the encoded text into pieces of at most `max_symbols` symbols, e.g. to send it in
+/// messages of a limited length. The pieces consist of whole chunks of 4 symbols, so each of them
+/// can be decoded on its own, and decoding them in order gives the original data. Line breaks
+/// between the pieces are left out; those within them are kept, so that wrapped text gives
+/// pieces which need a decoder skipping line breaks, like a [`Codec`](struct.Codec.html) built
+/// with [`Builder::ignore_line_breaks`](struct.Builder.html#method.ignore_line_breaks).
+///
+/// The text is not validated; an invalid piece fails to decode like the whole text would.
+///
+/// # Panics
+///
+/// Panics if `max_symbols` is less than 4, the length of a chunk.
+///
+/// # Examples
+///
+/// ```
+/// let pieces: Vec<_> = ecoji::split_encoded("👶😲🇲👅\n🍉🔙🌥🌩\n👖📸🎈☕", 9).collect();
+///
+/// assert_eq!(pieces, ["👶😲🇲👅\n🍉🔙🌥🌩", "👖📸🎈☕"]);
+/// ```
+pub fn split_encoded(s: &str, max_symbols: usize) -> SplitEncoded<'_> {
+    assert!(max_symbols >= 4, "Pieces must fit at least one chunk");
+    SplitEncoded {
+        rest: s,
+        max_symbols: max_symbols - max_symbols % 4,
+    }
+}
+
+/// Truncates the encoded text to at most `max_bytes` bytes, e.g. to store it in a column of a
+/// limited length, without splitting a chunk, so the result decodes to a prefix of the data
+/// whose length is a multiple of 5 bytes, or to all of the data if the text fits. Line breaks
+/// after the last chunk kept are left out.
+///
+/// [`Version::truncate_encoded_padded`](emojis/struct.Version.html#method.truncate_encoded_padded)
+/// keeps more of the data by encoding a part of the next chunk in a padded chunk.
+///
+/// # Examples
+///
+/// ```
+/// let encoded = "👶😲🇲👅\n🍉🔙🌥🌩";
+///
+/// assert_eq!(ecoji::truncate_encoded(encoded, 20), "👶😲🇲👅");
+/// assert_eq!(ecoji::truncate_encoded(encoded, 10), "");
+/// assert_eq!(ecoji::truncate_encoded(encoded, 100), encoded);
+/// ```
+pub fn truncate_encoded(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+
+    let mut symbols = 0;
+    let mut end = 0;
+    let mut chars = s
+        .char_indices()
+        .filter(|&(_, c)| !is_line_break(c))
+        .peekable();
+    while let Some((i, c)) = chars.next() {
+        if i + c.len_utf8() > max_bytes {
+            break;
+        }
+        symbols += 1;
+        // the padding symbol ends a short chunk in version 2, and is repeated to fill up one in
+        // version 1
+        let padded = c == PADDING && chars.peek().is_none_or(|&(_, next)| next != PADDING);
+        if symbols == 4 || padded {
+            symbols = 0;
+            end = i + c.len_utf8();
+        }
+    }
+    &s[..end]
+}
+
+impl Version {
+    /// Truncates the encoded text to at most `max_bytes` bytes like
+    /// [`truncate_encoded`](../fn.truncate_encoded.html) does, but if the next chunk does not fit
+    /// in whole, encodes as many of its bytes as fit into a padded chunk, which replaces it. The
+    /// result decodes to the longest prefix of the data which fits.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the chunk which is cut does not decode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> Result<(), ecoji::DecodeError> {
+    /// let encoded = "👶😲⛵👅🍉🧴🦪🦮";
+    /// let truncated = ecoji::VERSION2.truncate_encoded_padded(encoded, 27)?;
+    ///
+    /// assert_eq!(truncated, "👶😲⛵👅🍉🔓☕");
+    /// assert_eq!(ecoji::decode_string_with(&ecoji::VERSION2, &truncated).unwrap(), b"input d");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn truncate_encoded_padded(
+        &self,
+        s: &str,
+        max_bytes: usize,
+    ) -> Result<String, DecodeError> {
+        let kept = truncate_encoded(s, max_bytes);
+        let mut truncated = kept.to_owned();
+        if kept.len() == s.len() {
+            return Ok(truncated);
+        }
+
+        let rest = &s[kept.len()..];
+        let (data, len) = match self.decode_chunks_with(rest, Settings::wrapped()).next() {
+            Some(chunk) => chunk?,
+            None => return Ok(truncated),
+        };
+        let room = max_bytes - kept.len();
+        if let Some(chunk) = (1..len)
+            .rev()
+            .map(|n| self.encode_chunks(&data[..n]).next().unwrap())
+            .find(|chunk| chunk.len() <= room)
+        {
+            truncated.push_str(&chunk);
+        }
+        Ok(truncated)
+    }
+
+    /// Returns an iterator over the encoded chunks of the input, each of which encodes up to
+    /// 5 bytes. Their concatenation is exactly what [`encode`](#method.encode) produces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chunks: Vec<_> = ecoji::VERSION1.encode_chunks(b"input data").collect();
+    ///
+    /// assert_eq!(chunks.len(), 2);
+    /// assert_eq!(chunks[0].as_str(), "👶😲🇲👅");
+    /// assert_eq!(chunks[1].as_str(), "🍉🔙🌥🌩");
+    /// ```
+    pub fn encode_chunks<'a>(&'a self, input: &'a [u8]) -> EncodedChunks<'a> {
+        EncodedChunks {
+            version: self,
+            chunks: input.chunks(5),
+        }
+    }
+
+    /// Returns an iterator over the decoded chunks of the input, each of which is given as an
+    /// array of 5 bytes along with the number of them which are used. The input is decoded
+    /// lazily, like [`decode`](#method.decode) does, so the iteration may be stopped early;
+    /// after an error, the iterator ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut chunks = ecoji::VERSION1.decode_chunks("👶😲🇲👅🍉🔙🌥🌩👖📸🎈x");
+    ///
+    /// assert_eq!(chunks.next(), Some(Ok((*b"input", 5))));
+    /// assert_eq!(chunks.next(), Some(Ok((*b" data", 5))));
+    /// assert!(chunks.next().unwrap().is_err());
+    /// assert_eq!(chunks.next(), None);
+    /// ```
+    pub fn decode_chunks<'a>(&'a self, input: &'a str) -> DecodedChunks<'a> {
+        self.decode_chunks_with(input, Settings::default())
+    }
+
+    fn decode_chunks_with<'a>(&'a self, input: &'a str, settings: Settings) -> DecodedChunks<'a> {
+        DecodedChunks {
+            version: self,
+            decoder: self,
+            state: State::default(),
+            input: Chars::new(input.as_bytes()),
+            settings,
+            failed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_encoded, truncate_encoded};
+    use crate::decode::Settings;
+    use crate::emojis::{Version, VERSIONS};
+
+    fn decode_wrapped(v: &Version, text: &str) -> Vec<u8> {
+        let mut output = Vec::new();
+        v.decode_from(
+            &mut { v },
+            Settings::wrapped(),
+            &mut text.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        output
+    }
+
+    quickcheck! {
+        fn truncated_text_decodes(input: Vec<u8>, max_bytes: u8, wrap: bool) -> bool {
+            let max_bytes = max_bytes as usize;
+            VERSIONS.iter().all(|v| {
+                let mut encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                if wrap {
+                    encoded = encoded.replace('\u{2615}', "\u{2615}\n");
+                }
+                let truncated = truncate_encoded(&encoded, max_bytes);
+                let padded = v.truncate_encoded_padded(&encoded, max_bytes).unwrap();
+                let decoded = decode_wrapped(v, truncated);
+                let decoded_padded = decode_wrapped(v, &padded);
+
+                let fits = encoded.trim_end().len() <= max_bytes;
+                truncated.len() <= max_bytes
+                    && padded.len() <= max_bytes
+                    && encoded.starts_with(truncated)
+                    && padded.starts_with(truncated)
+                    && input.starts_with(&decoded)
+                    && input.starts_with(&decoded_padded)
+                    && (fits || decoded.len().is_multiple_of(5))
+                    && (fits == (decoded_padded.len() == input.len()))
+                    && decoded_padded.len() >= decoded.len()
+                    && decoded_padded.len() < decoded.len() + 5
+            })
+        }
+
+        fn split_pieces_decode_separately(input: Vec<u8>, max_symbols: u8) -> bool {
+            let max_symbols = max_symbols as usize % 20 + 4;
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let mut output = Vec::new();
+                for piece in split_encoded(&encoded, max_symbols) {
+                    if piece.chars().count() > max_symbols {
+                        return false;
+                    }
+                    output.extend(v.decode_to_vec(&mut piece.as_bytes()).unwrap());
+                }
+                output == input
+            })
+        }
+
+        fn encode_chunks_is_the_same_as_encode(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let chunks: String = v.encode_chunks(&input).map(|chunk| chunk.to_string()).collect();
+                chunks == v.encode_to_string(&mut input.as_slice()).unwrap()
+            })
+        }
+
+        fn decode_chunks_is_the_same_as_decode(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let chunks: Vec<u8> = v
+                    .other_version()
+                    .decode_chunks(&encoded)
+                    .flat_map(|chunk| {
+                        let (data, len) = chunk.unwrap();
+                        data[..len].to_vec()
+                    })
+                    .collect();
+                chunks == input
+            })
+        }
+    }
+}
@@ -0,0 +1,1344 @@
+//! Encoding and decoding with a configuration assembled by a builder.
+
+use std::cell::Cell;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::decode::{Settings, State};
+use crate::diagnostic::Diagnostic;
+use crate::emojis::{Version, VERSIONS};
+use crate::error::{CodedError, ErrorCode, ShortWrite};
+use crate::stream::DecoderReader;
+use crate::tables::MARKERS;
+use crate::width::{LineWidth, LineWrapper};
+use crate::DEFAULT_VERSION;
+
+/// How the decoder treats characters of the other version's alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum VersionPolicy {
+    /// Switch to the other version at the first character which belongs only to its alphabet,
+    /// like [`Version::decode`](emojis/struct.Version.html#method.decode) does.
+    #[default]
+    Auto,
+    /// Accept only the characters of the configured version, and reject the characters which
+    /// belong only to the other version's alphabet with an error of the
+    /// [`DecodeErrorKind::MixedVersion`](enum.DecodeErrorKind.html#variant.MixedVersion) kind.
+    Strict,
+}
+
+/// How the codec deals with a destination which does not accept all of the output it is given
+/// at once, e.g. a non-blocking socket.
+///
+/// Without a codec, the output is written with `Write::write_all`, which behaves like
+/// [`Continue`](#variant.Continue), except that the amount of output written before an error
+/// is not known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ShortWrites {
+    /// Write the rest of the output after a short write, and retry writes failing with
+    /// `io::ErrorKind::Interrupted`. A write which accepts no bytes at all fails the operation
+    /// with an error of the `io::ErrorKind::WriteZero` kind, and any other error is returned as
+    /// is.
+    #[default]
+    Continue,
+    /// Like [`Continue`](#variant.Continue), but also retry writes which accept no bytes, or fail
+    /// with `io::ErrorKind::WouldBlock`, up to the given number of times in a row, yielding to
+    /// other threads in between. The operation fails with the last error once the retries are
+    /// exhausted.
+    Retry(u32),
+    /// Fail at the first write which doesn't accept all of the output given to it, with an
+    /// error wrapping a [`ShortWrite`](struct.ShortWrite.html) which tells how much output has
+    /// been written in total. The error is of the `io::ErrorKind::WouldBlock` kind if the
+    /// destination failed with it, and of the `io::ErrorKind::WriteZero` kind otherwise.
+    Fail,
+}
+
+/// A builder of a [`Codec`](struct.Codec.html), collecting its settings.
+///
+/// Every setting has a default, so that `Builder::new().build()` gives a codec behaving like
+/// [`encode`](fn.encode.html) and [`decode`](fn.decode.html).
+///
+/// # Examples
+///
+/// ```
+/// use ecoji::{Builder, VersionPolicy};
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let codec = Builder::new()
+///     .version(&ecoji::VERSION2)
+///     .version_policy(VersionPolicy::Strict)
+///     .wrap(4)
+///     .ignore_whitespace(true)
+///     .build();
+///
+/// let encoded = codec.encode_to_string(&mut "input data".as_bytes())?;
+/// assert_eq!(encoded, "👶😲⛵👅\n🍉🧴🦪🦮\n");
+///
+/// let decoded = codec.decode_to_vec(&mut " 👶😲⛵👅 🍉🧴🦪🦮 ".as_bytes())?;
+/// assert_eq!(decoded, b"input data");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Builder {
+    codec: Codec,
+}
+
+impl Builder {
+    /// Creates a builder with the default settings.
+    pub fn new() -> Builder {
+        Builder {
+            codec: Codec {
+                version: DEFAULT_VERSION,
+                version_policy: VersionPolicy::Auto,
+                wrap: 0,
+                ignore_line_breaks: false,
+                ignore_whitespace: false,
+                strict: false,
+                full_padding: false,
+                version_marker: false,
+                short_writes: ShortWrites::Continue,
+                max_output: None,
+                max_input_bytes: None,
+                buffer_size: None,
+                progress: None,
+                cancel: None,
+            },
+        }
+    }
+
+    /// Sets the version to encode with and to start decoding with;
+    /// [`DEFAULT_VERSION`](constant.DEFAULT_VERSION.html) by default.
+    pub fn version(mut self, version: &'static Version) -> Builder {
+        self.codec.version = version;
+        self
+    }
+
+    /// Sets whether the decoder may switch to the other version;
+    /// [`VersionPolicy::Auto`](enum.VersionPolicy.html#variant.Auto) by default.
+    pub fn version_policy(mut self, policy: VersionPolicy) -> Builder {
+        self.codec.version_policy = policy;
+        self
+    }
+
+    /// Sets the number of symbols after which the encoder breaks lines; zero, the default,
+    /// disables wrapping. Every line, including the last one, is terminated with `\n`.
+    pub fn wrap(mut self, symbols: usize) -> Builder {
+        self.codec.wrap = symbols;
+        self
+    }
+
+    /// Sets whether the decoder skips line breaks (`\n` and `\r`) in the input, e.g. those of
+    /// text wrapped with [`wrap`](#method.wrap); disabled by default, so that line breaks are
+    /// rejected like by [`Version::decode`](emojis/struct.Version.html#method.decode).
+    pub fn ignore_line_breaks(mut self, ignore: bool) -> Builder {
+        self.codec.ignore_line_breaks = ignore;
+        self
+    }
+
+    /// Sets whether the decoder skips any whitespace in the input, including line breaks;
+    /// disabled by default.
+    pub fn ignore_whitespace(mut self, ignore: bool) -> Builder {
+        self.codec.ignore_whitespace = ignore;
+        self
+    }
+
+    /// Sets whether the decoder accepts only the canonical encoding, like
+    /// [`Version::decode_strict`](emojis/struct.Version.html#method.decode_strict); disabled by
+    /// default.
+    pub fn strict(mut self, strict: bool) -> Builder {
+        self.codec.strict = strict;
+        self
+    }
+
+    /// Sets whether the encoder pads the final chunk to 4 symbols even with version 2, whose
+    /// encoder otherwise stops after the first padding symbol; disabled by default. Version 1
+    /// always pads to 4 symbols.
+    ///
+    /// The output suits consumers whose decoders expect every chunk to be 4 symbols long. It is
+    /// accepted by [`Version::decode`](emojis/struct.Version.html#method.decode), but not by
+    /// the strict decoder, since it is not what the version 2 encoder produces.
+    pub fn full_padding(mut self, full_padding: bool) -> Builder {
+        self.codec.full_padding = full_padding;
+        self
+    }
+
+    /// Sets whether the encoder prefixes the encoded text with the
+    /// [marker](emojis/struct.Version.html#method.marker) of its version, and the decoder
+    /// decodes text which starts with a marker with the version it names, rather than the
+    /// configured one; disabled by default. Text without a marker is still decoded with the
+    /// configured version.
+    ///
+    /// This makes the encoded text self-describing, so that it remains decodable without
+    /// knowing its version once further versions of the alphabet exist. Markers of versions
+    /// unknown to this crate are rejected with an error of the `io::ErrorKind::InvalidData`
+    /// kind. Offsets in errors and diagnostics don't count the marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let encoder = ecoji::Builder::new()
+    ///     .version(&ecoji::VERSION2)
+    ///     .version_marker(true)
+    ///     .build();
+    /// let encoded = encoder.encode_to_string(&mut "input data".as_bytes())?;
+    /// assert_eq!(encoded, "\u{2777}👶😲⛵👅🍉🧴🦪🦮");
+    ///
+    /// let decoder = ecoji::Builder::new()
+    ///     .version(&ecoji::VERSION1)
+    ///     .version_policy(ecoji::VersionPolicy::Strict)
+    ///     .version_marker(true)
+    ///     .build();
+    /// assert_eq!(decoder.decode_to_vec(&mut encoded.as_bytes())?, b"input data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn version_marker(mut self, version_marker: bool) -> Builder {
+        self.codec.version_marker = version_marker;
+        self
+    }
+
+    /// Sets how the encoder and the decoder deal with a destination which doesn't accept all of
+    /// the output at once; [`ShortWrites::Continue`](enum.ShortWrites.html#variant.Continue) by
+    /// default. The returned number of bytes written is exact in any case, and so is the one
+    /// reported to the [`progress`](#method.progress) callback.
+    ///
+    /// With a [`buffer_size`](#method.buffer_size), the policy applies to the writes to the
+    /// buffer, which itself writes to the destination like `Write::write_all` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{self, Write};
+    ///
+    /// /// A destination taking 3 bytes at a time.
+    /// struct Slow(Vec<u8>);
+    ///
+    /// impl Write for Slow {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         let n = buf.len().min(3);
+    ///         self.0.extend_from_slice(&buf[..n]);
+    ///         Ok(n)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let codec = ecoji::Builder::new()
+    ///     .short_writes(ecoji::ShortWrites::Fail)
+    ///     .build();
+    /// let mut output = Slow(Vec::new());
+    /// let e = codec.encode(&mut "abc".as_bytes(), &mut output).unwrap_err();
+    ///
+    /// assert_eq!(ecoji::ShortWrite::from_io(&e).unwrap().written(), 3);
+    /// assert_eq!(output.0.len(), 3);
+    /// ```
+    pub fn short_writes(mut self, policy: ShortWrites) -> Builder {
+        self.codec.short_writes = policy;
+        self
+    }
+
+    /// Sets the largest number of bytes the encoder or the decoder may write; unlimited by
+    /// default. An operation whose output would exceed it fails with an error of the
+    /// `io::ErrorKind::Other` kind without writing the excess.
+    pub fn max_output(mut self, bytes: u64) -> Builder {
+        self.codec.max_output = Some(bytes);
+        self
+    }
+
+    /// Sets the largest number of bytes the encoder or the decoder may read from the source;
+    /// unlimited by default. An operation whose source produces more fails with an error of the
+    /// `io::ErrorKind::Other` kind as soon as the excess is read, e.g. to protect a service from
+    /// unbounded streams. At most one byte beyond the limit is read from the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let codec = ecoji::Builder::new().max_input_bytes(4).build();
+    ///
+    /// assert_eq!(codec.encode_to_string(&mut &b"data"[..]).unwrap(), "👢📩🏦🏍");
+    /// assert!(codec.encode_to_string(&mut &b"input data"[..]).is_err());
+    /// ```
+    pub fn max_input_bytes(mut self, bytes: u64) -> Builder {
+        self.codec.max_input_bytes = Some(bytes);
+        self
+    }
+
+    /// Makes the codec read the source and write the destination through buffers of the given
+    /// size, for sources and destinations which are not buffered themselves. By default, they
+    /// are used directly.
+    pub fn buffer_size(mut self, bytes: usize) -> Builder {
+        self.codec.buffer_size = Some(bytes);
+        self
+    }
+
+    /// Sets a callback which is called with the cumulative amounts of data processed whenever
+    /// another `interval` bytes have been read from the source, and once more when the operation
+    /// is finished, e.g. to drive a progress bar. An interval of zero is treated as one.
+    ///
+    /// The callback is not serialized with the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let reports = Arc::new(Mutex::new(Vec::new()));
+    /// let codec = ecoji::Builder::new()
+    ///     .progress(4, {
+    ///         let reports = reports.clone();
+    ///         move |progress| reports.lock().unwrap().push(progress.bytes_read)
+    ///     })
+    ///     .build();
+    ///
+    /// codec.encode_to_string(&mut "input data".as_bytes())?;
+    /// assert_eq!(*reports.lock().unwrap(), [5, 10, 10]);
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn progress<F>(mut self, interval: u64, callback: F) -> Builder
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.codec.progress = Some(Callback {
+            interval: interval.max(1),
+            function: Arc::new(callback),
+        });
+        self
+    }
+
+    /// Sets a flag which aborts the operations in progress once it is set, e.g. from another
+    /// thread. The flag is checked before every read from the source, so the operations fail
+    /// promptly with an error of kind `io::ErrorKind::Other`; the output written up to that point
+    /// is left in the destination.
+    ///
+    /// The flag is not reset by the codec, and is not serialized with the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let codec = ecoji::Builder::new().cancel_flag(cancel.clone()).build();
+    ///
+    /// cancel.store(true, Ordering::Relaxed);
+    /// assert!(codec.encode_to_string(&mut "input data".as_bytes()).is_err());
+    /// ```
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Builder {
+        self.codec.cancel = Some(CancelFlag(flag));
+        self
+    }
+
+    /// Creates a codec with the collected settings.
+    pub fn build(self) -> Codec {
+        self.codec
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+/// An encoder and decoder honoring the settings collected by a [`Builder`](struct.Builder.html).
+///
+/// With the `serde` feature, codecs and builders can be serialized, e.g. to be stored in
+/// configuration files, as a map of the settings named like the methods of `Builder`, with the
+/// version given as a number. When deserializing, missing settings take the default values.
+///
+/// ```
+/// # #[cfg(feature = "serde")]
+/// # fn test() -> Result<(), serde_json::Error> {
+/// let codec: ecoji::Codec = serde_json::from_str(r#"{"version": 2, "wrap": 76}"#)?;
+///
+/// assert_eq!(
+///     codec,
+///     ecoji::Builder::new().version(&ecoji::VERSION2).wrap(76).build()
+/// );
+/// #  Ok(())
+/// # }
+/// # #[cfg(feature = "serde")]
+/// # test().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Codec {
+    version: &'static Version,
+    version_policy: VersionPolicy,
+    wrap: usize,
+    ignore_line_breaks: bool,
+    ignore_whitespace: bool,
+    strict: bool,
+    full_padding: bool,
+    version_marker: bool,
+    short_writes: ShortWrites,
+    max_output: Option<u64>,
+    max_input_bytes: Option<u64>,
+    buffer_size: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    progress: Option<Callback>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cancel: Option<CancelFlag>,
+}
+
+impl Codec {
+    /// Creates a builder with the default settings, the same as
+    /// [`Builder::new`](struct.Builder.html#method.new).
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Returns the version the codec encodes with.
+    pub fn version(&self) -> &'static Version {
+        self.version
+    }
+
+    /// Encodes the entire source like [`Version::encode`](emojis/struct.Version.html#method.encode),
+    /// wrapping, fully padding and prefixing the output with the version marker if configured.
+    ///
+    /// If successful, returns the number of bytes which were written to the destination writer,
+    /// including line breaks.
+    pub fn encode<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.process(source, destination, self.wrap, |source, destination| {
+            self.write_marker(self.version, destination)?;
+            self.version
+                .encode_padded(source, destination, self.full_padding)
+        })
+    }
+
+    /// Encodes the entire source, storing the result of the encoding to a new owned string.
+    pub fn encode_to_string<R: Read + ?Sized>(&self, source: &mut R) -> io::Result<String> {
+        let mut output = Vec::new();
+        self.encode(source, &mut output)?;
+        // encoded output is guaranteed to be valid UTF-8
+        Ok(unsafe { String::from_utf8_unchecked(output) })
+    }
+
+    /// Decodes the entire source like [`Version::decode`](emojis/struct.Version.html#method.decode),
+    /// applying the version policy, line break and whitespace tolerance, strictness and version
+    /// marker.
+    ///
+    /// If successful, returns the number of bytes which were written to the destination writer.
+    pub fn decode<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let settings = self.settings();
+        self.process(source, destination, 0, |source, destination| {
+            let (version, mut source) = self.marked_version(source)?;
+            let mut decoder = version;
+            version.decode_from(&mut decoder, settings, &mut source, destination)
+        })
+    }
+
+    /// Decodes the entire source like [`decode`](#method.decode), and also returns the
+    /// deviations from pristine input which were tolerated because of the settings: whitespace
+    /// skipped with [`Builder::ignore_whitespace`](struct.Builder.html#method.ignore_whitespace),
+    /// and the switch to the other version with the
+    /// [`Auto`](enum.VersionPolicy.html#variant.Auto) version policy. Line breaks skipped with
+    /// either setting are not reported.
+    ///
+    /// If successful, returns the number of bytes which were written to the destination writer
+    /// along with the diagnostics, in the order of the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecoji::DiagnosticKind;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let codec = ecoji::Builder::new().ignore_whitespace(true).build();
+    ///
+    /// let mut output = Vec::new();
+    /// let (_, diagnostics) =
+    ///     codec.decode_with_diagnostics(&mut "👖📸 🎈☕".as_bytes(), &mut output)?;
+    ///
+    /// assert_eq!(output, b"abc");
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].kind(), &DiagnosticKind::SkippedWhitespace(' '));
+    /// assert_eq!(diagnostics[0].position(), 2);
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_with_diagnostics<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<(usize, Vec<Diagnostic>)> {
+        let settings = self.settings();
+        let mut state = State::collecting();
+        let written = self.process(source, destination, 0, |source, destination| {
+            let (version, mut source) = self.marked_version(source)?;
+            let mut decoder = version;
+            version.decode_with_state(&mut decoder, settings, &mut state, &mut source, destination)
+        })?;
+        Ok((written, state.diagnostics.unwrap_or_default()))
+    }
+
+    /// Decodes the entire source, storing the result of the decoding to a new byte vector.
+    pub fn decode_to_vec<R: Read + ?Sized>(&self, source: &mut R) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self.decode(source, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decodes the entire source, storing the result of the decoding to a new owned string, and
+    /// fails with an error of the `io::ErrorKind::InvalidData` kind if it is not valid UTF-8.
+    pub fn decode_to_string<R: Read + ?Sized>(&self, source: &mut R) -> io::Result<String> {
+        let output = self.decode_to_vec(source)?;
+        String::from_utf8(output).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Decodes the entire source like [`decode`](#method.decode), processing blocks of the
+    /// source in parallel on the current rayon thread pool like
+    /// [`Version::decode_parallel`](emojis/struct.Version.html#method.decode_parallel).
+    ///
+    /// Strict decoding and skipping whitespace other than line breaks can't be split into
+    /// blocks, so with those settings the source is decoded sequentially instead.
+    ///
+    /// Requires the `parallel` feature.
+    #[cfg(feature = "parallel")]
+    pub fn decode_parallel<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        if self.strict || self.ignore_whitespace {
+            return self.decode(source, destination);
+        }
+        let settings = self.settings();
+        self.process(source, destination, 0, |source, destination| {
+            let (version, mut source) = self.marked_version(source)?;
+            version.decode_parallel_by(
+                crate::parallel::DECODE_SEGMENT,
+                settings,
+                &mut source,
+                destination,
+            )
+        })
+    }
+
+    /// Rewraps already encoded text without decoding it: removes all whitespace from it, and
+    /// breaks it into lines like [`encode`](#method.encode) does, e.g. to clean up text which
+    /// has been reflowed by an email client. The symbols are checked like
+    /// [`decode`](#method.decode) checks them first, so that only valid encoded text is
+    /// reformatted; error offsets refer to the text with the whitespace removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let codec = ecoji::Builder::new().wrap(3).build();
+    ///
+    /// assert_eq!(codec.reformat("👶😲 🇲👅🍉\r\n 🔙🌥🌩")?, "👶😲🇲\n👅🍉🔙\n🌥🌩\n");
+    /// assert!(codec.reformat("👶😲 🇲👅🍉 hello").is_err());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn reformat(&self, input: &str) -> io::Result<String> {
+        let symbols: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut bytes = symbols.as_bytes();
+        let (version, mut source) = self.marked_version(&mut bytes)?;
+        let mut decoder = version;
+        version.decode_from(&mut decoder, self.settings(), &mut source, &mut io::sink())?;
+
+        let body = match symbols.strip_prefix(version.marker()) {
+            Some(body) if self.version_marker => body,
+            _ => &symbols,
+        };
+        let mut output = Vec::new();
+        self.process(
+            &mut body.as_bytes(),
+            &mut output,
+            self.wrap,
+            |source, destination| {
+                self.write_marker(version, destination)?;
+                io::copy(source, destination).map(|n| n as usize)
+            },
+        )?;
+        // the symbols have been validated, so they are valid UTF-8
+        Ok(unsafe { String::from_utf8_unchecked(output) })
+    }
+
+    /// Writes the marker of the version, if version markers are enabled.
+    fn write_marker(&self, version: &Version, destination: &mut dyn Write) -> io::Result<()> {
+        if self.version_marker {
+            let mut marker = [0; 4];
+            destination.write_all(version.marker().encode_utf8(&mut marker).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the version to decode with, which is the one named by the marker at the start of
+    /// the source if version markers are enabled and it has one, and the source without the
+    /// marker.
+    fn marked_version<'a>(
+        &self,
+        source: &'a mut dyn Read,
+    ) -> io::Result<(&'static Version, impl Read + 'a)> {
+        // all markers are 3 bytes long in UTF-8
+        let mut start = [0; 3];
+        let mut len = 0;
+        while self.version_marker && len < start.len() {
+            match source.read(&mut start[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let marker = std::str::from_utf8(&start[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .filter(|c| MARKERS.contains(c));
+        let version = match marker {
+            Some(marker) => {
+                len = 0;
+                VERSIONS
+                    .iter()
+                    .copied()
+                    .find(|v| v.marker() == marker)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Unsupported version marker {}", marker),
+                        )
+                    })?
+            }
+            None => self.version,
+        };
+        Ok((
+            version,
+            io::Cursor::new(start).take(len as u64).chain(source),
+        ))
+    }
+
+    /// Returns a reader decoding the source incrementally as configured, starting with the
+    /// version named by its marker if version markers are enabled. The output limit and the
+    /// progress callback are not applied.
+    pub(crate) fn decoder_reader<'a>(
+        &self,
+        source: &'a mut dyn Read,
+    ) -> io::Result<DecoderReader<'static, impl Read + 'a>> {
+        let (version, source) = self.marked_version(source)?;
+        Ok(DecoderReader::with_settings(
+            version,
+            source,
+            self.settings(),
+        ))
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            strict: self.strict,
+            locked: self.version_policy == VersionPolicy::Strict,
+            line_breaks: self.ignore_line_breaks,
+            ignore_whitespace: self.ignore_whitespace,
+        }
+    }
+
+    /// Runs the operation with the source and the destination buffered, and the output limited
+    /// and wrapped, as configured. Returns the number of bytes written to the destination.
+    fn process<R, W, F>(
+        &self,
+        mut source: &mut R,
+        mut destination: &mut W,
+        wrap: usize,
+        operation: F,
+    ) -> io::Result<usize>
+    where
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+        F: FnOnce(&mut dyn Read, &mut dyn Write) -> io::Result<usize>,
+    {
+        let mut buffered_source;
+        let source: &mut dyn Read = match self.buffer_size {
+            Some(size) => {
+                buffered_source = BufReader::with_capacity(size, source);
+                &mut buffered_source
+            }
+            None => &mut source,
+        };
+        let mut buffered_destination;
+        let destination: &mut dyn Write = match self.buffer_size {
+            Some(size) => {
+                buffered_destination = BufWriter::with_capacity(size, destination);
+                &mut buffered_destination
+            }
+            None => &mut destination,
+        };
+
+        let read = Cell::new(0);
+        let mut input = Input {
+            inner: source,
+            read: &read,
+            limit: self.max_input_bytes,
+            cancel: self.cancel.as_ref(),
+        };
+        let mut output = Output {
+            inner: destination,
+            short_writes: self.short_writes,
+            written: 0,
+            limit: self.max_output,
+            read: &read,
+            progress: self.progress.as_ref(),
+            next_report: self
+                .progress
+                .as_ref()
+                .map_or(0, |progress| progress.interval),
+        };
+        let mut wrapper = LineWrapper::new(&mut output, LineWidth::Symbols(wrap));
+        operation(&mut input, &mut wrapper)?;
+        wrapper.finish()?;
+        output.flush()?;
+        if let Some(progress) = output.progress {
+            progress.report(read.get(), output.written);
+        }
+        Ok(output.written as usize)
+    }
+}
+
+impl Default for Codec {
+    fn default() -> Codec {
+        Builder::new().build()
+    }
+}
+
+/// Cumulative amounts of data processed by a [`Codec`](struct.Codec.html), passed to the
+/// callback set with [`Builder::progress`](struct.Builder.html#method.progress).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Progress {
+    /// Number of bytes read from the source.
+    pub bytes_read: u64,
+    /// Number of bytes written to the destination.
+    pub bytes_written: u64,
+}
+
+/// A progress callback along with its interval. Callbacks are only equal to their clones.
+#[derive(Clone)]
+struct Callback {
+    interval: u64,
+    function: Arc<dyn Fn(Progress) + Send + Sync>,
+}
+
+impl Callback {
+    fn report(&self, bytes_read: u64, bytes_written: u64) {
+        (self.function)(Progress {
+            bytes_read,
+            bytes_written,
+        });
+    }
+}
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Callback")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Callback {
+    fn eq(&self, other: &Callback) -> bool {
+        self.interval == other.interval && Arc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+impl Eq for Callback {}
+
+impl Hash for Callback {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.interval.hash(state);
+    }
+}
+
+/// A cancellation flag. Flags are only equal to their clones.
+#[derive(Debug, Clone)]
+struct CancelFlag(Arc<AtomicBool>);
+
+impl PartialEq for CancelFlag {
+    fn eq(&self, other: &CancelFlag) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancelFlag {}
+
+impl Hash for CancelFlag {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+/// A reader adapter counting and limiting the bytes read through it, and failing once the
+/// cancellation flag is set.
+struct Input<'a> {
+    inner: &'a mut dyn Read,
+    read: &'a Cell<u64>,
+    limit: Option<u64>,
+    cancel: Option<&'a CancelFlag>,
+}
+
+impl<'a> Read for Input<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(cancel) = self.cancel {
+            if cancel.0.load(Ordering::Relaxed) {
+                return Err(CodedError::io(ErrorCode::Cancelled, "Operation cancelled"));
+            }
+        }
+        let buf = match self.limit {
+            // read at most one byte beyond the limit, which is enough to tell that it's exceeded
+            Some(limit) => {
+                let allowed = limit.saturating_add(1).saturating_sub(self.read.get());
+                let len = allowed.min(buf.len() as u64) as usize;
+                &mut buf[..len]
+            }
+            None => buf,
+        };
+        let n = self.inner.read(buf)?;
+        self.read.set(self.read.get() + n as u64);
+        if let Some(limit) = self.limit {
+            if self.read.get() > limit {
+                return Err(CodedError::io(
+                    ErrorCode::InputLimit,
+                    format!("Input exceeds the limit of {} bytes", limit),
+                ));
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// A writer adapter counting and limiting the bytes written through it. It also reports the
+/// progress, since the data read is only processed once its output is written.
+struct Output<'a> {
+    inner: &'a mut dyn Write,
+    short_writes: ShortWrites,
+    written: u64,
+    limit: Option<u64>,
+    /// The number of bytes read by the corresponding `Input`.
+    read: &'a Cell<u64>,
+    progress: Option<&'a Callback>,
+    /// The number of bytes to be read before the progress is reported next time.
+    next_report: u64,
+}
+
+impl<'a> Output<'a> {
+    fn write_through(&mut self, buf: &[u8]) -> io::Result<()> {
+        if let Some(limit) = self.limit {
+            if self.written + buf.len() as u64 > limit {
+                return Err(CodedError::io(
+                    ErrorCode::OutputLimit,
+                    format!("Output exceeds the limit of {} bytes", limit),
+                ));
+            }
+        }
+        let mut rest = buf;
+        let mut retries = 0;
+        while !rest.is_empty() {
+            let error = match self.inner.write(rest) {
+                Ok(0) => io::Error::from(io::ErrorKind::WriteZero),
+                Ok(n) => {
+                    self.written += n as u64;
+                    rest = &rest[n..];
+                    retries = 0;
+                    if !rest.is_empty() && self.short_writes == ShortWrites::Fail {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            ShortWrite::new(self.written),
+                        ));
+                    }
+                    continue;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => e,
+            };
+            let temporary = matches!(
+                error.kind(),
+                io::ErrorKind::WriteZero | io::ErrorKind::WouldBlock
+            );
+            match self.short_writes {
+                ShortWrites::Retry(max) if temporary && retries < max => {
+                    retries += 1;
+                    thread::yield_now();
+                }
+                ShortWrites::Fail if temporary => {
+                    return Err(io::Error::new(error.kind(), ShortWrite::new(self.written)));
+                }
+                _ => return Err(error),
+            }
+        }
+
+        if let Some(progress) = self.progress {
+            let read = self.read.get();
+            if read >= self.next_report {
+                progress.report(read, self.written);
+                self.next_report = (read / progress.interval + 1) * progress.interval;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Write for Output<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_through(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emojis::{VERSION1, VERSION2, VERSIONS};
+    use crate::error::{DecodeError, DecodeErrorKind};
+
+    #[test]
+    fn test_defaults() {
+        let codec = Codec::default();
+        for input in [&b""[..], b"a", b"abcde", b"abcdefghijk"] {
+            let encoded = codec.encode_to_string(&mut &input[..]).unwrap();
+            assert_eq!(
+                encoded,
+                DEFAULT_VERSION.encode_to_string(&mut &input[..]).unwrap()
+            );
+            assert_eq!(codec.decode_to_vec(&mut encoded.as_bytes()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_ignore_line_breaks() {
+        let wrapped = "👖📸\r\n🎈☕\n";
+        assert!(Codec::default()
+            .decode_to_vec(&mut wrapped.as_bytes())
+            .is_err());
+        for codec in [
+            Builder::new().ignore_line_breaks(true).build(),
+            Builder::new().ignore_line_breaks(true).strict(true).build(),
+            Builder::new().ignore_whitespace(true).build(),
+        ] {
+            assert_eq!(
+                codec.decode_to_vec(&mut wrapped.as_bytes()).unwrap(),
+                b"abc"
+            );
+        }
+
+        // other whitespace is still rejected
+        let e = Builder::new()
+            .ignore_line_breaks(true)
+            .build()
+            .decode_to_vec(&mut "👖📸 🎈☕".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e).map(DecodeError::kind),
+            Some(&DecodeErrorKind::InvalidChar(' '))
+        );
+    }
+
+    #[test]
+    fn test_version_policy() {
+        let encoded = VERSION2.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+        let auto = Builder::new().version(&VERSION1).build();
+        assert_eq!(
+            auto.decode_to_vec(&mut encoded.as_bytes()).unwrap(),
+            b"abcdefghij"
+        );
+
+        let strict = Builder::new().version_policy(VersionPolicy::Strict).build();
+        let e = strict.decode_to_vec(&mut encoded.as_bytes()).unwrap_err();
+        match DecodeError::from_io(&e).map(DecodeError::kind) {
+            Some(&DecodeErrorKind::MixedVersion {
+                expected: 1,
+                found: 2,
+                ..
+            }) => {}
+            kind => panic!("Unexpected error {:?}", kind),
+        }
+
+        let e = strict.decode_to_vec(&mut "👖📸🎈x".as_bytes()).unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e).map(DecodeError::kind),
+            Some(&DecodeErrorKind::InvalidChar('x'))
+        );
+    }
+
+    #[test]
+    fn test_full_padding() {
+        let codec = Builder::new().version(&VERSION2).full_padding(true).build();
+        for input in [&b"a"[..], b"ab", b"abc", b"abcd", b"abcde", b"abcdef"] {
+            let encoded = codec.encode_to_string(&mut &input[..]).unwrap();
+            assert_eq!(encoded.chars().count() % 4, 0);
+            assert_eq!(
+                VERSION2.decode_to_vec(&mut encoded.as_bytes()).unwrap(),
+                input
+            );
+        }
+        assert_eq!(codec.encode_to_string(&mut &b"a"[..]).unwrap(), "👕☕☕☕");
+
+        let codec = Builder::new().version(&VERSION1).full_padding(true).build();
+        assert_eq!(
+            codec.encode_to_string(&mut &b"abc"[..]).unwrap(),
+            VERSION1.encode_to_string(&mut &b"abc"[..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_version_marker() {
+        let codec = Builder::new()
+            .version(&VERSION1)
+            .version_policy(VersionPolicy::Strict)
+            .version_marker(true)
+            .wrap(4)
+            .ignore_line_breaks(true)
+            .build();
+        let encoded = codec.encode_to_string(&mut &b"abc"[..]).unwrap();
+        assert_eq!(encoded, "\u{2776}👖📸🎈\n☕\n");
+        assert_eq!(
+            codec.decode_to_vec(&mut encoded.as_bytes()).unwrap(),
+            b"abc"
+        );
+        assert_eq!(codec.reformat("👖📸 🎈☕").unwrap(), encoded);
+        assert_eq!(codec.encode_to_string(&mut &b""[..]).unwrap(), "\u{2776}\n");
+
+        // the marker overrides the configured version, and is optional
+        let encoded = VERSION2.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+        let marked = format!("{}{}", VERSION2.marker(), encoded);
+        assert_eq!(
+            codec.decode_to_vec(&mut marked.as_bytes()).unwrap(),
+            b"abcdefghij"
+        );
+        assert!(codec.decode_to_vec(&mut encoded.as_bytes()).is_err());
+        assert_eq!(
+            codec.decode_to_vec(&mut "👖📸🎈☕".as_bytes()).unwrap(),
+            b"abc"
+        );
+        assert!(codec.decode_to_vec(&mut "".as_bytes()).unwrap().is_empty());
+
+        let e = codec
+            .decode_to_vec(&mut "\u{2778}👖📸🎈☕".as_bytes())
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(DecodeError::from_io(&e).is_none());
+
+        // without the setting, markers are invalid characters
+        let e = Codec::default()
+            .decode_to_vec(&mut marked.as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e).map(DecodeError::kind),
+            Some(&DecodeErrorKind::InvalidChar(VERSION2.marker()))
+        );
+    }
+
+    /// A destination taking at most 3 bytes per write, and failing every other write, starting
+    /// with the first one unless `fail` is set, with the given error kind, or accepting nothing
+    /// if there is none.
+    struct Choppy {
+        output: Vec<u8>,
+        failure: Option<io::ErrorKind>,
+        fail: bool,
+    }
+
+    impl Write for Choppy {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.fail = !self.fail;
+            match self.failure {
+                Some(kind) if self.fail => return Err(kind.into()),
+                None if self.fail => return Ok(0),
+                _ => {}
+            }
+            let n = buf.len().min(3);
+            self.output.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_short_writes() {
+        let input = b"abcdefghijk";
+        let encoded = VERSION1.encode_to_string(&mut &input[..]).unwrap();
+        for failure in [None, Some(io::ErrorKind::WouldBlock)] {
+            let choppy = || Choppy {
+                output: Vec::new(),
+                failure,
+                fail: true,
+            };
+
+            let mut output = choppy();
+            let e = Codec::default()
+                .encode(&mut &input[..], &mut output)
+                .unwrap_err();
+            assert_eq!(e.kind(), failure.unwrap_or(io::ErrorKind::WriteZero));
+            assert!(ShortWrite::from_io(&e).is_none());
+
+            let mut output = choppy();
+            let codec = Builder::new().short_writes(ShortWrites::Retry(1)).build();
+            let written = codec.encode(&mut &input[..], &mut output).unwrap();
+            assert_eq!(written, encoded.len());
+            assert_eq!(output.output, encoded.as_bytes());
+            let decoded = codec.decode_to_vec(&mut output.output.as_slice()).unwrap();
+            assert_eq!(decoded, input);
+
+            let mut output = choppy();
+            let e = Builder::new()
+                .short_writes(ShortWrites::Fail)
+                .build()
+                .encode(&mut &input[..], &mut output)
+                .unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::WriteZero);
+            assert_eq!(ShortWrite::from_io(&e).unwrap().written(), 3);
+            assert_eq!(output.output, &encoded.as_bytes()[..3]);
+        }
+
+        // errors other than the temporary ones are not retried
+        let mut output = Choppy {
+            output: Vec::new(),
+            failure: Some(io::ErrorKind::BrokenPipe),
+            fail: false,
+        };
+        let e = Builder::new()
+            .short_writes(ShortWrites::Retry(10))
+            .build()
+            .encode(&mut &input[..], &mut output)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_max_output() {
+        let codec = Builder::new().max_output(8).build();
+        assert_eq!(
+            codec.decode_to_vec(&mut "👖📸🎈☕".as_bytes()).unwrap(),
+            b"abc"
+        );
+        assert!(codec.encode_to_string(&mut &b"abc"[..]).is_err());
+
+        let mut output = Vec::new();
+        let codec = Builder::new().max_output(2).build();
+        assert!(codec
+            .decode(&mut "👖📸🎈☕".as_bytes(), &mut output)
+            .is_err());
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_max_input_bytes() {
+        let codec = Builder::new().max_input_bytes(16).build();
+        assert_eq!(
+            codec.decode_to_vec(&mut "👖📸🎈☕".as_bytes()).unwrap(),
+            b"abc"
+        );
+        assert_eq!(
+            codec
+                .encode_to_string(&mut &[0; 16][..])
+                .unwrap()
+                .chars()
+                .count(),
+            16
+        );
+
+        let mut source = &[0; 100][..];
+        let mut output = Vec::new();
+        let e = codec.encode(&mut source, &mut output).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Other);
+        assert_eq!(ErrorCode::of(&e), ErrorCode::InputLimit);
+        assert_eq!(source.len(), 100 - 17);
+
+        let codec = Builder::new().max_input_bytes(14).build();
+        assert!(codec
+            .decode(&mut "👖📸🎈☕".as_bytes(), &mut output)
+            .is_err());
+
+        let codec = Builder::new().max_input_bytes(u64::MAX).build();
+        let encoded = codec.encode_to_string(&mut &b"abc"[..]).unwrap();
+        assert_eq!(
+            codec.decode_to_vec(&mut encoded.as_bytes()).unwrap(),
+            b"abc"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        use crate::diagnostic::DiagnosticKind;
+
+        let mut encoded = VERSION2.encode_to_string(&mut &b"abcdefghij"[..]).unwrap();
+        encoded.insert(4, '\t');
+        let codec = Builder::new().ignore_whitespace(true).build();
+        let (written, diagnostics) = codec
+            .decode_with_diagnostics(&mut encoded.as_bytes(), &mut Vec::new())
+            .unwrap();
+        assert_eq!(written, 10);
+        let kinds: Vec<_> = diagnostics.iter().map(Diagnostic::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &DiagnosticKind::SkippedWhitespace('\t'),
+                &DiagnosticKind::SwitchedVersion(1, 2)
+            ]
+        );
+        assert_eq!(diagnostics[0].offset(), 4);
+
+        let (_, diagnostics) = Builder::new()
+            .ignore_line_breaks(true)
+            .build()
+            .decode_with_diagnostics(&mut "👖📸🎈☕\n".as_bytes(), &mut Vec::new())
+            .unwrap();
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_reformat() {
+        let encoded = VERSION2.encode_to_string(&mut &b"abcdefghijk"[..]).unwrap();
+        let strict = Builder::new()
+            .version(&VERSION2)
+            .version_policy(VersionPolicy::Strict)
+            .strict(true)
+            .build();
+        let spaced: String = encoded.chars().flat_map(|c| [c, ' ']).collect();
+        assert_eq!(strict.reformat(&spaced).unwrap(), encoded);
+        assert!(strict
+            .reformat(&VERSION1.encode_to_string(&mut &b"abcdefghijk"[..]).unwrap())
+            .is_err());
+
+        // the symbols are checked before anything is written
+        let e = Builder::new()
+            .max_output(4)
+            .build()
+            .reformat("x")
+            .unwrap_err();
+        assert!(DecodeError::from_io(&e).is_some());
+    }
+
+    #[test]
+    fn test_progress() {
+        use std::sync::Mutex;
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let codec = Builder::new()
+            .buffer_size(5)
+            .progress(10, {
+                let reports = reports.clone();
+                move |progress| reports.lock().unwrap().push(progress)
+            })
+            .build();
+        let input = [0; 32];
+        let encoded = codec.encode_to_string(&mut &input[..]).unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 4);
+        assert!(reports.windows(2).all(
+            |w| w[0].bytes_read <= w[1].bytes_read && w[0].bytes_written <= w[1].bytes_written
+        ));
+        assert_eq!(
+            reports.last(),
+            Some(&Progress {
+                bytes_read: 32,
+                bytes_written: encoded.len() as u64,
+            })
+        );
+        assert_eq!(codec.clone(), codec);
+    }
+
+    #[test]
+    fn test_cancel_flag() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let codec = Builder::new()
+            .buffer_size(5)
+            .progress(5, {
+                let cancel = cancel.clone();
+                move |progress| {
+                    if progress.bytes_read >= 10 {
+                        cancel.store(true, Ordering::Relaxed)
+                    }
+                }
+            })
+            .cancel_flag(cancel.clone())
+            .build();
+
+        let mut output = Vec::new();
+        let e = codec.encode(&mut &[0; 100][..], &mut output).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Other);
+        assert!(!output.is_empty() && output.len() < 40);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let codec = Builder::new()
+            .version(&VERSION2)
+            .version_policy(VersionPolicy::Strict)
+            .max_output(100)
+            .build();
+        let json = serde_json::to_string(&codec).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":2,"version_policy":"strict","wrap":0,"ignore_line_breaks":false,"ignore_whitespace":false,"strict":false,"full_padding":false,"version_marker":false,"short_writes":"continue","max_output":100,"max_input_bytes":null,"buffer_size":null}"#
+        );
+        assert_eq!(serde_json::from_str::<Codec>(&json).unwrap(), codec);
+        assert!(serde_json::from_str::<Codec>(r#"{"version":3}"#).is_err());
+    }
+
+    quickcheck! {
+        fn wrapped_output_decodes(input: Vec<u8>, wrap: u8, buffer_size: u8) -> bool {
+            VERSIONS.iter().all(|&v| {
+                let wrap = wrap as usize % 10;
+                let codec = Builder::new()
+                    .version(v)
+                    .wrap(wrap)
+                    .buffer_size(buffer_size as usize + 1)
+                    .strict(true)
+                    .ignore_line_breaks(true)
+                    .build();
+                let encoded = codec.encode_to_string(&mut input.as_slice()).unwrap();
+                let lines_fit = encoded.lines().all(|line| wrap == 0 || line.chars().count() <= wrap);
+                lines_fit && codec.decode_to_vec(&mut encoded.as_bytes()).unwrap() == input
+            })
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Comparison of encoded streams by the data they carry.
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::codec::Codec;
+use crate::emojis::Version;
+use crate::DEFAULT_VERSION;
+
+/// Checks whether two encoded streams carry the same data, decoding both starting with the
+/// [default version](constant.DEFAULT_VERSION.html); see
+/// [`equivalent_with`](fn.equivalent_with.html).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// let wrapped = "👶😲🇲👅\n🍉🔙🌥🌩\n";
+/// let marked = "❷👶😲⛵👅 🍉🧴🦪🦮";
+///
+/// assert!(ecoji::equivalent(wrapped.as_bytes(), marked.as_bytes())?);
+/// assert!(!ecoji::equivalent(wrapped.as_bytes(), "👖📸🎈☕".as_bytes())?);
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn equivalent<A: Read, B: Read>(a: A, b: B) -> io::Result<bool> {
+    equivalent_with(DEFAULT_VERSION, a, b)
+}
+
+/// Checks whether two encoded streams carry the same data, decoding both starting with the given
+/// version, so that text which is wrapped differently, or encoded with different versions, is
+/// found equivalent if the decoded data is the same.
+///
+/// The streams are decoded tolerantly: like
+/// [`Version::decode`](emojis/struct.Version.html#method.decode), the decoder switches to the
+/// other version at its first character which belongs only to that version's alphabet, a
+/// [version marker](emojis/struct.Version.html#method.marker) at the start of a stream selects
+/// the version it is decoded with, and any whitespace is ignored. Both streams are decoded
+/// incrementally, side by side, and only a small part of each is held in memory; the comparison
+/// stops at the first difference.
+///
+/// Fails under the same conditions as [`first_difference_with`](fn.first_difference_with.html).
+pub fn equivalent_with<A: Read, B: Read>(
+    version: &'static Version,
+    a: A,
+    b: B,
+) -> io::Result<bool> {
+    first_difference_with(version, a, b).map(|offset| offset.is_none())
+}
+
+/// Finds the offset of the first byte at which the data carried by two encoded streams differs,
+/// decoding both starting with the [default version](constant.DEFAULT_VERSION.html); see
+/// [`first_difference_with`](fn.first_difference_with.html).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// let a = ecoji::encode_bytes("input data");
+/// let b = ecoji::encode_bytes("input date");
+///
+/// assert_eq!(ecoji::first_difference(a.as_bytes(), b.as_bytes())?, Some(9));
+/// assert_eq!(ecoji::first_difference(a.as_bytes(), "👶😲⛵👅".as_bytes())?, Some(5));
+/// assert_eq!(ecoji::first_difference(a.as_bytes(), a.as_bytes())?, None);
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn first_difference<A: Read, B: Read>(a: A, b: B) -> io::Result<Option<u64>> {
+    first_difference_with(DEFAULT_VERSION, a, b)
+}
+
+/// Finds the offset of the first byte at which the data carried by two encoded streams differs,
+/// decoding both tolerantly starting with the given version like
+/// [`equivalent_with`](fn.equivalent_with.html) does. Returns `None` if the data is the same;
+/// if one stream carries a prefix of the data of the other one, the offset is the length of the
+/// shorter data.
+///
+/// Fails if reading either stream fails, or if either is not valid encoded text, although a
+/// stream whose data differs from the other one before its invalid part may be found to differ
+/// instead.
+pub fn first_difference_with<A: Read, B: Read>(
+    version: &'static Version,
+    a: A,
+    b: B,
+) -> io::Result<Option<u64>> {
+    let codec = Codec::builder()
+        .version(version)
+        .version_marker(true)
+        .ignore_whitespace(true)
+        .build();
+    let mut a = BufReader::new(a);
+    let mut b = BufReader::new(b);
+    let mut a = codec.decoder_reader(&mut a)?;
+    let mut b = codec.decoder_reader(&mut b)?;
+
+    let mut offset = 0;
+    loop {
+        let (a_data, b_data) = (a.fill_buf()?, b.fill_buf()?);
+        let n = a_data.len().min(b_data.len());
+        if let Some(i) = a_data[..n].iter().zip(b_data).position(|(x, y)| x != y) {
+            return Ok(Some(offset + i as u64));
+        }
+        if n == 0 {
+            // at least one of the streams has ended, so both must have
+            let same = a_data.is_empty() && b_data.is_empty();
+            return Ok(if same { None } else { Some(offset) });
+        }
+        a.consume(n);
+        b.consume(n);
+        offset += n as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_invalid_streams() {
+        let valid = "👖📸🎈☕";
+        for invalid in ["👖📸🎈x", "👖📸🎈", "❾👖📸🎈☕"] {
+            assert!(equivalent(valid.as_bytes(), invalid.as_bytes()).is_err());
+            assert!(equivalent(invalid.as_bytes(), valid.as_bytes()).is_err());
+        }
+        // the difference comes before the invalid part
+        let a = crate::encode_bytes("12345") + valid;
+        let b = crate::encode_bytes("54321") + "👖📸🎈x";
+        assert_eq!(
+            first_difference(a.as_bytes(), b.as_bytes()).unwrap(),
+            Some(0)
+        );
+    }
+
+    quickcheck! {
+        fn encodings_are_equivalent(a: Vec<u8>, b: Vec<u8>, wrap: u8) -> bool {
+            let codecs = VERSIONS.map(|v| {
+                Codec::builder()
+                    .version(v)
+                    .wrap(wrap as usize % 8)
+                    .version_marker(wrap.is_multiple_of(2))
+                    .build()
+            });
+            let a1 = codecs[0].encode_to_string(&mut a.as_slice()).unwrap();
+            let a2 = codecs[1].encode_to_string(&mut a.as_slice()).unwrap();
+            let b2 = codecs[1].encode_to_string(&mut b.as_slice()).unwrap();
+
+            let common = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+            let expected = (a != b).then_some(common as u64);
+
+            VERSIONS.iter().all(|&v| {
+                equivalent_with(v, a1.as_bytes(), a2.as_bytes()).unwrap()
+                    && equivalent_with(v, a2.as_bytes(), b2.as_bytes()).unwrap() == (a == b)
+                    && first_difference_with(v, a1.as_bytes(), b2.as_bytes()).unwrap() == expected
+            })
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! `data:` URIs with encoded data.
+
+use std::io;
+
+use crate::emojis::{Version, VERSIONS};
+
+/// A `data:` URI with Ecoji-encoded data, parsed by [`DataUri::parse`](#method.parse).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataUri {
+    mime: String,
+    version: &'static Version,
+    data: Vec<u8>,
+}
+
+impl DataUri {
+    /// Parses a URI in the form produced by
+    /// [`Version::encode_data_uri`](emojis/struct.Version.html#method.encode_data_uri), and
+    /// decodes its data with the version it names. The data may be percent-encoded, like
+    /// [`Version::decode_url`](emojis/struct.Version.html#method.decode_url) accepts.
+    ///
+    /// Fails with an error of the `io::ErrorKind::InvalidData` kind if the URI is not a `data:`
+    /// URI with the `ecoji1` or `ecoji2` encoding, and under the same conditions as `decode_url`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let uri = ecoji::DataUri::parse("data:text/plain;ecoji2,👶😲⛵👅🍉🧴🦪🦮")?;
+    ///
+    /// assert_eq!(uri.mime(), "text/plain");
+    /// assert_eq!(uri.version(), &ecoji::VERSION2);
+    /// assert_eq!(uri.data(), b"input data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn parse(uri: &str) -> io::Result<DataUri> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Not an Ecoji data URI");
+
+        let scheme = uri.get(..5).ok_or_else(invalid)?;
+        if !scheme.eq_ignore_ascii_case("data:") {
+            return Err(invalid());
+        }
+        let (header, payload) = uri[5..].split_once(',').ok_or_else(invalid)?;
+        let (mime, encoding) = header.rsplit_once(';').ok_or_else(invalid)?;
+        let version = VERSIONS
+            .iter()
+            .find(|v| encoding.eq_ignore_ascii_case(&format!("ecoji{}", v.VERSION_NUMBER)))
+            .ok_or_else(invalid)?;
+
+        Ok(DataUri {
+            mime: mime.to_owned(),
+            version,
+            data: version.decode_url(payload)?,
+        })
+    }
+
+    /// Returns the media type, along with its parameters if any; it may be empty.
+    pub fn mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// Returns the version named by the URI.
+    pub fn version(&self) -> &'static Version {
+        self.version
+    }
+
+    /// Returns the decoded data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the decoded data, consuming the URI.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl Version {
+    /// Encodes the data into a `data:` URI with the given media type, naming this version as its
+    /// encoding, e.g. `data:text/plain;ecoji2,...`. The emoji are not percent-encoded; use
+    /// [`encode_url`](#method.encode_url) to build the URI where they have to be.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     ecoji::VERSION2.encode_data_uri("text/plain", b"input data"),
+    ///     "data:text/plain;ecoji2,👶😲⛵👅🍉🧴🦪🦮"
+    /// );
+    /// ```
+    pub fn encode_data_uri(&self, mime: &str, input: &[u8]) -> String {
+        let encoded = self
+            .encode_to_string(&mut &input[..])
+            .expect("Encoding a slice failed");
+        format!("data:{};ecoji{},{}", mime, self.VERSION_NUMBER, encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataUri;
+    use crate::emojis::{VERSION1, VERSIONS};
+
+    #[test]
+    fn test_parse() {
+        let uri = DataUri::parse("DATA:;charset=utf-8;Ecoji1,%F0%9F%91%96📸🎈☕").unwrap();
+        assert_eq!(uri.mime(), ";charset=utf-8");
+        assert_eq!(uri.version(), &VERSION1);
+        assert_eq!(uri.into_data(), b"abc");
+
+        for uri in [
+            "data:text/plain;base64,YWJj",
+            "data:text/plain;ecoji3,",
+            "data:ecoji1,",
+            "text/plain;ecoji1,",
+            "data:text/plain;ecoji1",
+        ] {
+            let e = DataUri::parse(uri).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    quickcheck! {
+        fn parse_reverses_encode_data_uri(mime: String, input: Vec<u8>) -> bool {
+            let mime = mime.replace(',', "");
+            VERSIONS.iter().all(|&v| {
+                let uri = DataUri::parse(&v.encode_data_uri(&mime, &input)).unwrap();
+                uri.mime() == mime && uri.version() == v && uri.data() == &input[..]
+            })
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use std::io::{self, Read, Write};
+use std::slice;
+use std::str;
 
 use crate::chars::{Chars, CharsError};
+use crate::diagnostic::{Diagnostic, DiagnosticKind};
 use crate::emojis::*;
+use crate::error::{DecodeError, DecodeErrorKind, Deviation};
 
 impl Version {
     /// Decodes the entire source from the Ecoji format (assumed to be UTF-8-encoded) and writes the
@@ -15,6 +19,8 @@ impl Version {
     /// of the Ecoji alphabet. No guarantees are made about the state of the destination if an error
     /// occurs, so it is possible for the destination to contain only a part of the decoded data.
     ///
+    /// The output is written with `Write::write_all`, like [`encode`](#method.encode) writes it.
+    ///
     /// # Examples
     ///
     /// Successful decoding:
@@ -78,83 +84,237 @@ impl Version {
         &self,
         source: &mut R,
         destination: &mut W,
+    ) -> io::Result<usize> {
+        let mut decoder = self;
+        self.decode_from(&mut decoder, Settings::default(), source, destination)
+    }
+
+    /// Decodes the entire source like [`decode`](#method.decode), but accepts only the canonical
+    /// encoding, i.e. exactly what [`encode`](#method.encode) produces with either version.
+    ///
+    /// Besides the failure conditions of `decode`, returns an error of the
+    /// [`DecodeErrorKind::NotCanonical`](enum.DecodeErrorKind.html#variant.NotCanonical) kind if
+    /// the input mixes characters of both versions, if its padding is misplaced or doesn't match
+    /// the version, if the unused bits of the final chunk are not zero or if anything follows the
+    /// final chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecoji::{DecodeError, DecodeErrorKind, Deviation};
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    /// ecoji::VERSION2.decode_strict(&mut "👕☕".as_bytes(), &mut output).unwrap();
+    /// assert_eq!(output, b"a");
+    ///
+    /// // Another chunk after a padded one, which `decode` silently ignores
+    /// let e = ecoji::VERSION2
+    ///     .decode_strict(&mut "👕☕👕☕".as_bytes(), &mut output)
+    ///     .unwrap_err();
+    /// let e = DecodeError::from_io(&e).unwrap();
+    /// assert_eq!(e.kind(), &DecodeErrorKind::NotCanonical(Deviation::TrailingData));
+    /// assert_eq!(e.position(), 2);
+    /// ```
+    pub fn decode_strict<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let mut decoder = self;
+        let settings = Settings {
+            strict: true,
+            ..Settings::default()
+        };
+        self.decode_from(&mut decoder, settings, source, destination)
+    }
+
+    /// Decodes the source starting with the given decoder, which is either `self` or, if the
+    /// switch to the other version has already happened, `self.other_version()`. The decoder is
+    /// updated if the switch happens during the decoding.
+    ///
+    /// In the strict mode, the decoder must be `self`, and every chunk is checked to be the
+    /// canonical encoding of the data decoded from it.
+    pub(crate) fn decode_from<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        decoder: &mut &Version,
+        settings: Settings,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let mut state = State::default();
+        self.decode_with_state(decoder, settings, &mut state, source, destination)
+    }
+
+    /// Decodes the entire source like `decode_from`, starting in the given state, which is
+    /// left with the diagnostics collected if it was set up to collect them.
+    pub(crate) fn decode_with_state<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        decoder: &mut &Version,
+        settings: Settings,
+        state: &mut State,
+        source: &mut R,
+        destination: &mut W,
     ) -> io::Result<usize> {
         let mut input = Chars::new(source);
 
         let mut bytes_written = 0;
-        let mut decoder = self;
-        loop {
-            let mut chars = ['\0'; 4];
+        while let Some((out, len)) = self.next_chunk(decoder, settings, state, &mut input)? {
+            destination.write_all(&out[..len])?;
+            bytes_written += len;
+        }
 
-            match input.next() {
-                Some(c) => chars[0] = self.check_char(&mut decoder, c)?,
-                None => break,
-            };
+        Ok(bytes_written)
+    }
 
-            let mut last_was_padding = false;
-            for chars in chars.iter_mut().skip(1) {
-                match input.next() {
-                    Some(c) => {
-                        let c = self.check_char(&mut decoder, c)?;
-                        last_was_padding = decoder.is_padding(c);
-                        *chars = c;
-                    }
-                    None => {
-                        if !last_was_padding {
-                            return Err(io::Error::new(
-                                io::ErrorKind::UnexpectedEof,
-                                "Unexpected end of data, input code points count is not a multiple of 4"));
-                        }
+    /// Decodes the next chunk of the input, returning the decoded bytes along with their number,
+    /// or `None` at the end of the input.
+    pub(crate) fn next_chunk<R: Read>(
+        &self,
+        decoder: &mut &Version,
+        settings: Settings,
+        state: &mut State,
+        input: &mut Chars<R>,
+    ) -> io::Result<Option<([u8; 5], usize)>> {
+        let mut chars = ['\0'; 4];
+        // Byte offsets and code point positions of the characters
+        let mut locations = [(0, 0); 4];
+        let mut count = 1;
+
+        match self.next_char(decoder, settings, state, input)? {
+            Some((c, offset, position)) => {
+                chars[0] = c;
+                locations[0] = (offset, position);
+            }
+            None => return Ok(None),
+        };
+        if settings.strict && state.finished {
+            return Err(not_canonical(Deviation::TrailingData, locations[0]));
+        }
+
+        let mut last_was_padding = false;
+        for i in 1..4 {
+            match self.next_char(decoder, settings, state, input)? {
+                Some((c, offset, position)) => {
+                    last_was_padding = decoder.is_padding(c);
+                    chars[i] = c;
+                    locations[i] = (offset, position);
+                    count += 1;
+                }
+                None => {
+                    if !last_was_padding {
+                        let kind = DecodeErrorKind::UnexpectedEof {
+                            symbols: count,
+                            chunks: state.chunks,
+                        };
+                        return Err(
+                            DecodeError::new(kind, input.bytes_read(), input.chars_read()).into(),
+                        );
                     }
                 }
             }
+        }
 
-            let (bits1, bits2, bits3) = (
-                decoder.EMOJIS_REV.get(&chars[0]).cloned().unwrap_or(0),
-                decoder.EMOJIS_REV.get(&chars[1]).cloned().unwrap_or(0),
-                decoder.EMOJIS_REV.get(&chars[2]).cloned().unwrap_or(0),
-            );
-            let bits4 = if chars[3] == decoder.PADDING_40 {
-                0
-            } else if chars[3] == decoder.PADDING_41 {
-                1 << 8
-            } else if chars[3] == decoder.PADDING_42 {
-                2 << 8
-            } else if chars[3] == decoder.PADDING_43 {
-                3 << 8
-            } else {
-                decoder.EMOJIS_REV.get(&chars[3]).cloned().unwrap_or(0)
-            };
+        let (bits1, bits2, bits3) = (
+            decoder.EMOJIS_REV.get(&chars[0]).cloned().unwrap_or(0),
+            decoder.EMOJIS_REV.get(&chars[1]).cloned().unwrap_or(0),
+            decoder.EMOJIS_REV.get(&chars[2]).cloned().unwrap_or(0),
+        );
+        let bits4 = if chars[3] == decoder.PADDING_40 {
+            0
+        } else if chars[3] == decoder.PADDING_41 {
+            1 << 8
+        } else if chars[3] == decoder.PADDING_42 {
+            2 << 8
+        } else if chars[3] == decoder.PADDING_43 {
+            3 << 8
+        } else {
+            decoder.EMOJIS_REV.get(&chars[3]).cloned().unwrap_or(0)
+        };
 
-            let out = [
-                (bits1 >> 2) as u8,
-                (((bits1 & 0x3) << 6) | (bits2 >> 4)) as u8,
-                (((bits2 & 0xf) << 4) | (bits3 >> 6)) as u8,
-                (((bits3 & 0x3f) << 2) | (bits4 >> 8)) as u8,
-                (bits4 & 0xff) as u8,
-            ];
-
-            let out = if chars[1] == decoder.PADDING {
-                &out[..1]
-            } else if chars[2] == decoder.PADDING {
-                &out[..2]
-            } else if chars[3] == decoder.PADDING {
-                &out[..3]
-            } else if chars[3] == decoder.PADDING_40
-                || chars[3] == decoder.PADDING_41
-                || chars[3] == decoder.PADDING_42
-                || chars[3] == decoder.PADDING_43
-            {
-                &out[..4]
-            } else {
-                &out[..]
-            };
+        let out = [
+            (bits1 >> 2) as u8,
+            (((bits1 & 0x3) << 6) | (bits2 >> 4)) as u8,
+            (((bits2 & 0xf) << 4) | (bits3 >> 6)) as u8,
+            (((bits3 & 0x3f) << 2) | (bits4 >> 8)) as u8,
+            (bits4 & 0xff) as u8,
+        ];
+
+        let len = if chars[1] == decoder.PADDING {
+            1
+        } else if chars[2] == decoder.PADDING {
+            2
+        } else if chars[3] == decoder.PADDING {
+            3
+        } else if decoder.is_padding_4(chars[3]) {
+            4
+        } else {
+            5
+        };
 
-            destination.write_all(out)?;
-            bytes_written += out.len();
+        if settings.strict {
+            for (&c, &location) in chars[..count].iter().zip(&locations) {
+                if state.switched {
+                    break;
+                }
+                if !self.is_valid_alphabet_char(c) {
+                    state.switched = true;
+                    if state.exclusive {
+                        return Err(not_canonical(Deviation::MixedVersions, location));
+                    }
+                } else if !self.other_version().is_valid_alphabet_char(c) {
+                    state.exclusive = true;
+                }
+            }
+            let end = (input.bytes_read(), input.chars_read());
+            let result = decoder.check_chunk(&out[..len], &chars[..count], &locations, end);
+            // As long as every character belongs to both alphabets, the version is not known
+            let undetermined = !settings.locked && !state.switched && !state.exclusive;
+            if let Err(e) = result {
+                if !undetermined
+                    || decoder
+                        .other_version()
+                        .check_chunk(&out[..len], &chars[..count], &locations, end)
+                        .is_err()
+                {
+                    return Err(e);
+                }
+            }
+            state.finished = len < 5;
         }
 
-        Ok(bytes_written)
+        state.chunks += 1;
+        Ok(Some((out, len)))
+    }
+
+    /// Checks that the characters of a chunk are exactly what the encoder produces for the data
+    /// decoded from them. `end` is the location of the end of the input, in case the chunk is
+    /// shorter than expected.
+    fn check_chunk(
+        &self,
+        data: &[u8],
+        chars: &[char],
+        locations: &[(usize, usize); 4],
+        end: (usize, usize),
+    ) -> io::Result<()> {
+        let mut buf = [0; 16];
+        let n = self.encode_chunk(data, &mut &mut buf[..])?;
+        let expected = str::from_utf8(&buf[..n]).expect("Encoder produced invalid UTF-8");
+        let mut expected = expected.chars();
+
+        for (i, &c) in chars.iter().enumerate() {
+            let deviation = match expected.next() {
+                Some(e) if e == c => continue,
+                Some(e) if self.is_padding(e) || self.is_padding(c) => Deviation::MisplacedPadding,
+                Some(_) => Deviation::UnusedBits,
+                None if self.is_padding(c) => Deviation::MisplacedPadding,
+                None => Deviation::TrailingData,
+            };
+            return Err(not_canonical(deviation, locations[i]));
+        }
+        match expected.next() {
+            Some(_) => Err(not_canonical(Deviation::MisplacedPadding, end)),
+            None => Ok(()),
+        }
     }
 
     /// Decodes the entire source from the Ecoji format (assumed to be UTF-8-encoded), storing the
@@ -186,6 +346,49 @@ impl Version {
         Ok(output)
     }
 
+    /// Decodes the characters like [`decode`](#method.decode) decodes their UTF-8 encoding, for
+    /// callers which keep text as characters rather than UTF-8.
+    ///
+    /// Failure conditions are the same as those of `decode`, except that the input is always
+    /// valid. The byte offsets of errors refer to the UTF-8 encoding of the characters, while
+    /// their positions are indices into the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let input: Vec<char> = "👶😲🇲👅🍉🔙🌥🌩".chars().collect();
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    /// ecoji::VERSION1.decode_chars(&input, &mut output)?;
+    ///
+    /// assert_eq!(output, b"input data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_chars<W: Write + ?Sized>(
+        &self,
+        input: &[char],
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let mut source = CharsReader {
+            chars: input.iter(),
+            buf: [0; 4],
+            start: 0,
+            end: 0,
+        };
+        self.decode(&mut source, destination)
+    }
+
+    /// Decodes the characters like [`decode_chars`](#method.decode_chars), storing the result
+    /// of the decoding to a new byte vector.
+    pub fn decode_chars_to_vec(&self, input: &[char]) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self.decode_chars(input, &mut output)?;
+        Ok(output)
+    }
+
     /// Decodes the entire source from the Ecoji format (assumed to be UTF-8-encoded), storing the
     /// result of the decoding to a new owned string.
     ///
@@ -226,31 +429,192 @@ impl Version {
         String::from_utf8(output).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
     }
 
-    fn check_char(&self, decoder: &mut &Version, c: Result<char, CharsError>) -> io::Result<char> {
-        c.map_err(CharsError::into_io).and_then(|c| {
+    /// Reads the next character other than a line break or other whitespace, if they are ignored,
+    /// along with its byte offset and code point position in the input.
+    fn next_char<R: Read>(
+        &self,
+        decoder: &mut &Version,
+        settings: Settings,
+        state: &mut State,
+        input: &mut Chars<R>,
+    ) -> io::Result<Option<(char, usize, usize)>> {
+        let (offset, position, c) = loop {
+            let (offset, position) = (input.bytes_read(), input.chars_read());
+            match input.next() {
+                Some(Ok(c))
+                    if is_line_break(c) && (settings.line_breaks || settings.ignore_whitespace) =>
+                {
+                    continue
+                }
+                Some(Ok(c)) if settings.ignore_whitespace && c.is_whitespace() => {
+                    state.report(DiagnosticKind::SkippedWhitespace(c), offset, position);
+                    continue;
+                }
+                Some(Ok(c)) => break (offset, position, c),
+                Some(Err(CharsError::NotUtf8)) => {
+                    return Err(DecodeError::new(DecodeErrorKind::NotUtf8, offset, position).into())
+                }
+                Some(Err(CharsError::Other(e))) => return Err(e),
+                None => return Ok(None),
+            }
+        };
+
+        if decoder.is_valid_alphabet_char(c) {
+            return Ok(Some((c, offset, position)));
+        }
+
+        // switch to the other decoder if we've not already
+        if !settings.locked && std::ptr::eq(self, *decoder) {
+            *decoder = self.other_version();
             if decoder.is_valid_alphabet_char(c) {
-                return Ok(c);
-            } else {
-                // switch to the other decoder if we've not already
-                if std::ptr::eq(self, *decoder) {
-                    *decoder = self.other_version();
-                    if decoder.is_valid_alphabet_char(c) {
-                        return Ok(c);
+                let kind =
+                    DiagnosticKind::SwitchedVersion(self.VERSION_NUMBER, decoder.VERSION_NUMBER);
+                state.report(kind, offset, position);
+                return Ok(Some((c, offset, position)));
+            }
+        }
+
+        let other = decoder.other_version();
+        let kind = if settings.locked && other.is_valid_alphabet_char(c) {
+            DecodeErrorKind::MixedVersion {
+                character: c,
+                expected: decoder.VERSION_NUMBER,
+                found: other.VERSION_NUMBER,
+            }
+        } else {
+            DecodeErrorKind::InvalidChar(c)
+        };
+        Err(DecodeError::new(kind, offset, position).into())
+    }
+}
+
+/// Settings of the decoder other than the version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Settings {
+    /// Whether only the canonical encoding is accepted.
+    pub strict: bool,
+    /// Whether characters of the other version are rejected instead of switching to it.
+    pub locked: bool,
+    /// Whether line breaks are skipped, e.g. those of wrapped text.
+    pub line_breaks: bool,
+    /// Whether any whitespace is skipped, including line breaks.
+    pub ignore_whitespace: bool,
+}
+
+impl Settings {
+    /// The default settings, but with line breaks skipped.
+    pub(crate) fn wrapped() -> Settings {
+        Settings {
+            line_breaks: true,
+            ..Settings::default()
+        }
+    }
+}
+
+/// Progress of the strict checks and the diagnostics collected, carried over from chunk to chunk.
+#[derive(Debug, Default)]
+pub(crate) struct State {
+    /// Whether the switch to the other version has happened.
+    switched: bool,
+    /// Whether any character which belongs only to the alphabet of this version has been met
+    /// before the switch.
+    exclusive: bool,
+    /// Whether a chunk of less than 5 bytes has been decoded, which must be the final one.
+    finished: bool,
+    /// The number of chunks decoded so far.
+    chunks: usize,
+    /// The tolerated deviations from pristine input, if they are collected.
+    pub diagnostics: Option<Vec<Diagnostic>>,
+}
+
+impl State {
+    /// Returns the initial state, set up to collect diagnostics.
+    pub fn collecting() -> State {
+        State {
+            diagnostics: Some(Vec::new()),
+            ..State::default()
+        }
+    }
+
+    /// Returns the state of decoding which continues where the given progress was recorded.
+    pub fn resumed(progress: Progress) -> State {
+        State {
+            switched: progress.switched,
+            exclusive: progress.exclusive,
+            finished: progress.finished,
+            chunks: progress.chunks,
+            diagnostics: None,
+        }
+    }
+
+    /// Returns the progress made so far, to continue from it later.
+    pub fn progress(&self) -> Progress {
+        Progress {
+            switched: self.switched,
+            exclusive: self.exclusive,
+            finished: self.finished,
+            chunks: self.chunks,
+        }
+    }
+
+    fn report(&mut self, kind: DiagnosticKind, offset: usize, position: usize) {
+        if let Some(ref mut diagnostics) = self.diagnostics {
+            diagnostics.push(Diagnostic::new(kind, offset, position));
+        }
+    }
+}
+
+/// The part of the `State` which decoding continued later starts from, without the diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Progress {
+    switched: bool,
+    exclusive: bool,
+    finished: bool,
+    chunks: usize,
+}
+
+fn not_canonical(deviation: Deviation, (offset, position): (usize, usize)) -> io::Error {
+    DecodeError::new(DecodeErrorKind::NotCanonical(deviation), offset, position).into()
+}
+
+/// A reader yielding the UTF-8 encoding of a slice of characters.
+struct CharsReader<'a> {
+    chars: slice::Iter<'a, char>,
+    /// The encoding of the current character, of which `buf[start..end]` is not read yet.
+    buf: [u8; 4],
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Read for CharsReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            if self.start == self.end {
+                match self.chars.next() {
+                    Some(c) => {
+                        self.start = 0;
+                        self.end = c.encode_utf8(&mut self.buf).len();
                     }
+                    None => break,
                 }
             }
-
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Input character '{}' is not a part of the Ecoji alphabet",
-                    c
-                ),
-            ))
-        })
+            let len = (self.end - self.start).min(buf.len() - n);
+            buf[n..n + len].copy_from_slice(&self.buf[self.start..self.start + len]);
+            self.start += len;
+            n += len;
+        }
+        Ok(n)
     }
 }
 
+/// Checks whether the character is a line break, which the decoder skips if configured to.
+pub(crate) fn is_line_break(c: char) -> bool {
+    c == '\n' || c == '\r'
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,6 +638,21 @@ mod tests {
         assert_eq!(output, buf.as_slice());
     }
 
+    #[test]
+    fn test_chars() {
+        for v in VERSIONS {
+            let encoded = v.encode_to_chars(&mut &b"abcdefg"[..]).unwrap();
+            assert_eq!(v.decode_chars_to_vec(&encoded).unwrap(), b"abcdefg");
+        }
+
+        let input: Vec<char> = "👖📸🎈☕👖x".chars().collect();
+        let e = VERSION1.decode_chars_to_vec(&input).unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e),
+            Some(&DecodeError::new(DecodeErrorKind::InvalidChar('x'), 19, 5))
+        );
+    }
+
     #[test]
     fn test_random() {
         check_all(&["👖📸🎈☕".as_bytes(), "👖📸🎈☕".as_bytes()], b"abc");
@@ -353,4 +732,164 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_line_breaks() {
+        for text in ["👖📸\n🎈☕", "\r\n👖📸🎈☕\r\n", "👖\n📸\n🎈\n☕"] {
+            for v in VERSIONS {
+                assert_eq!(decode_wrapped(v, false, text).unwrap(), b"abc");
+                assert!(v.decode_to_vec(&mut text.as_bytes()).is_err());
+            }
+        }
+        check_error(
+            "👖📸\n🎈☕".as_bytes(),
+            DecodeErrorKind::InvalidChar('\n'),
+            8,
+            2,
+        );
+        let e = decode_wrapped(&VERSION1, false, "👖📸\n🎈").unwrap_err();
+        let kind = DecodeErrorKind::UnexpectedEof {
+            symbols: 3,
+            chunks: 0,
+        };
+        assert_eq!(
+            DecodeError::from_io(&e),
+            Some(&DecodeError::new(kind, 13, 4))
+        );
+        for text in ["👖📸 🎈☕", "👖📸\t🎈☕"] {
+            assert!(decode_wrapped(&VERSION1, false, text).is_err());
+        }
+    }
+
+    /// Decodes the text with line breaks skipped, strictly if `strict` is set.
+    fn decode_wrapped(v: &Version, strict: bool, text: &str) -> io::Result<Vec<u8>> {
+        let settings = Settings {
+            strict,
+            ..Settings::wrapped()
+        };
+        let mut output = Vec::new();
+        v.decode_from(&mut { v }, settings, &mut text.as_bytes(), &mut output)?;
+        Ok(output)
+    }
+
+    /// Breaks the text into lines of `width` characters, each ending in `line_break`.
+    fn wrap(text: &str, width: usize, line_break: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        chars
+            .chunks(width)
+            .map(|line| line.iter().collect::<String>() + line_break)
+            .collect()
+    }
+
+    quickcheck! {
+        fn wrapped_is_the_same(input: Vec<u8>, width: u8, crlf: bool) -> bool {
+            let line_break = if crlf { "\r\n" } else { "\n" };
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let wrapped = wrap(&encoded, width as usize % 8 + 1, line_break);
+                decode_wrapped(v, false, &wrapped).unwrap() == input
+                    && decode_wrapped(v, true, &wrapped).unwrap() == input
+            })
+        }
+    }
+
+    fn check_error(mut input: &[u8], kind: DecodeErrorKind, offset: usize, position: usize) {
+        let e = VERSION1.decode_to_vec(&mut input).unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e),
+            Some(&DecodeError::new(kind, offset, position))
+        );
+    }
+
+    #[test]
+    fn test_error_positions() {
+        check_error(
+            "👖📸🎈☕👖x".as_bytes(),
+            DecodeErrorKind::InvalidChar('x'),
+            19,
+            5,
+        );
+        check_error(
+            "👖📸🎈☕👖📸".as_bytes(),
+            DecodeErrorKind::UnexpectedEof {
+                symbols: 2,
+                chunks: 1,
+            },
+            23,
+            6,
+        );
+        check_error(b"\xf0\x9f\x91\x96\xff", DecodeErrorKind::NotUtf8, 4, 1);
+    }
+
+    fn check_deviation(v: &Version, input: &[char], deviation: Deviation, position: usize) {
+        let input: String = input.iter().cloned().collect();
+        let e = v
+            .decode_strict(&mut input.as_bytes(), &mut Vec::new())
+            .unwrap_err();
+        let e = DecodeError::from_io(&e).unwrap();
+        assert_eq!(e.kind(), &DecodeErrorKind::NotCanonical(deviation));
+        assert_eq!(e.position(), position);
+    }
+
+    #[test]
+    fn test_strict() {
+        for v in VERSIONS {
+            for input in [&b""[..], b"a", b"ab", b"abc", b"abcd", b"abcde", b"abcdef"] {
+                let encoded = v.encode_to_string(&mut &input[..]).unwrap();
+                let wrapped = encoded.replace('\u{1f4f8}', "\u{1f4f8}\r\n");
+                let mut output = Vec::new();
+                v.other_version()
+                    .decode_strict(&mut encoded.as_bytes(), &mut output)
+                    .unwrap();
+                assert_eq!(output, input);
+                assert_eq!(
+                    decode_wrapped(v.other_version(), true, &wrapped).unwrap(),
+                    input
+                );
+            }
+
+            let (a, padding) = (v.EMOJIS[('a' as usize) << 2], v.PADDING);
+            check_deviation(v, &[a, v.EMOJIS[1], padding], Deviation::UnusedBits, 1);
+            let chunk: Vec<char> = v
+                .encode_to_string(&mut &b"a"[..])
+                .unwrap()
+                .chars()
+                .collect();
+            let twice: Vec<char> = chunk.iter().chain(&chunk).cloned().collect();
+            check_deviation(v, &twice, Deviation::TrailingData, chunk.len());
+            check_deviation(v, &[padding, a, a, a], Deviation::MisplacedPadding, 0);
+            check_deviation(
+                v,
+                &[a, a, v.PADDING_40, a, a, a, a, a],
+                Deviation::MisplacedPadding,
+                2,
+            );
+            check_deviation(
+                v,
+                &[a, a, a, v.PADDING_40, a, a, a, a],
+                Deviation::TrailingData,
+                4,
+            );
+        }
+
+        // Characters which exist only in one of the versions and encode a single byte
+        let exclusive = |v: &Version| {
+            v.EMOJIS
+                .iter()
+                .step_by(4)
+                .cloned()
+                .find(|&c| !v.other_version().is_valid_alphabet_char(c))
+                .unwrap()
+        };
+        let (x1, x2) = (exclusive(&VERSION1), exclusive(&VERSION2));
+        let (p1, p2) = (VERSION1.PADDING, VERSION2.PADDING);
+        check_deviation(&VERSION1, &[x1, p1], Deviation::MisplacedPadding, 2);
+        check_deviation(&VERSION2, &[x2, p2, p2, p2], Deviation::MisplacedPadding, 2);
+        check_deviation(
+            &VERSION1,
+            &[x1, x1, x1, x1, x2, p2],
+            Deviation::MixedVersions,
+            4,
+        );
+    }
 }
@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// A deviation from pristine input which the decoder tolerated, collected by
+/// [`Codec::decode_with_diagnostics`](struct.Codec.html#method.decode_with_diagnostics).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    kind: DiagnosticKind,
+    offset: usize,
+    position: usize,
+}
+
+/// The kind of a [`Diagnostic`](struct.Diagnostic.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// A whitespace character other than a line break was skipped, as configured with
+    /// [`Builder::ignore_whitespace`](struct.Builder.html#method.ignore_whitespace).
+    SkippedWhitespace(char),
+    /// The decoder switched from the version with the first number to the version with the second
+    /// number at a character which only belongs to the alphabet of the latter, as allowed by
+    /// [`VersionPolicy::Auto`](enum.VersionPolicy.html#variant.Auto).
+    SwitchedVersion(usize, usize),
+}
+
+impl Diagnostic {
+    pub(crate) fn new(kind: DiagnosticKind, offset: usize, position: usize) -> Diagnostic {
+        Diagnostic {
+            kind,
+            offset,
+            position,
+        }
+    }
+
+    /// Returns the kind of this diagnostic.
+    pub fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+
+    /// Returns the offset, in bytes, of the character in the input which the diagnostic is about.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the position, in code points, of the character in the input which the diagnostic
+    /// is about.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            DiagnosticKind::SkippedWhitespace(c) => write!(f, "Skipped whitespace {:?}", c)?,
+            DiagnosticKind::SwitchedVersion(from, to) => write!(
+                f,
+                "Switched from version {} to version {} of the alphabet",
+                from, to
+            )?,
+        }
+        write!(
+            f,
+            " (at code point {}, byte {})",
+            self.position, self.offset
+        )
+    }
+}
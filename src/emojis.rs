@@ -1,3 +1,9 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A version of the Ecoji alphabet, along with its padding characters.
+///
+/// Versions are compared and hashed by their numbers, and displayed as `version N`.
 #[allow(non_snake_case)]
 pub struct Version {
     pub VERSION_NUMBER: usize,
@@ -13,6 +19,7 @@ pub struct Version {
 include!(concat!(env!("OUT_DIR"), "/emojis.rs"));
 
 impl Version {
+    /// Returns the other version of the alphabet.
     pub fn other_version(&self) -> &'static Version {
         match self.VERSION_NUMBER {
             1 => &VERSION2,
@@ -21,6 +28,62 @@ impl Version {
         }
     }
 
+    /// Returns the padding character which fills up, or in version 2 terminates, a final chunk
+    /// of less than 4 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ecoji::VERSION1.padding(), '\u{2615}');
+    /// assert_eq!(ecoji::VERSION2.padding(), '\u{2615}');
+    /// ```
+    pub fn padding(&self) -> char {
+        self.PADDING
+    }
+
+    /// Returns the padding character which ends a final chunk of exactly 4 bytes, standing for
+    /// the given value of the two lowest bits of the last byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not less than 4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ecoji::VERSION1.padding_4(0), '\u{269c}');
+    /// assert_eq!(ecoji::VERSION2.padding_4(0), '\u{1f977}');
+    /// ```
+    pub fn padding_4(&self, bits: usize) -> char {
+        match bits {
+            0 => self.PADDING_40,
+            1 => self.PADDING_41,
+            2 => self.PADDING_42,
+            3 => self.PADDING_43,
+            _ => panic!("Invalid padding bits {}", bits),
+        }
+    }
+
+    /// Returns the marker which identifies this version at the start of the encoded text, if the
+    /// encoder is configured to write it with
+    /// [`Builder::version_marker`](../struct.Builder.html#method.version_marker). The markers
+    /// are the dingbat negative circled digits, which don't belong to any alphabet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ecoji::VERSION1.marker(), '\u{2776}');
+    /// assert_eq!(ecoji::VERSION2.marker(), '\u{2777}');
+    /// ```
+    pub fn marker(&self) -> char {
+        match self.VERSION_NUMBER {
+            1 => crate::tables::V1_MARKER,
+            2 => crate::tables::V2_MARKER,
+            _ => unreachable!("No marker for version {}", self.VERSION_NUMBER),
+        }
+    }
+
+    /// Checks whether the character is any of the padding characters of this version.
     pub fn is_padding(&self, c: char) -> bool {
         [
             self.PADDING,
@@ -31,8 +94,68 @@ impl Version {
         ]
         .contains(&c)
     }
+
+    /// Checks whether the character is one of the padding characters which end a final chunk of
+    /// 4 bytes, as opposed to the [`padding`](#method.padding) character.
+    pub fn is_padding_4(&self, c: char) -> bool {
+        (0..4).any(|bits| self.padding_4(bits) == c)
+    }
+
+    /// Checks whether the character belongs to the alphabet of this version, including the
+    /// padding characters.
     pub fn is_valid_alphabet_char(&self, c: char) -> bool {
-        self.is_padding(c) || self.EMOJIS_REV.contains_key(&c)
+        self.alphabet_set().contains(c)
+    }
+}
+
+impl fmt::Debug for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Version")
+            .field("VERSION_NUMBER", &self.VERSION_NUMBER)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "version {}", self.VERSION_NUMBER)
+    }
+}
+
+/// Serializes the version as its number.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.VERSION_NUMBER as u64)
+    }
+}
+
+/// Deserializes a version from its number.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for &'static Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
+
+        let number = u64::deserialize(deserializer)?;
+        VERSIONS
+            .iter()
+            .find(|v| v.VERSION_NUMBER as u64 == number)
+            .cloned()
+            .ok_or_else(|| Error::invalid_value(Unexpected::Unsigned(number), &"1 or 2"))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Version) -> bool {
+        self.VERSION_NUMBER == other.VERSION_NUMBER
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.VERSION_NUMBER.hash(state);
     }
 }
 
@@ -46,3 +169,31 @@ fn test_mapping() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traits() {
+        assert_eq!(VERSION1, VERSION1);
+        assert_ne!(VERSION1, VERSION2);
+        assert_eq!(VERSION2.to_string(), "version 2");
+        assert_eq!(
+            format!("{:?}", VERSION1),
+            "Version { VERSION_NUMBER: 1, .. }"
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        for v in VERSIONS {
+            assert!(v.is_padding(v.padding()));
+            assert!(!v.is_padding_4(v.padding()));
+            for bits in 0..4 {
+                assert!(v.is_padding(v.padding_4(bits)));
+                assert!(v.is_padding_4(v.padding_4(bits)));
+            }
+        }
+    }
+}
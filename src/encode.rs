@@ -1,7 +1,23 @@
 use crate::emojis::*;
 use std::io::{self, Read, Write};
+use std::str;
 impl Version {
-    fn encode_chunk<W: Write + ?Sized>(&self, s: &[u8], out: &mut W) -> io::Result<usize> {
+    pub(crate) fn encode_chunk<W: Write + ?Sized>(
+        &self,
+        s: &[u8],
+        out: &mut W,
+    ) -> io::Result<usize> {
+        self.encode_chunk_padded(s, out, false)
+    }
+
+    /// Encodes the chunk like `encode_chunk`, but if `full_padding` is set, pads the final chunk
+    /// to 4 symbols even with version 2, like version 1 does.
+    pub(crate) fn encode_chunk_padded<W: Write + ?Sized>(
+        &self,
+        s: &[u8],
+        out: &mut W,
+        full_padding: bool,
+    ) -> io::Result<usize> {
         assert!(!s.is_empty() && s.len() <= 5, "Unexpected slice length");
 
         let (b0, b1, b2, b3, b4) = (
@@ -53,7 +69,7 @@ impl Version {
             out.write_all(s)?;
             bytes_written += s.len();
 
-            if self.VERSION_NUMBER == 2 && self.is_padding(*c) {
+            if self.VERSION_NUMBER == 2 && !full_padding && self.is_padding(*c) {
                 break;
             }
         }
@@ -70,6 +86,11 @@ impl Version {
     /// made about the state of the destination if an error occurs, so it is possible for the
     /// destination to contain only a part of the encoded data.
     ///
+    /// The output is written with `Write::write_all`, which continues after short writes, and
+    /// fails with an error of the `io::ErrorKind::WriteZero` kind if the destination accepts no
+    /// bytes. Use a [`Codec`](../struct.Codec.html) with a [`ShortWrites`](../enum.ShortWrites.html)
+    /// policy for destinations which may be temporarily unable to take more output.
+    ///
     /// # Examples
     ///
     /// Successful encoding:
@@ -90,6 +111,17 @@ impl Version {
         &self,
         source: &mut R,
         destination: &mut W,
+    ) -> io::Result<usize> {
+        self.encode_padded(source, destination, false)
+    }
+
+    /// Encodes the entire source like `encode`, padding the final chunk to 4 symbols if
+    /// `full_padding` is set.
+    pub(crate) fn encode_padded<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+        full_padding: bool,
     ) -> io::Result<usize> {
         let mut buf = [0; 5];
         let mut bytes_written = 0;
@@ -102,7 +134,7 @@ impl Version {
                 break;
             }
 
-            bytes_written += self.encode_chunk(&buf[..n], destination)?;
+            bytes_written += self.encode_chunk_padded(&buf[..n], destination, full_padding)?;
         }
 
         Ok(bytes_written)
@@ -137,9 +169,83 @@ impl Version {
         // encoded output is guaranteed to be valid UTF-8
         Ok(unsafe { String::from_utf8_unchecked(output) })
     }
+
+    /// Encodes the entire source into the Ecoji format, appending the encoded symbols to any
+    /// collection of characters, like a `String`, a `Vec<char>` or the text buffer of an editor.
+    /// The symbols of each chunk are appended as soon as it is encoded.
+    ///
+    /// If successful, returns the number of symbols which were appended to the destination.
+    ///
+    /// Failure conditions are exactly the same as those of the [`encode`](#method.encode)
+    /// function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::VecDeque;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let mut output: VecDeque<char> = VecDeque::new();
+    /// let n = ecoji::VERSION1.encode_chars(&mut "input data".as_bytes(), &mut output)?;
+    ///
+    /// assert_eq!(n, 8);
+    /// assert_eq!(output.iter().collect::<String>(), "👶😲🇲👅🍉🔙🌥🌩");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn encode_chars<R: Read + ?Sized, E: Extend<char> + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut E,
+    ) -> io::Result<usize> {
+        let mut buf = [0; 5];
+        let mut chars_written = 0;
+        loop {
+            let n = read_exact(source, &mut buf)?;
+
+            // EOF
+            if n == 0 {
+                break;
+            }
+
+            let mut encoded = [0; 16];
+            let len = self.encode_chunk(&buf[..n], &mut &mut encoded[..])?;
+            let encoded = str::from_utf8(&encoded[..len]).expect("Encoder produced invalid UTF-8");
+            chars_written += encoded.chars().count();
+            destination.extend(encoded.chars());
+        }
+        Ok(chars_written)
+    }
+
+    /// Encodes the entire source into the Ecoji format, storing the encoded symbols to a new
+    /// vector of characters, for callers which keep text as characters rather than UTF-8.
+    ///
+    /// Failure conditions are exactly the same as those of the [`encode`](#method.encode)
+    /// function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let output: Vec<char> = ecoji::VERSION1.encode_to_chars(&mut "input data".as_bytes())?;
+    ///
+    /// assert_eq!(output, "👶😲🇲👅🍉🔙🌥🌩".chars().collect::<Vec<_>>());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn encode_to_chars<R: Read + ?Sized>(&self, source: &mut R) -> io::Result<Vec<char>> {
+        let mut output = Vec::new();
+        self.encode_chars(source, &mut output)?;
+        Ok(output)
+    }
 }
 
-fn read_exact<R: Read + ?Sized>(source: &mut R, mut buf: &mut [u8]) -> io::Result<usize> {
+pub(crate) fn read_exact<R: Read + ?Sized>(
+    source: &mut R,
+    mut buf: &mut [u8],
+) -> io::Result<usize> {
     let mut bytes_read = 0;
     while !buf.is_empty() {
         match source.read(buf) {
@@ -160,6 +266,7 @@ fn read_exact<R: Read + ?Sized>(source: &mut R, mut buf: &mut [u8]) -> io::Resul
 mod tests {
     use super::*;
 
+    #[allow(clippy::needless_as_bytes)]
     fn check(v: &Version, mut input: &[u8], output: &[u8]) {
         let encoded = v.encode_to_string(&mut input).unwrap();
         dbg!(output.len());
@@ -169,6 +276,7 @@ mod tests {
         assert_eq!(output, encoded.as_bytes());
     }
 
+    #[allow(clippy::iter_cloned_collect)]
     fn check_chars(v: &Version, mut input: &[u8], output: &[char]) {
         let buf = v.encode_to_string(&mut input).unwrap();
         let chars: Vec<_> = buf.chars().collect();
@@ -181,6 +289,7 @@ mod tests {
         assert_eq!(output, chars.as_slice());
     }
 
+    #[allow(clippy::needless_borrow)]
     fn check_all(input: &[u8], output: &[&[u8]]) {
         for (i, v) in VERSIONS.iter().enumerate() {
             dbg!(v.VERSION_NUMBER);
@@ -188,6 +297,39 @@ mod tests {
         }
     }
 
+    /// Identifies the symbol independently of the version: by its index in the alphabet, or for
+    /// a padding character, by its kind.
+    fn symbol(v: &Version, c: char) -> Result<usize, Option<usize>> {
+        if c == v.PADDING {
+            Err(None)
+        } else if v.is_padding_4(c) {
+            Err((0..4).find(|&bits| v.padding_4(bits) == c))
+        } else {
+            Ok(v.EMOJIS_REV[&c])
+        }
+    }
+
+    quickcheck! {
+        fn versions_differ_in_glyphs_and_truncation(input: Vec<u8>) -> bool {
+            let [v1, v2] = VERSIONS.map(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                encoded.chars().map(|c| symbol(v, c)).collect::<Vec<_>>()
+            });
+            // version 2 only leaves out the padding following the first padding character
+            let first_padding = v1.iter().position(Result::is_err).map_or(v1.len(), |i| i + 1);
+            v2 == v1[..first_padding]
+        }
+
+        fn encode_chars_is_the_same_as_encode(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let mut output = String::new();
+                let n = v.encode_chars(&mut input.as_slice(), &mut output).unwrap();
+                n == output.chars().count()
+                    && output == v.encode_to_string(&mut input.as_slice()).unwrap()
+            })
+        }
+    }
+
     #[test]
     fn test_random() {
         check_all(b"abc", &["👖📸🎈☕".as_bytes(), "👖📸🎈☕".as_bytes()]);
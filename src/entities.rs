@@ -0,0 +1,114 @@
+//! Output as numeric character references, for embedding encoded data in HTML and XML.
+
+use std::fmt::Write as _;
+use std::io;
+
+use crate::emojis::Version;
+
+impl Version {
+    /// Encodes the data and writes every symbol of the result as a hexadecimal numeric character
+    /// reference, e.g. `&#x1F476;`, so that it can be put into HTML or XML documents which are
+    /// not reliably kept in UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     ecoji::VERSION1.encode_html(b"abc"),
+    ///     "&#x1F456;&#x1F4F8;&#x1F388;&#x2615;"
+    /// );
+    /// ```
+    pub fn encode_html(&self, input: &[u8]) -> String {
+        let encoded = self
+            .encode_to_string(&mut &input[..])
+            .expect("Encoding a slice failed");
+        let mut output = String::with_capacity(encoded.chars().count() * 10);
+        for c in encoded.chars() {
+            write!(output, "&#x{:X};", c as u32).unwrap();
+        }
+        output
+    }
+
+    /// Replaces the numeric character references in the input, either hexadecimal or decimal,
+    /// with the characters they refer to, and decodes the result, like [`decode`](#method.decode)
+    /// does. Characters which are not escaped are taken as they are.
+    ///
+    /// Fails under the same conditions as `decode`, with a `&` which does not start a valid
+    /// numeric character reference treated as an invalid character. Error offsets refer to the
+    /// input with the references replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let output = ecoji::VERSION1.decode_html("&#x1F456;&#128248;&#x1f388;☕")?;
+    ///
+    /// assert_eq!(output, b"abc");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_html(&self, input: &str) -> io::Result<Vec<u8>> {
+        let mut unescaped = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find('&') {
+            unescaped.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match parse_reference(rest) {
+                Some((c, len)) => {
+                    unescaped.push(c);
+                    rest = &rest[len..];
+                }
+                None => {
+                    unescaped.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        unescaped.push_str(rest);
+        self.decode_to_vec(&mut unescaped.as_bytes())
+    }
+}
+
+/// Parses the numeric character reference at the start of the input, returning the character
+/// along with the length of the reference.
+fn parse_reference(input: &str) -> Option<(char, usize)> {
+    let end = input.find(';')?;
+    let number = input[..end].strip_prefix("&#")?;
+    let (digits, radix) = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => (hex, 16),
+        None => (number, 10),
+    };
+    // from_str_radix would also accept a sign
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let value = u32::from_str_radix(digits, radix).ok()?;
+    char::from_u32(value).map(|c| (c, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::{VERSION1, VERSIONS};
+    use crate::error::{DecodeError, DecodeErrorKind};
+
+    #[test]
+    fn test_invalid_reference() {
+        for input in ["&#x1F456", "&#x;", "&#+128248;", "&#xD800;", "&amp;"] {
+            let e = VERSION1.decode_html(input).unwrap_err();
+            assert_eq!(
+                DecodeError::from_io(&e).map(DecodeError::kind),
+                Some(&DecodeErrorKind::InvalidChar('&'))
+            );
+        }
+    }
+
+    quickcheck! {
+        fn decode_html_reverses_encode_html(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let html = v.encode_html(&input);
+                html.is_ascii() && v.decode_html(&html).unwrap() == input
+            })
+        }
+    }
+}
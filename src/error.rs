@@ -0,0 +1,500 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+/// An error which occurred while decoding Ecoji data.
+///
+/// The streaming decoding functions return `std::io::Result`, so decoding errors are reported
+/// as `io::Error` values wrapping a `DecodeError`. Use [`DecodeError::from_io`] to extract it
+/// and find out what exactly went wrong and where.
+///
+/// [`DecodeError::from_io`]: struct.DecodeError.html#method.from_io
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DecodeError {
+    kind: DecodeErrorKind,
+    offset: usize,
+    position: usize,
+}
+
+/// The kind of a [`DecodeError`](struct.DecodeError.html).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The input is not a valid UTF-8 stream.
+    NotUtf8,
+    /// The input code point is not a part of the Ecoji alphabet.
+    InvalidChar(char),
+    /// The input ended in the middle of an encoded chunk, e.g. because it was cut off in
+    /// transport.
+    UnexpectedEof {
+        /// The number of symbols of the incomplete chunk, which has 4 when complete: 1, 2 or 3,
+        /// or 0 if the input is empty although data is required, like the value of a
+        /// [short ID](short_id/index.html).
+        symbols: usize,
+        /// The number of complete chunks decoded before the incomplete one.
+        chunks: usize,
+    },
+    /// The input code point belongs only to the alphabet of the version numbered `found`, while
+    /// the decoder is locked to the version numbered `expected`. Only reported with the
+    /// [`VersionPolicy::Strict`](enum.VersionPolicy.html#variant.Strict) policy.
+    MixedVersion {
+        /// The input code point.
+        character: char,
+        /// The number of the version the decoder is locked to.
+        expected: usize,
+        /// The number of the version whose alphabet contains the code point.
+        found: usize,
+    },
+    /// The decoded data does not fit into the buffer provided by the caller, which must be
+    /// `needed` bytes long. Only reported by
+    /// [`Version::decode_into`](emojis/struct.Version.html#method.decode_into), at the end of
+    /// the input.
+    BufferTooSmall {
+        /// The number of bytes of the decoded data.
+        needed: usize,
+    },
+    /// The input could be decoded, but it is not exactly what the encoder produces. Only reported
+    /// by [`Version::decode_strict`](emojis/struct.Version.html#method.decode_strict).
+    NotCanonical(Deviation),
+    /// The checksum symbol of a short ID does not match its value. Only reported by
+    /// [`short_id::decode`](short_id/fn.decode.html); the location is that of the checksum
+    /// symbol.
+    ChecksumMismatch,
+}
+
+/// The way in which the input deviates from the canonical encoding, as reported by
+/// [`DecodeErrorKind::NotCanonical`](enum.DecodeErrorKind.html#variant.NotCanonical).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Deviation {
+    /// The input contains characters of both versions of the alphabet.
+    MixedVersions,
+    /// A padding character is not where the encoder puts it, or the final chunk is padded with
+    /// a different number of padding characters than the encoder of its version uses.
+    MisplacedPadding,
+    /// Bits of the final chunk which do not correspond to any byte of the data are not zero.
+    UnusedBits,
+    /// More data follows the final, padded chunk.
+    TrailingData,
+}
+
+/// A stable numeric code identifying the cause of an error, for reporting it to other programs,
+/// e.g. through a C interface, in JSON output or in logs, where the messages of the errors are not
+/// suitable for matching.
+///
+/// The code of an error returned by the library is found with [`of`](#method.of), and those of
+/// the error types of this library with their `code` methods. The values and the names of the
+/// codes never change; new codes may be added in future releases.
+///
+/// # Examples
+///
+/// ```
+/// use ecoji::ErrorCode;
+///
+/// let e = ecoji::decode_to_vec(&mut "👶😲🇲👅🍉🔙🌥x".as_bytes()).unwrap_err();
+/// assert_eq!(ErrorCode::of(&e), ErrorCode::InvalidChar);
+/// assert_eq!(ErrorCode::of(&e).value(), 11);
+/// assert_eq!(ErrorCode::of(&e).name(), "invalid_char");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum ErrorCode {
+    /// Reading the source or writing the destination failed, or any other error without a more
+    /// specific code.
+    Io = 1,
+    /// The input or the output is invalid in a way without a more specific code, e.g. decoded
+    /// data of the wrong length or with an unsupported version marker.
+    InvalidData = 2,
+    /// The operation was cancelled with
+    /// [`Builder::cancel_flag`](struct.Builder.html#method.cancel_flag).
+    Cancelled = 3,
+    /// [`DecodeErrorKind::NotUtf8`](enum.DecodeErrorKind.html#variant.NotUtf8).
+    NotUtf8 = 10,
+    /// [`DecodeErrorKind::InvalidChar`](enum.DecodeErrorKind.html#variant.InvalidChar).
+    InvalidChar = 11,
+    /// [`DecodeErrorKind::UnexpectedEof`](enum.DecodeErrorKind.html#variant.UnexpectedEof).
+    UnexpectedEof = 12,
+    /// [`DecodeErrorKind::MixedVersion`](enum.DecodeErrorKind.html#variant.MixedVersion).
+    MixedVersion = 13,
+    /// [`DecodeErrorKind::BufferTooSmall`](enum.DecodeErrorKind.html#variant.BufferTooSmall),
+    /// or an [`EncodeError`](struct.EncodeError.html).
+    BufferTooSmall = 14,
+    /// [`DecodeErrorKind::ChecksumMismatch`](enum.DecodeErrorKind.html#variant.ChecksumMismatch).
+    ChecksumMismatch = 15,
+    /// [`DecodeErrorKind::NotCanonical`](enum.DecodeErrorKind.html#variant.NotCanonical) with
+    /// [`Deviation::MixedVersions`](enum.Deviation.html#variant.MixedVersions).
+    MixedVersions = 20,
+    /// `DecodeErrorKind::NotCanonical` with
+    /// [`Deviation::MisplacedPadding`](enum.Deviation.html#variant.MisplacedPadding).
+    MisplacedPadding = 21,
+    /// `DecodeErrorKind::NotCanonical` with
+    /// [`Deviation::UnusedBits`](enum.Deviation.html#variant.UnusedBits).
+    UnusedBits = 22,
+    /// `DecodeErrorKind::NotCanonical` with
+    /// [`Deviation::TrailingData`](enum.Deviation.html#variant.TrailingData).
+    TrailingData = 23,
+    /// The output exceeds the limit set with
+    /// [`Builder::max_output`](struct.Builder.html#method.max_output).
+    OutputLimit = 30,
+    /// The input exceeds the limit set with
+    /// [`Builder::max_input_bytes`](struct.Builder.html#method.max_input_bytes).
+    InputLimit = 31,
+    /// A [`ShortWrite`](struct.ShortWrite.html).
+    ShortWrite = 32,
+}
+
+impl ErrorCode {
+    /// Returns the code of an `io::Error` returned by one of the functions of this library.
+    pub fn of(e: &io::Error) -> ErrorCode {
+        if let Some(inner) = e.get_ref() {
+            if let Some(e) = inner.downcast_ref::<DecodeError>() {
+                return e.code();
+            }
+            if let Some(e) = inner.downcast_ref::<EncodeError>() {
+                return e.code();
+            }
+            if let Some(e) = inner.downcast_ref::<ShortWrite>() {
+                return e.code();
+            }
+            if let Some(e) = inner.downcast_ref::<CodedError>() {
+                return e.code;
+            }
+        }
+        match e.kind() {
+            io::ErrorKind::InvalidData => ErrorCode::InvalidData,
+            _ => ErrorCode::Io,
+        }
+    }
+
+    /// Returns the numeric value of the code.
+    pub fn value(self) -> u32 {
+        self as u32
+    }
+
+    /// Returns the name of the code in snake case, e.g. `invalid_char`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorCode::Io => "io",
+            ErrorCode::InvalidData => "invalid_data",
+            ErrorCode::Cancelled => "cancelled",
+            ErrorCode::NotUtf8 => "not_utf8",
+            ErrorCode::InvalidChar => "invalid_char",
+            ErrorCode::UnexpectedEof => "unexpected_eof",
+            ErrorCode::MixedVersion => "mixed_version",
+            ErrorCode::BufferTooSmall => "buffer_too_small",
+            ErrorCode::ChecksumMismatch => "checksum_mismatch",
+            ErrorCode::MixedVersions => "mixed_versions",
+            ErrorCode::MisplacedPadding => "misplaced_padding",
+            ErrorCode::UnusedBits => "unused_bits",
+            ErrorCode::TrailingData => "trailing_data",
+            ErrorCode::OutputLimit => "output_limit",
+            ErrorCode::InputLimit => "input_limit",
+            ErrorCode::ShortWrite => "short_write",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An error of the codec without a public type of its own, like exceeding a limit, carrying its
+/// code.
+#[derive(Debug)]
+pub(crate) struct CodedError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl CodedError {
+    /// Creates an `io::Error` of the `io::ErrorKind::Other` kind with the code and the message.
+    pub(crate) fn io(code: ErrorCode, message: impl Into<String>) -> io::Error {
+        io::Error::other(CodedError {
+            code,
+            message: message.into(),
+        })
+    }
+}
+
+impl error::Error for CodedError {}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl DecodeError {
+    pub(crate) fn new(kind: DecodeErrorKind, offset: usize, position: usize) -> DecodeError {
+        DecodeError {
+            kind,
+            offset,
+            position,
+        }
+    }
+
+    /// Moves the error location forward by the given amounts, for errors detected in a fragment
+    /// of the input which does not start at its beginning.
+    pub(crate) fn shifted(mut self, offset: usize, position: usize) -> DecodeError {
+        self.offset += offset;
+        self.position += position;
+        self
+    }
+
+    /// Adds the given number of chunks to those decoded before an unexpected end of the input,
+    /// for errors detected in a fragment of the input which starts after them.
+    pub(crate) fn after_chunks(mut self, chunks: usize) -> DecodeError {
+        if let DecodeErrorKind::UnexpectedEof {
+            chunks: ref mut decoded,
+            ..
+        } = self.kind
+        {
+            *decoded += chunks;
+        }
+        self
+    }
+
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> &DecodeErrorKind {
+        &self.kind
+    }
+
+    /// Returns the stable numeric code of the kind of this error.
+    pub fn code(&self) -> ErrorCode {
+        match self.kind {
+            DecodeErrorKind::NotUtf8 => ErrorCode::NotUtf8,
+            DecodeErrorKind::InvalidChar(_) => ErrorCode::InvalidChar,
+            DecodeErrorKind::UnexpectedEof { .. } => ErrorCode::UnexpectedEof,
+            DecodeErrorKind::MixedVersion { .. } => ErrorCode::MixedVersion,
+            DecodeErrorKind::BufferTooSmall { .. } => ErrorCode::BufferTooSmall,
+            DecodeErrorKind::NotCanonical(Deviation::MixedVersions) => ErrorCode::MixedVersions,
+            DecodeErrorKind::NotCanonical(Deviation::MisplacedPadding) => {
+                ErrorCode::MisplacedPadding
+            }
+            DecodeErrorKind::NotCanonical(Deviation::UnusedBits) => ErrorCode::UnusedBits,
+            DecodeErrorKind::NotCanonical(Deviation::TrailingData) => ErrorCode::TrailingData,
+            DecodeErrorKind::ChecksumMismatch => ErrorCode::ChecksumMismatch,
+        }
+    }
+
+    /// Returns the offset, in bytes, of the place in the input where the error was detected.
+    ///
+    /// For invalid characters, this is the offset of the first byte of the character.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the position, in code points, of the place in the input where the error was
+    /// detected.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Extracts a decoding error from an `io::Error` returned by one of the decoding functions.
+    ///
+    /// Returns `None` if the error was not caused by invalid input data, e.g. if it was produced
+    /// by the underlying reader or writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecoji::{DecodeError, DecodeErrorKind};
+    ///
+    /// let input = "👶😲🇲👅🍉🔙🌥x";
+    ///
+    /// let e = ecoji::decode_to_vec(&mut input.as_bytes()).unwrap_err();
+    /// let e = DecodeError::from_io(&e).unwrap();
+    /// assert_eq!(e.kind(), &DecodeErrorKind::InvalidChar('x'));
+    /// assert_eq!(e.position(), 7);
+    /// ```
+    pub fn from_io(e: &io::Error) -> Option<&DecodeError> {
+        e.get_ref().and_then(|e| e.downcast_ref())
+    }
+}
+
+/// An error which occurred while encoding into a buffer provided by the caller, returned by
+/// [`Version::encode_into`](emojis/struct.Version.html#method.encode_into) when the encoded text
+/// does not fit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncodeError {
+    needed: usize,
+}
+
+impl EncodeError {
+    pub(crate) fn new(needed: usize) -> EncodeError {
+        EncodeError { needed }
+    }
+
+    /// Returns the number of bytes of the encoded text, which the buffer must be able to hold.
+    pub fn needed(&self) -> usize {
+        self.needed
+    }
+
+    /// Returns the stable numeric code of this error,
+    /// [`ErrorCode::BufferTooSmall`](enum.ErrorCode.html#variant.BufferTooSmall).
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::BufferTooSmall
+    }
+}
+
+impl error::Error for EncodeError {}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Output buffer is too small, {} bytes are needed",
+            self.needed
+        )
+    }
+}
+
+/// An error which occurred because the destination writer did not accept all of the output it was
+/// given, returned by a [`Codec`](struct.Codec.html) configured with the
+/// [`ShortWrites::Fail`](enum.ShortWrites.html#variant.Fail) policy.
+///
+/// It tells how much of the output has reached the destination, so that the operation can be
+/// resumed from there once the destination is ready again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShortWrite {
+    written: u64,
+}
+
+impl ShortWrite {
+    pub(crate) fn new(written: u64) -> ShortWrite {
+        ShortWrite { written }
+    }
+
+    /// Returns the number of bytes which have been written to the destination in total,
+    /// including those accepted by the short write.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Returns the stable numeric code of this error,
+    /// [`ErrorCode::ShortWrite`](enum.ErrorCode.html#variant.ShortWrite).
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::ShortWrite
+    }
+
+    /// Extracts the short write error from an `io::Error` returned by a codec.
+    ///
+    /// Returns `None` if the error was caused by anything else.
+    pub fn from_io(e: &io::Error) -> Option<&ShortWrite> {
+        e.get_ref().and_then(|e| e.downcast_ref())
+    }
+}
+
+impl error::Error for ShortWrite {}
+
+impl fmt::Display for ShortWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Destination did not accept all of the output, after {} bytes",
+            self.written
+        )
+    }
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(e: DecodeError) -> io::Error {
+        let kind = match e.kind {
+            DecodeErrorKind::UnexpectedEof { .. } => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+impl error::Error for DecodeError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            DecodeErrorKind::NotUtf8 => write!(f, "Input is not a valid UTF-8 stream")?,
+            DecodeErrorKind::InvalidChar(c) => write!(
+                f,
+                "Input character {:?} is not a part of the Ecoji alphabet",
+                c
+            )?,
+            DecodeErrorKind::UnexpectedEof { symbols: 0, .. } => {
+                write!(f, "Unexpected end of data, input is empty")?
+            }
+            DecodeErrorKind::UnexpectedEof { symbols, chunks } => write!(
+                f,
+                "Unexpected end of data after {} of the 4 symbols of chunk {}",
+                symbols,
+                chunks + 1
+            )?,
+            DecodeErrorKind::MixedVersion {
+                character,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Input character {:?} belongs to version {} of the alphabet, not to version {}",
+                character, found, expected
+            )?,
+            DecodeErrorKind::BufferTooSmall { needed } => {
+                write!(f, "Output buffer is too small, {} bytes are needed", needed)?
+            }
+            DecodeErrorKind::ChecksumMismatch => {
+                write!(f, "Checksum symbol does not match the decoded data")?
+            }
+            DecodeErrorKind::NotCanonical(deviation) => {
+                write!(f, "Input is not in the canonical form, ")?;
+                match deviation {
+                    Deviation::MixedVersions => {
+                        write!(f, "it mixes both versions of the alphabet")?
+                    }
+                    Deviation::MisplacedPadding => write!(f, "its padding is misplaced")?,
+                    Deviation::UnusedBits => write!(f, "unused bits of its final chunk are set")?,
+                    Deviation::TrailingData => write!(f, "data follows its final chunk")?,
+                }
+            }
+        }
+        write!(
+            f,
+            " (at code point {}, byte {})",
+            self.position, self.offset
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let decode_error = |kind| io::Error::from(DecodeError::new(kind, 0, 0));
+        let errors = [
+            (io::Error::other("x"), 1),
+            (io::Error::new(io::ErrorKind::InvalidData, "x"), 2),
+            (CodedError::io(ErrorCode::Cancelled, "x"), 3),
+            (
+                decode_error(DecodeErrorKind::UnexpectedEof {
+                    symbols: 1,
+                    chunks: 0,
+                }),
+                12,
+            ),
+            (
+                decode_error(DecodeErrorKind::NotCanonical(Deviation::TrailingData)),
+                23,
+            ),
+            (io::Error::other(EncodeError::new(10)), 14),
+            (io::Error::other(ShortWrite::new(10)), 32),
+        ];
+        for (e, value) in errors {
+            assert_eq!(ErrorCode::of(&e).value(), value, "{}", e);
+        }
+        assert_eq!(ErrorCode::MisplacedPadding.to_string(), "misplaced_padding");
+    }
+}
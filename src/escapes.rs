@@ -0,0 +1,143 @@
+//! Output as JSON escape sequences, for embedding encoded data in JSON strings.
+
+use std::fmt::Write as _;
+use std::io;
+
+use crate::emojis::Version;
+
+impl Version {
+    /// Encodes the data and writes every symbol of the result as `\uXXXX` escape sequences, with
+    /// the characters outside of the Basic Multilingual Plane written as surrogate pairs, like
+    /// JSON serializers which escape all non-ASCII characters do. The result can be put between
+    /// quotes as a JSON string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     ecoji::VERSION1.encode_json(b"abc"),
+    ///     r"\uD83D\uDC56\uD83D\uDCF8\uD83C\uDF88\u2615"
+    /// );
+    /// ```
+    pub fn encode_json(&self, input: &[u8]) -> String {
+        let encoded = self
+            .encode_to_string(&mut &input[..])
+            .expect("Encoding a slice failed");
+        let mut output = String::with_capacity(encoded.chars().count() * 12);
+        let mut units = [0; 2];
+        for c in encoded.chars() {
+            for unit in c.encode_utf16(&mut units) {
+                write!(output, "\\u{:04X}", unit).unwrap();
+            }
+        }
+        output
+    }
+
+    /// Replaces the JSON escape sequences in the input with the characters they stand for, and
+    /// decodes the result, like [`decode`](#method.decode) does. Characters which are not escaped
+    /// are taken as they are. The input is the contents of a JSON string, without the quotes.
+    ///
+    /// Fails under the same conditions as `decode`, with a backslash which does not start a valid
+    /// escape sequence, or a surrogate which is not a part of a pair, treated as an invalid
+    /// character. Error offsets refer to the input with the escape sequences replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let output = ecoji::VERSION1.decode_json(r"\ud83d\udc56\ud83d\udcf8\ud83c\udf88☕")?;
+    ///
+    /// assert_eq!(output, b"abc");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_json(&self, input: &str) -> io::Result<Vec<u8>> {
+        let mut unescaped = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find('\\') {
+            unescaped.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match parse_escape(rest) {
+                Some((c, len)) => {
+                    unescaped.push(c);
+                    rest = &rest[len..];
+                }
+                None => {
+                    unescaped.push('\\');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        unescaped.push_str(rest);
+        self.decode_to_vec(&mut unescaped.as_bytes())
+    }
+}
+
+/// Parses the escape sequence at the start of the input, returning the character along with the
+/// length of the sequence, which is two escapes for a surrogate pair.
+fn parse_escape(input: &str) -> Option<(char, usize)> {
+    let c = match input.as_bytes().get(1)? {
+        b'"' => '"',
+        b'\\' => '\\',
+        b'/' => '/',
+        b'b' => '\u{8}',
+        b'f' => '\u{c}',
+        b'n' => '\n',
+        b'r' => '\r',
+        b't' => '\t',
+        b'u' => {
+            let high = parse_unit(input)?;
+            if let Some(c) = char::from_u32(high as u32) {
+                return Some((c, 6));
+            }
+            let low = parse_unit(input.get(6..)?)?;
+            let c = char::decode_utf16([high, low]).next()?.ok()?;
+            return Some((c, 12));
+        }
+        _ => return None,
+    };
+    Some((c, 2))
+}
+
+/// Parses the code unit of the `\uXXXX` escape sequence at the start of the input.
+fn parse_unit(input: &str) -> Option<u16> {
+    let digits = input.strip_prefix("\\u")?.get(..4)?;
+    // from_str_radix would also accept a sign
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u16::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::{VERSION1, VERSIONS};
+    use crate::error::{DecodeError, DecodeErrorKind};
+
+    #[test]
+    fn test_invalid_escape() {
+        for input in [r"\ud83d", r"\ud83d☕", r"\udc56", r"\u+615", r"\x"] {
+            let e = VERSION1.decode_json(input).unwrap_err();
+            assert_eq!(
+                DecodeError::from_io(&e).map(DecodeError::kind),
+                Some(&DecodeErrorKind::InvalidChar('\\'))
+            );
+        }
+        // line breaks escaped by a serializer are rejected like the literal ones
+        let e = VERSION1.decode_json(r"👖📸\n🎈☕").unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e).map(DecodeError::kind),
+            Some(&DecodeErrorKind::InvalidChar('\n'))
+        );
+    }
+
+    quickcheck! {
+        fn decode_json_reverses_encode_json(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let json = v.encode_json(&input);
+                json.is_ascii() && v.decode_json(&json).unwrap() == input
+            })
+        }
+    }
+}
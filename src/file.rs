@@ -0,0 +1,127 @@
+//! Conversion of files, replacing the destination atomically.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::emojis::Version;
+
+/// Encodes the file at `src` with the given version and writes the encoded text to the file at
+/// `dst`; see [`Version::encode`](emojis/struct.Version.html#method.encode).
+///
+/// The destination is replaced atomically: the output goes to a temporary file next to it, which
+/// is renamed over the destination once complete, so the destination is left untouched if
+/// anything fails. If successful, returns the number of bytes written.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn test() -> ::std::io::Result<()> {
+/// ecoji::encode_file("photo.jpg", "photo.jpg.ecoji", &ecoji::VERSION2)?;
+/// ecoji::decode_file("photo.jpg.ecoji", "copy.jpg", &ecoji::VERSION2)?;
+/// #  Ok(())
+/// # }
+/// ```
+pub fn encode_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    version: &Version,
+) -> io::Result<usize> {
+    convert_file(src.as_ref(), dst.as_ref(), |source, destination| {
+        version.encode(source, destination)
+    })
+}
+
+/// Decodes the file at `src` starting with the given version and writes the data to the file at
+/// `dst`; see [`Version::decode`](emojis/struct.Version.html#method.decode).
+///
+/// The destination is replaced atomically like with [`encode_file`](fn.encode_file.html), so it
+/// is left untouched if the input fails to decode. If successful, returns the number of bytes
+/// written.
+pub fn decode_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    version: &Version,
+) -> io::Result<usize> {
+    convert_file(src.as_ref(), dst.as_ref(), |source, destination| {
+        version.decode(source, destination)
+    })
+}
+
+fn convert_file<F>(src: &Path, dst: &Path, convert: F) -> io::Result<usize>
+where
+    F: FnOnce(&mut dyn Read, &mut dyn Write) -> io::Result<usize>,
+{
+    let mut source = BufReader::new(File::open(src)?);
+    let temp = temp_path(dst)?;
+    let result = File::create(&temp).and_then(|file| {
+        let mut destination = BufWriter::new(file);
+        let written = convert(&mut source, &mut destination)?;
+        destination
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+        fs::rename(&temp, dst)?;
+        Ok(written)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Returns the path of a hidden temporary file in the directory of the destination, so that it
+/// can be renamed over the destination.
+fn temp_path(dst: &Path) -> io::Result<PathBuf> {
+    let name = dst.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", dst.display()),
+        )
+    })?;
+    let mut temp = OsString::from(".");
+    temp.push(name);
+    temp.push(format!(".{}.tmp", process::id()));
+    Ok(dst.with_file_name(temp))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use super::{decode_file, encode_file};
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_files() {
+        let dir = env::temp_dir().join(format!("ecoji-file-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (data, encoded, decoded) = (dir.join("data"), dir.join("encoded"), dir.join("decoded"));
+        fs::write(&data, b"input data").unwrap();
+
+        for v in VERSIONS.iter() {
+            let n = encode_file(&data, &encoded, v).unwrap();
+            let text = fs::read_to_string(&encoded).unwrap();
+            assert_eq!(n, text.len());
+            assert_eq!(
+                v.decode_to_vec(&mut text.as_bytes()).unwrap(),
+                b"input data"
+            );
+
+            assert_eq!(decode_file(&encoded, &decoded, v).unwrap(), 10);
+            assert_eq!(fs::read(&decoded).unwrap(), b"input data");
+        }
+
+        // a failed conversion leaves the destination and no temporary file behind
+        fs::write(&encoded, "not encoded").unwrap();
+        assert!(decode_file(&encoded, &decoded, VERSIONS[0]).is_err());
+        assert_eq!(fs::read(&decoded).unwrap(), b"input data");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
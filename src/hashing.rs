@@ -0,0 +1,277 @@
+//! Hashing of the data while it is encoded or decoded.
+
+use std::io::{self, BufRead, Read, Write};
+
+use digest::{Digest, Output};
+
+use crate::emojis::Version;
+
+/// A reader which computes the hash of all data read from the underlying reader, e.g. of the
+/// data read by the encoder, or of the data decoded by a
+/// [`DecoderReader`](struct.DecoderReader.html).
+///
+/// Generic over the hash functions implementing the `Digest` trait of the `digest` crate, like
+/// those of the `sha2` crate. Requires the `digest` feature.
+///
+/// # Examples
+///
+/// ```
+/// use sha2::{Digest, Sha256};
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut source = ecoji::DigestReader::<_, Sha256>::new("input data".as_bytes());
+/// let encoded = ecoji::VERSION1.encode_to_string(&mut source)?;
+///
+/// assert_eq!(encoded, "👶😲🇲👅🍉🔙🌥🌩");
+/// assert_eq!(source.bytes_read(), 10);
+/// assert_eq!(source.digest(), Sha256::digest(b"input data"));
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DigestReader<R, D> {
+    inner: R,
+    hasher: D,
+    bytes: u64,
+}
+
+impl<R: Read, D: Digest> DigestReader<R, D> {
+    /// Creates a new reader hashing the data read from `inner`.
+    pub fn new(inner: R) -> DigestReader<R, D> {
+        DigestReader {
+            inner,
+            hasher: D::new(),
+            bytes: 0,
+        }
+    }
+}
+
+impl<R, D: Digest + Clone> DigestReader<R, D> {
+    /// Returns the hash of the data read so far.
+    pub fn digest(&self) -> Output<D> {
+        self.hasher.clone().finalize()
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, D: Digest> Read for DigestReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.bytes += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead, D: Digest> BufRead for DigestReader<R, D> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        // hash only what the caller has actually consumed
+        if let Ok(buf) = self.inner.fill_buf() {
+            let amount = amount.min(buf.len());
+            self.hasher.update(&buf[..amount]);
+            self.bytes += amount as u64;
+        }
+        self.inner.consume(amount);
+    }
+}
+
+/// A writer which computes the hash of all data written to the underlying writer, e.g. of the
+/// data written by the decoder, or of the data written to an
+/// [`EncoderWriter`](struct.EncoderWriter.html).
+///
+/// Generic over the hash functions implementing the `Digest` trait of the `digest` crate, like
+/// those of the `sha2` crate. Requires the `digest` feature.
+///
+/// # Examples
+///
+/// ```
+/// use sha2::{Digest, Sha256};
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut destination = ecoji::DigestWriter::<_, Sha256>::new(Vec::new());
+/// ecoji::VERSION1.decode(&mut "👶😲🇲👅🍉🔙🌥🌩".as_bytes(), &mut destination)?;
+///
+/// assert_eq!(destination.bytes_written(), 10);
+/// assert_eq!(destination.digest(), Sha256::digest(b"input data"));
+/// assert_eq!(destination.into_inner(), b"input data");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DigestWriter<W, D> {
+    inner: W,
+    hasher: D,
+    bytes: u64,
+}
+
+impl<W: Write, D: Digest> DigestWriter<W, D> {
+    /// Creates a new writer hashing the data written to `inner`.
+    pub fn new(inner: W) -> DigestWriter<W, D> {
+        DigestWriter {
+            inner,
+            hasher: D::new(),
+            bytes: 0,
+        }
+    }
+}
+
+impl<W, D: Digest + Clone> DigestWriter<W, D> {
+    /// Returns the hash of the data written so far.
+    pub fn digest(&self) -> Output<D> {
+        self.hasher.clone().finalize()
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, D: Digest> Write for DigestWriter<W, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.bytes += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Version {
+    /// Encodes the entire source like [`encode`](#method.encode) does, computing the hash of the
+    /// data along the way with the given hash function, e.g. `sha2::Sha256`.
+    ///
+    /// If successful, returns the number of bytes which were written to the destination writer,
+    /// the number of bytes of data and their hash. Requires the `digest` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sha2::{Digest, Sha256};
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let mut output = Vec::new();
+    /// let (written, bytes, digest) =
+    ///     ecoji::VERSION1.encode_with_digest::<Sha256, _, _>(&mut "abc".as_bytes(), &mut output)?;
+    ///
+    /// assert_eq!((written, bytes), (output.len(), 3));
+    /// assert_eq!(digest, Sha256::digest(b"abc"));
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn encode_with_digest<D, R, W>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<(usize, u64, Output<D>)>
+    where
+        D: Digest + Clone,
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+    {
+        let mut source = DigestReader::<_, D>::new(source);
+        let written = self.encode(&mut source, destination)?;
+        Ok((written, source.bytes_read(), source.digest()))
+    }
+
+    /// Decodes the entire source like [`decode`](#method.decode) does, computing the hash of the
+    /// decoded data along the way with the given hash function, e.g. `sha2::Sha256`.
+    ///
+    /// If successful, returns the number of bytes which were written to the destination writer,
+    /// i.e. of decoded data, and their hash. Requires the `digest` feature.
+    pub fn decode_with_digest<D, R, W>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<(usize, Output<D>)>
+    where
+        D: Digest + Clone,
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+    {
+        let mut destination = DigestWriter::<_, D>::new(destination);
+        let written = self.decode(source, &mut destination)?;
+        Ok((written, destination.digest()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    use sha2::{Digest, Sha256};
+
+    use super::{DigestReader, DigestWriter};
+    use crate::emojis::VERSIONS;
+    use crate::stream::{DecoderReader, EncoderWriter};
+
+    quickcheck! {
+        fn digests_match(input: Vec<u8>) -> bool {
+            let expected = Sha256::digest(&input);
+            VERSIONS.iter().all(|&v| {
+                let mut encoded = Vec::new();
+                let (_, bytes, encode_digest) = v
+                    .encode_with_digest::<Sha256, _, _>(&mut input.as_slice(), &mut encoded)
+                    .unwrap();
+                let (decoded, decode_digest) = v
+                    .decode_with_digest::<Sha256, _, _>(&mut encoded.as_slice(), &mut Vec::new())
+                    .unwrap();
+
+                let mut writer = DigestWriter::<_, Sha256>::new(EncoderWriter::new(v, Vec::new()));
+                writer.write_all(&input).unwrap();
+
+                let mut reader = DigestReader::<_, Sha256>::new(BufReader::with_capacity(
+                    7,
+                    DecoderReader::new(v, encoded.as_slice()),
+                ));
+                let mut lines = Vec::new();
+                while reader.read_until(b'\n', &mut lines).unwrap() > 0 {}
+                reader.read_to_end(&mut Vec::new()).unwrap();
+
+                bytes == input.len() as u64
+                    && decoded == input.len()
+                    && encode_digest == expected
+                    && decode_digest == expected
+                    && writer.digest() == expected
+                    && writer.into_inner().finish().unwrap() == encoded
+                    && reader.bytes_read() == input.len() as u64
+                    && reader.digest() == expected
+                    && lines == input
+            })
+        }
+    }
+}
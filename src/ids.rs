@@ -0,0 +1,168 @@
+//! Encoding of identifiers, like UUIDs and hash digests, into fixed-length text.
+
+use std::io;
+
+#[cfg(feature = "digest")]
+use digest::{Output, OutputSizeUser};
+
+use crate::emojis::Version;
+use crate::DEFAULT_VERSION;
+
+impl Version {
+    /// Encodes the UUID into text which is always of the same length for the version: 16
+    /// symbols with version 1 and 14 symbols with version 2.
+    ///
+    /// Requires the `uuid` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let id = uuid::Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8);
+    /// let encoded = ecoji::VERSION2.encode_uuid(&id);
+    ///
+    /// assert_eq!(encoded.chars().count(), 14);
+    /// assert_eq!(ecoji::VERSION2.decode_uuid(&encoded).unwrap(), id);
+    /// ```
+    #[cfg(feature = "uuid")]
+    pub fn encode_uuid(&self, uuid: &uuid::Uuid) -> String {
+        self.encode_array(uuid.as_bytes()).to_string()
+    }
+
+    /// Decodes text produced by [`encode_uuid`](#method.encode_uuid), like
+    /// [`decode`](#method.decode) does.
+    ///
+    /// Fails under the same conditions as `decode`, and with an error of the
+    /// `io::ErrorKind::InvalidData` kind if the decoded data is not 16 bytes long. Requires the
+    /// `uuid` feature.
+    #[cfg(feature = "uuid")]
+    pub fn decode_uuid(&self, input: &str) -> io::Result<uuid::Uuid> {
+        self.decode_array(input).map(uuid::Uuid::from_bytes)
+    }
+
+    /// Encodes the output of a hash function, like a SHA-256 or a BLAKE2b digest, into text which
+    /// is always of the same length for the version and the hash function.
+    ///
+    /// Requires the `digest` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sha2::{Digest, Sha256};
+    ///
+    /// let digest = Sha256::digest(b"input data");
+    /// let encoded = ecoji::VERSION1.encode_digest::<Sha256>(&digest);
+    ///
+    /// assert_eq!(encoded.chars().count(), 28);
+    /// assert_eq!(ecoji::VERSION1.decode_digest::<Sha256>(&encoded).unwrap(), digest);
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn encode_digest<D: OutputSizeUser>(&self, digest: &Output<D>) -> String {
+        self.encode_to_string(&mut &digest[..])
+            .expect("Encoding a slice failed")
+    }
+
+    /// Decodes text produced by [`encode_digest`](#method.encode_digest) for the same hash
+    /// function, like [`decode`](#method.decode) does.
+    ///
+    /// Fails under the same conditions as `decode`, and with an error of the
+    /// `io::ErrorKind::InvalidData` kind if the decoded data is not as long as the output of the
+    /// hash function. Requires the `digest` feature.
+    #[cfg(feature = "digest")]
+    pub fn decode_digest<D: OutputSizeUser>(&self, input: &str) -> io::Result<Output<D>> {
+        let data = self.decode_to_vec(&mut input.as_bytes())?;
+        if data.len() != D::output_size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Decoded data is {} bytes long instead of {}",
+                    data.len(),
+                    D::output_size()
+                ),
+            ));
+        }
+        Ok(Output::<D>::clone_from_slice(&data))
+    }
+}
+
+/// Encodes the UUID with the [default version](constant.DEFAULT_VERSION.html); see
+/// [`Version::encode_uuid`](emojis/struct.Version.html#method.encode_uuid).
+///
+/// Requires the `uuid` feature.
+#[cfg(feature = "uuid")]
+pub fn encode_uuid(uuid: uuid::Uuid) -> String {
+    DEFAULT_VERSION.encode_uuid(&uuid)
+}
+
+/// Decodes a UUID starting with the default version; see
+/// [`Version::decode_uuid`](emojis/struct.Version.html#method.decode_uuid).
+///
+/// Requires the `uuid` feature.
+#[cfg(feature = "uuid")]
+pub fn decode_uuid(input: &str) -> io::Result<uuid::Uuid> {
+    DEFAULT_VERSION.decode_uuid(input)
+}
+
+/// Encodes the output of a hash function with the default version; see
+/// [`Version::encode_digest`](emojis/struct.Version.html#method.encode_digest).
+///
+/// Requires the `digest` feature.
+#[cfg(feature = "digest")]
+pub fn encode_digest<D: OutputSizeUser>(digest: &Output<D>) -> String {
+    DEFAULT_VERSION.encode_digest::<D>(digest)
+}
+
+/// Decodes the output of a hash function starting with the default version; see
+/// [`Version::decode_digest`](emojis/struct.Version.html#method.decode_digest).
+///
+/// Requires the `digest` feature.
+#[cfg(feature = "digest")]
+pub fn decode_digest<D: OutputSizeUser>(input: &str) -> io::Result<Output<D>> {
+    DEFAULT_VERSION.decode_digest::<D>(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::VERSIONS;
+
+    #[cfg(feature = "uuid")]
+    quickcheck! {
+        fn uuids_round_trip(a: u64, b: u64) -> bool {
+            let id = uuid::Uuid::from_u64_pair(a, b);
+            let lengths = VERSIONS.map(|v| v.encode_uuid(&id).chars().count());
+            VERSIONS.iter().all(|v| v.decode_uuid(&v.encode_uuid(&id)).unwrap() == id)
+                && lengths == [16, 14]
+                && super::decode_uuid(&super::encode_uuid(id)).unwrap() == id
+        }
+    }
+
+    #[cfg(feature = "digest")]
+    quickcheck! {
+        fn digests_round_trip(input: Vec<u8>) -> bool {
+            use sha2::{Digest, Sha256, Sha512};
+
+            let short = Sha256::digest(&input);
+            let long = Sha512::digest(&input);
+            VERSIONS.iter().all(|v| {
+                let short_encoded = v.encode_digest::<Sha256>(&short);
+                let long_encoded = v.encode_digest::<Sha512>(&long);
+                v.decode_digest::<Sha256>(&short_encoded).unwrap() == short
+                    && v.decode_digest::<Sha512>(&long_encoded).unwrap() == long
+                    && v.decode_digest::<Sha512>(&short_encoded).is_err()
+            })
+        }
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_digest_lengths() {
+        use sha2::{Digest, Sha256, Sha512};
+
+        let short = super::encode_digest::<Sha256>(&Sha256::digest(b""));
+        let long = super::encode_digest::<Sha512>(&Sha512::digest(b""));
+        assert_eq!((short.chars().count(), long.chars().count()), (28, 52));
+        assert_eq!(
+            super::decode_digest::<Sha256>(&short).unwrap(),
+            Sha256::digest(b"")
+        );
+    }
+}
@@ -79,14 +79,19 @@
 //! [`decode`](fn.decode.html), which both have the same signature: they accept a reference
 //! to an `std::io::Read` and a reference to `std::io::Write` and return an `std::io::Result<usize>`
 //! with the number of bytes written to the output `std::io::Write`.
+//! They use the [default version](constant.DEFAULT_VERSION.html), while
+//! [`encode_with`](fn.encode_with.html) and [`decode_with`](fn.decode_with.html), as well as the
+//! other functions with the `_with` suffix, take the version explicitly.
+//!
+//! Settings beyond the version, like wrapping, strictness and output limits, are collected by
+//! a [`Builder`](struct.Builder.html) into a [`Codec`](struct.Codec.html), whose `encode` and
+//! `decode` methods honor all of them.
 //!
 //! Additionally, this library provides shortcut functions,
 //! [`encode_to_string`](fn.encode_to_string.html), [`decode_to_vec`](fn.decode_to_vec.html) and
 //! [`decode_to_string`](fn.decode_to_string.html), whose output is an in-memory `String` or
-//! `Vec<u8>`. Note that there is no need to support special versions of the encode/decode
-//! operations which would *accept* strings or vectors, because slices of bytes (`&[u8]`) implement
-//! the `std::io::Read` trait by default. Therefore, if you have a string or a byte vector, you
-//! can invoke the encoding/decoding functions like this:
+//! `Vec<u8>`. Slices of bytes (`&[u8]`) implement the `std::io::Read` trait, so these functions
+//! accept strings or byte vectors as well:
 //!
 //! ```
 //! # fn test() -> ::std::io::Result<()> {
@@ -102,26 +107,74 @@
 //! # }
 //! ```
 //!
+//! For data which is in memory anyway, [`encode_bytes`](fn.encode_bytes.html) and
+//! [`decode_string`](fn.decode_string.html) take anything which can be viewed as bytes or as a
+//! string directly:
+//!
+//! ```
+//! # fn test() -> ::std::io::Result<()> {
+//! let encoded = ecoji::encode_bytes("some data");
+//! assert_eq!(ecoji::decode_string(&encoded)?, b"some data");
+//! #   Ok(())
+//! # }
+//! # test().unwrap();
+//! ```
+//!
+//! All the types defined by this library are `Send` and `Sync` whenever their type parameters
+//! are, e.g. an [`EncoderWriter`](struct.EncoderWriter.html) over a `Send` writer can be moved to
+//! another thread, and a [`Codec`](struct.Codec.html) can be shared between threads and async
+//! tasks. The [`MessageSender`](trait.MessageSender.html),
+//! [`MessageReceiver`](trait.MessageReceiver.html) and [`Sink`](trait.Sink.html) traits are
+//! implemented for types of the standard library and of `crossbeam_channel`, which keep their own
+//! guarantees: the channel ends are `Send`, as the background threads require, but the receivers
+//! of the standard library are not `Sync`, and of the sinks, `Vec<u8>` and `String` are both, but
+//! a `fmt::Formatter` is neither.
+//!
 //! ## Command line tool
 //!
 //! This crate also provides an executable binary, `ecoji`, which provides a command line
 //! interface similar to that of the standard `base64` command and which can encode or decode data
 //! coming on the standard input and write the results of this processing to the standard output.
-//! You can install it by invoking the following command:
+//! It is built when the default `cli` feature is enabled, so you can install it by invoking the
+//! following command:
 //!
 //! ```none
-//! $ cargo install --bin ecoji --features build-binary ecoji
+//! $ cargo install ecoji
 //! ```
 //!
+//! Libraries and applications which only need the library can disable the default features to
+//! avoid pulling in the dependencies of the command line tool. The `unicode-width` feature, which
+//! `cli` enables, can be enabled on its own for [`display_width`](fn.display_width.html) and
+//! [`wrap_display`](fn.wrap_display.html), which lay out encoded text for terminals. The
+//! `encoding` feature adds [`Version::encode_text`](emojis/struct.Version.html#method.encode_text)
+//! and [`Version::decode_text`](emojis/struct.Version.html#method.decode_text), which convert
+//! text in other character encodings, like UTF-16, from and to UTF-8. The `digest` feature adds
+//! [`DigestReader`](struct.DigestReader.html) and [`DigestWriter`](struct.DigestWriter.html),
+//! which hash the data in the same pass as it is encoded or decoded, and
+//! [`encode_digest`](fn.encode_digest.html), which encodes hash digests into fixed-length text,
+//! like the `uuid` feature adds [`encode_uuid`](fn.encode_uuid.html) for UUIDs. The `base64`
+//! feature, which `cli` enables as well, adds
+//! [`transcode_from_base64`](fn.transcode_from_base64.html) and
+//! [`transcode_to_base64`](fn.transcode_to_base64.html), which convert Base64 text from and to
+//! encoded text without decoding it in full first. The `testing` feature adds the
+//! [`testing`](testing/index.html) module, which generates random encoded text and mutations of
+//! it for the tests of code handling encoded text.
+//!
 //! It will be installed in your default Cargo binaries directory (usually `~/.cargo/bin` on Unix
 //! systems). Run `ecoji --help` (assuming the aforementioned directory is in your `PATH`) to
 //! see documentation on how to invoke itl.
 //!
 //! ## Issues and limitations
 //!
-//! Currently this crate does not provide an ability to do wrapping of the encoded text, like
-//! e.g. what the `base64` command does with the `-w` flag. It is possible that this feature will
-//! be implemented in future; pull requests for this functionality are welcome!
+//! Wrapping of the encoded text is only available through a [`Codec`](struct.Codec.html)
+//! configured with [`Builder::wrap`](struct.Builder.html#method.wrap), and in the command line
+//! tool, like the `base64` command, with the `-w` flag.
+//!
+//! The decoders reject line breaks (`\n` and `\r`) like any other character outside of the
+//! alphabet, so wrapped text has to be decoded with a codec configured with
+//! [`Builder::ignore_line_breaks`](struct.Builder.html#method.ignore_line_breaks), or with
+//! [`Builder::ignore_whitespace`](struct.Builder.html#method.ignore_whitespace), which skips any
+//! whitespace. The command line tool always skips line breaks when decoding.
 //!
 //! This library is almost a direct line-by-line reimplementation of the original algorithm
 //! which is implemented in Go. There were almost zero attempts at optimization, therefore
@@ -136,41 +189,305 @@ extern crate phf;
 #[macro_use]
 extern crate quickcheck;
 
+mod alphabet;
+mod analyze;
+mod array;
 mod chars;
+#[cfg(feature = "encoding")]
+mod charset;
+mod chunks;
+mod codec;
+mod compare;
+mod data_uri;
 mod decode;
+mod diagnostic;
 pub mod emojis;
 mod encode;
+mod entities;
+mod error;
+mod escapes;
+mod file;
+#[cfg(feature = "digest")]
+mod hashing;
+#[cfg(any(feature = "uuid", feature = "digest"))]
+mod ids;
+mod mime;
+mod parallel;
+mod partial;
+mod percent;
+mod pipeline;
+mod scan;
+pub mod short_id;
+mod sink;
+mod slice;
+mod stream;
+pub mod tables;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod transcode;
+pub mod v1;
+pub mod v2;
+mod validate;
+mod width;
 
+pub use crate::alphabet::AlphabetSet;
+pub use crate::analyze::{analyze, Classification, Confidence, Detection, EncodingStats};
+pub use crate::array::{encode_array, encode_array_with, EncodedArray};
+pub use crate::chunks::{
+    split_encoded, truncate_encoded, DecodedChunks, EncodedChunk, EncodedChunks, SplitEncoded,
+};
+pub use crate::codec::{Builder, Codec, Progress, ShortWrites, VersionPolicy};
+pub use crate::compare::{equivalent, equivalent_with, first_difference, first_difference_with};
+pub use crate::data_uri::DataUri;
+pub use crate::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::emojis::Version;
 pub use crate::emojis::{VERSION1, VERSION2};
+pub use crate::error::{
+    DecodeError, DecodeErrorKind, Deviation, EncodeError, ErrorCode, ShortWrite,
+};
+pub use crate::file::{decode_file, encode_file};
+#[cfg(feature = "digest")]
+pub use crate::hashing::{DigestReader, DigestWriter};
+#[cfg(feature = "digest")]
+pub use crate::ids::{decode_digest, encode_digest};
+#[cfg(feature = "uuid")]
+pub use crate::ids::{decode_uuid, encode_uuid};
+pub use crate::mime::MimePart;
+pub use crate::partial::PartialDecode;
+pub use crate::pipeline::{spawn_decoder, spawn_encoder, MessageReceiver, MessageSender};
+pub use crate::scan::{Run, Scanner};
+pub use crate::sink::Sink;
+pub use crate::stream::{DecodedBytes, DecoderCheckpoint, DecoderReader, EncoderWriter};
+#[cfg(feature = "base64")]
+pub use crate::transcode::{
+    transcode_from_base64, transcode_from_base64_with, transcode_to_base64,
+};
+pub use crate::transcode::{
+    transcode_from_hex, transcode_from_hex_with, transcode_to_hex, HexReader, SkipWhitespace,
+};
+pub use crate::validate::ValidationReport;
+#[cfg(feature = "unicode-width")]
+pub use crate::width::{display_width, wrap_display};
+pub use crate::width::{symbol_count, LineWidth, LineWrapper};
 use std::io;
 use std::io::{Read, Write};
 
+/// The version used by the free functions without an explicit version, like
+/// [`encode`](fn.encode.html), and by default by [`Builder`](struct.Builder.html).
+///
+/// It is version 1 for compatibility with earlier releases, but may be switched to version 2,
+/// which the reference implementation defaults to, in a future major release. Use the functions
+/// with the `_with` suffix, like [`encode_with`](fn.encode_with.html), to stick to a version.
+pub const DEFAULT_VERSION: &Version = &VERSION1;
+
+/// Encodes the source with the [default version](constant.DEFAULT_VERSION.html); see
+/// [`Version::encode`](emojis/struct.Version.html#method.encode).
 pub fn encode<R: Read + ?Sized, W: Write + ?Sized>(
     source: &mut R,
     destination: &mut W,
 ) -> io::Result<usize> {
-    VERSION1.encode(source, destination)
+    encode_with(DEFAULT_VERSION, source, destination)
 }
 
+/// Encodes the source with the given version; see
+/// [`Version::encode`](emojis/struct.Version.html#method.encode).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut output: Vec<u8> = Vec::new();
+/// ecoji::encode_with(&ecoji::VERSION2, &mut "input data".as_bytes(), &mut output)?;
+///
+/// assert_eq!(output, "👶😲⛵👅🍉🧴🦪🦮".as_bytes());
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn encode_with<R: Read + ?Sized, W: Write + ?Sized>(
+    version: &Version,
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    version.encode(source, destination)
+}
+
+/// Encodes the source with the default version into a new string; see
+/// [`Version::encode_to_string`](emojis/struct.Version.html#method.encode_to_string).
 pub fn encode_to_string<R: Read + ?Sized>(source: &mut R) -> io::Result<String> {
-    VERSION1.encode_to_string(source)
+    encode_to_string_with(DEFAULT_VERSION, source)
 }
 
+/// Encodes the source with the given version into a new string; see
+/// [`Version::encode_to_string`](emojis/struct.Version.html#method.encode_to_string).
+pub fn encode_to_string_with<R: Read + ?Sized>(
+    version: &Version,
+    source: &mut R,
+) -> io::Result<String> {
+    version.encode_to_string(source)
+}
+
+/// Decodes the source starting with the default version; see
+/// [`Version::decode`](emojis/struct.Version.html#method.decode).
 pub fn decode<R: Read + ?Sized, W: Write + ?Sized>(
     source: &mut R,
     destination: &mut W,
 ) -> io::Result<usize> {
-    VERSION1.decode(source, destination)
+    decode_with(DEFAULT_VERSION, source, destination)
+}
+
+/// Decodes the source starting with the given version; see
+/// [`Version::decode`](emojis/struct.Version.html#method.decode).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut output: Vec<u8> = Vec::new();
+/// ecoji::decode_with(&ecoji::VERSION2, &mut "👶😲⛵👅🍉🧴🦪🦮".as_bytes(), &mut output)?;
+///
+/// assert_eq!(output, b"input data");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn decode_with<R: Read + ?Sized, W: Write + ?Sized>(
+    version: &Version,
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    version.decode(source, destination)
 }
 
+/// Decodes the source starting with the default version into a new string; see
+/// [`Version::decode_to_string`](emojis/struct.Version.html#method.decode_to_string).
 pub fn decode_to_string<R: Read + ?Sized>(source: &mut R) -> io::Result<String> {
-    VERSION1.decode_to_string(source)
+    decode_to_string_with(DEFAULT_VERSION, source)
 }
 
+/// Decodes the source starting with the given version into a new string; see
+/// [`Version::decode_to_string`](emojis/struct.Version.html#method.decode_to_string).
+pub fn decode_to_string_with<R: Read + ?Sized>(
+    version: &Version,
+    source: &mut R,
+) -> io::Result<String> {
+    version.decode_to_string(source)
+}
+
+/// Decodes the source starting with the default version into a new byte vector; see
+/// [`Version::decode_to_vec`](emojis/struct.Version.html#method.decode_to_vec).
 pub fn decode_to_vec<R: Read + ?Sized>(source: &mut R) -> io::Result<Vec<u8>> {
-    VERSION1.decode_to_vec(source)
+    decode_to_vec_with(DEFAULT_VERSION, source)
 }
 
+/// Decodes the source starting with the given version into a new byte vector; see
+/// [`Version::decode_to_vec`](emojis/struct.Version.html#method.decode_to_vec).
+pub fn decode_to_vec_with<R: Read + ?Sized>(
+    version: &Version,
+    source: &mut R,
+) -> io::Result<Vec<u8>> {
+    version.decode_to_vec(source)
+}
+
+/// Encodes the bytes with the default version into a new string.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(ecoji::encode_bytes(b"input data"), "👶😲🇲👅🍉🔙🌥🌩");
+/// assert_eq!(ecoji::encode_bytes(vec![b'a']), "👕☕☕☕");
+/// ```
+pub fn encode_bytes<T: AsRef<[u8]>>(input: T) -> String {
+    encode_bytes_with(DEFAULT_VERSION, input)
+}
+
+/// Encodes the bytes with the given version into a new string.
+pub fn encode_bytes_with<T: AsRef<[u8]>>(version: &Version, input: T) -> String {
+    version
+        .encode_to_string(&mut input.as_ref())
+        .expect("Encoding a slice failed")
+}
+
+/// Decodes the string starting with the default version into a new byte vector; see
+/// [`Version::decode`](emojis/struct.Version.html#method.decode).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// assert_eq!(ecoji::decode_string("👶😲🇲👅🍉🔙🌥🌩")?, b"input data");
+/// assert!(ecoji::decode_string(String::from("👶")).is_err());
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn decode_string<T: AsRef<str>>(input: T) -> io::Result<Vec<u8>> {
+    decode_string_with(DEFAULT_VERSION, input)
+}
+
+/// Decodes the string starting with the given version into a new byte vector; see
+/// [`Version::decode`](emojis/struct.Version.html#method.decode).
+pub fn decode_string_with<T: AsRef<str>>(version: &Version, input: T) -> io::Result<Vec<u8>> {
+    version.decode_to_vec(&mut input.as_ref().as_bytes())
+}
+
+// All the types of the library are `Send` and `Sync` whenever their type parameters are, so
+// that they can be moved to and shared with other threads and async tasks. This is checked here
+// at compile time, so that it does not get lost by accident, along with the guarantees of the
+// foreign types implementing the traits of the library which the documentation mentions.
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>() {}
+
+    #[allow(dead_code)]
+    fn check<R: Read + Send + Sync, W: Write + Send + Sync>() {
+        assert_send_sync::<Version>();
+        assert_send_sync::<AlphabetSet>();
+        assert_send_sync::<EncodedArray<16>>();
+        assert_send_sync::<Builder>();
+        assert_send_sync::<Codec>();
+        assert_send_sync::<EncoderWriter<'static, W>>();
+        assert_send_sync::<DecoderReader<'static, R>>();
+        assert_send_sync::<DecodedBytes<'static, R>>();
+        assert_send_sync::<DecoderCheckpoint>();
+        assert_send_sync::<EncodedChunks<'static>>();
+        assert_send_sync::<DecodedChunks<'static>>();
+        assert_send_sync::<SplitEncoded<'static>>();
+        assert_send_sync::<Scanner<'static>>();
+        assert_send_sync::<Run<'static>>();
+        assert_send_sync::<DecodeError>();
+        assert_send_sync::<EncodeError>();
+        assert_send_sync::<ShortWrite>();
+        assert_send_sync::<ErrorCode>();
+        assert_send_sync::<Diagnostic>();
+        assert_send_sync::<Detection>();
+        assert_send_sync::<EncodingStats>();
+        assert_send_sync::<PartialDecode>();
+        assert_send_sync::<ValidationReport>();
+        assert_send_sync::<DataUri>();
+        assert_send_sync::<MimePart>();
+        #[cfg(feature = "testing")]
+        assert_send_sync::<testing::Sample>();
+
+        assert_send_sync::<std::sync::mpsc::Sender<Vec<u8>>>();
+        assert_send_sync::<std::sync::mpsc::SyncSender<Vec<u8>>>();
+        assert_send::<std::sync::mpsc::Receiver<String>>();
+        #[cfg(feature = "crossbeam-channel")]
+        assert_send_sync::<crossbeam_channel::Sender<Vec<u8>>>();
+        #[cfg(feature = "crossbeam-channel")]
+        assert_send_sync::<crossbeam_channel::Receiver<String>>();
+        assert_send_sync::<Vec<u8>>();
+        assert_send_sync::<String>();
+    }
+
+    #[cfg(feature = "digest")]
+    #[allow(dead_code)]
+    fn check_digest<R: Read + Send + Sync, W: Write + Send + Sync, D: Send + Sync>() {
+        assert_send_sync::<DigestReader<R, D>>();
+        assert_send_sync::<DigestWriter<W, D>>();
+    }
+};
+
 #[cfg(test)]
 mod test {
     use crate::{emojis::VERSIONS, VERSION1, VERSION2};
@@ -200,6 +517,62 @@ mod test {
         assert_eq!(input, output);
     }
 
+    /// Checks that the final chunk of the input round-trips through both decoders, both on its
+    /// own and after a complete chunk, and has the shape its version prescribes.
+    fn check_final_chunk(chunk: &[u8]) {
+        let mut input = b"ecoji".to_vec();
+        input.extend_from_slice(chunk);
+        for v in VERSIONS.iter() {
+            let encoded = v.encode_to_string(&mut &chunk[..]).unwrap();
+            let symbols: Vec<char> = encoded.chars().collect();
+            match (v.VERSION_NUMBER, chunk.len()) {
+                (_, 5) => assert!(symbols.iter().all(|&c| !v.is_padding(c))),
+                (_, 4) => {
+                    assert_eq!(symbols.len(), 4);
+                    assert!(v.is_padding_4(symbols[3]));
+                    assert_eq!(symbols[3], v.padding_4((chunk[3] & 0x03) as usize));
+                }
+                (1, n) => assert_eq!(symbols[n..], vec![v.padding(); 4 - n][..]),
+                (_, n) => assert_eq!(symbols[n..], [v.padding()]),
+            }
+
+            for decoder in VERSIONS.iter() {
+                let output = decoder.decode_to_vec(&mut encoded.as_bytes()).unwrap();
+                assert_eq!(output, chunk, "{:?} decoded by {}", encoded, decoder);
+            }
+            let mut output = Vec::new();
+            v.decode_strict(&mut encoded.as_bytes(), &mut output)
+                .unwrap();
+            assert_eq!(output, chunk);
+
+            let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+            assert_eq!(v.decode_to_vec(&mut encoded.as_bytes()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn final_chunks_round_trip() {
+        // every value of the last byte of a final chunk of each length, which determines the
+        // padding, along with every value of each half of the byte before it, and the preceding
+        // bytes set to a few patterns
+        for len in 1..=5 {
+            for prefix in [0x00, 0x5a, 0xa5, 0xff] {
+                for high in (0..16).map(|n| n * 0x11) {
+                    for low in 0..=u8::MAX {
+                        let mut chunk = vec![prefix; len];
+                        chunk[len - 1] = low;
+                        if len > 1 {
+                            chunk[len - 2] = high;
+                        } else if high > 0 {
+                            continue;
+                        }
+                        check_final_chunk(&chunk);
+                    }
+                }
+            }
+        }
+    }
+
     quickcheck! {
         fn encode_then_decode_identity(encode: VersionChoice, decode: VersionChoice, input: Vec<u8>) -> bool {
             let encode = VERSIONS[encode as usize];
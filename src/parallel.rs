@@ -0,0 +1,657 @@
+//! Parallel encoding and decoding.
+//!
+//! Both operations read the source in large blocks, split each block into segments which are
+//! processed independently, on the rayon thread pool or on scoped threads, and write the results
+//! in order. Segments
+//! are aligned so that the output is byte-for-byte identical to that of the sequential functions:
+//!
+//! * when encoding, segments are a multiple of 5 bytes long, so no encoded chunk spans two
+//!   segments, and only the last segment of the input may end with a padded chunk;
+//! * when decoding, segments consist of whole groups of 4 code points, which is exactly how the
+//!   sequential decoder consumes its input. The sequential decoder switches to the alphabet of
+//!   the other version once it meets a character outside the alphabet of the version it was
+//!   invoked on; because segments are decoded before it is known whether a preceding segment has
+//!   made this switch, segments following the first one which did are decoded again as if the
+//!   switch had already happened, so that exactly the same characters are accepted or rejected.
+
+use std::io::{self, Read, Write};
+use std::num::NonZeroUsize;
+use std::panic;
+use std::ptr;
+use std::str;
+use std::thread::{Scope, ScopedJoinHandle};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::decode::{is_line_break, Settings};
+use crate::emojis::Version;
+use crate::encode::read_exact;
+use crate::error::DecodeError;
+
+/// Number of input bytes in an encoding segment; must be a multiple of 5.
+const ENCODE_SEGMENT: usize = 5 * 16 * 1024;
+
+/// Minimal number of input bytes in a decoding segment.
+pub(crate) const DECODE_SEGMENT: usize = 64 * 1024;
+
+/// The decoded data of a segment, or the error, along with the decoder to be used for the
+/// following segment.
+type SegmentResult<'a> = (io::Result<Vec<u8>>, &'a Version);
+
+/// A piece of the decoder input along with its location in the whole input, relative to the
+/// block it belongs to.
+struct Segment<'a> {
+    text: &'a str,
+    offset: usize,
+    position: usize,
+    /// The number of chunks of the block before the segment.
+    chunks: usize,
+}
+
+impl Version {
+    /// Encodes the entire source into the Ecoji format, processing blocks of the source in
+    /// parallel on the current rayon thread pool, and writes a UTF-8 representation of the
+    /// encoded data to the provided destination.
+    ///
+    /// The output is exactly the same as that of [`encode`](#method.encode), and so are the
+    /// return value and failure conditions. Parallel processing pays off only for sufficiently
+    /// large inputs, starting from hundreds of kilobytes.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let input = "input data";
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    /// ecoji::VERSION1.encode_parallel(&mut input.as_bytes(), &mut output)?;
+    ///
+    /// assert_eq!(output, "👶😲🇲👅🍉🔙🌥🌩".as_bytes());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn encode_parallel<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.encode_parallel_by(ENCODE_SEGMENT, source, destination)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn encode_parallel_by<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        segment: usize,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let block = segment * rayon::current_num_threads();
+        self.encode_blocks(block, source, destination, |buf| {
+            buf.par_chunks(segment)
+                .map(|s| self.encode_segment(s))
+                .collect()
+        })
+    }
+
+    /// Encodes the entire source into the Ecoji format like
+    /// [`encode_parallel`](#method.encode_parallel) does, but on the given number of threads
+    /// spawned in the given scope instead of the rayon thread pool, so it doesn't need the
+    /// `parallel` feature.
+    ///
+    /// The threads are spawned for every block of the source, and all of them have finished by
+    /// the time this returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use std::thread;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let input = "input data";
+    /// let workers = NonZeroUsize::new(4).unwrap();
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    /// thread::scope(|scope| {
+    ///     ecoji::VERSION1.encode_parallel_with(scope, workers, &mut input.as_bytes(), &mut output)
+    /// })?;
+    ///
+    /// assert_eq!(output, "👶😲🇲👅🍉🔙🌥🌩".as_bytes());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn encode_parallel_with<'scope, 'env, R: Read + ?Sized, W: Write + ?Sized>(
+        &'env self,
+        scope: &'scope Scope<'scope, 'env>,
+        workers: NonZeroUsize,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.encode_scoped_by(scope, workers, ENCODE_SEGMENT, source, destination)
+    }
+
+    fn encode_scoped_by<'scope, 'env, R: Read + ?Sized, W: Write + ?Sized>(
+        &'env self,
+        scope: &'scope Scope<'scope, 'env>,
+        workers: NonZeroUsize,
+        segment: usize,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.encode_blocks(segment * workers.get(), source, destination, |buf| {
+            let handles: Vec<_> = buf
+                .chunks(segment)
+                .map(|s| {
+                    let s = s.to_vec();
+                    scope.spawn(move || self.encode_segment(&s))
+                })
+                .collect();
+            handles.into_iter().map(join).collect()
+        })
+    }
+
+    /// Reads the source in blocks of the given size, and writes the encodings of the segments
+    /// of every block, which `encode_segments` computes in parallel.
+    fn encode_blocks<R, W, F>(
+        &self,
+        block: usize,
+        source: &mut R,
+        destination: &mut W,
+        encode_segments: F,
+    ) -> io::Result<usize>
+    where
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+        F: Fn(&[u8]) -> Vec<Vec<u8>>,
+    {
+        let mut buf = vec![0; block];
+        let mut bytes_written = 0;
+
+        loop {
+            let n = read_exact(source, &mut buf)?;
+
+            for output in encode_segments(&buf[..n]) {
+                destination.write_all(&output)?;
+                bytes_written += output.len();
+            }
+
+            // EOF
+            if n < buf.len() {
+                break;
+            }
+        }
+
+        Ok(bytes_written)
+    }
+
+    fn encode_segment(&self, mut s: &[u8]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(s.len() / 5 * 16 + 16);
+        // reading from a slice and writing to a vector can't fail
+        self.encode(&mut s, &mut output).unwrap();
+        output
+    }
+
+    /// Decodes the entire source from the Ecoji format (assumed to be UTF-8-encoded), processing
+    /// blocks of the source in parallel on the current rayon thread pool, and writes the result
+    /// of the decoding to the provided destination.
+    ///
+    /// The output is exactly the same as that of [`decode`](#method.decode), and so are the
+    /// return value and failure conditions, including the location of the reported
+    /// [`DecodeError`](struct.DecodeError.html). However, when an error occurs, the destination
+    /// may contain less of the data preceding the error than it would with `decode`.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let input = "👶😲🇲👅🍉🔙🌥🌩";
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    /// ecoji::VERSION1.decode_parallel(&mut input.as_bytes(), &mut output)?;
+    ///
+    /// assert_eq!(output, b"input data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn decode_parallel<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.decode_parallel_by(DECODE_SEGMENT, Settings::default(), source, destination)
+    }
+
+    /// Decodes the entire source like [`decode_parallel`](#method.decode_parallel) with the given
+    /// settings, which must not be strict, since the strict checks can't be split into segments.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn decode_parallel_by<R: Read + ?Sized, W: Write + ?Sized>(
+        &self,
+        segment: usize,
+        settings: Settings,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let block = segment * rayon::current_num_threads();
+        self.decode_blocks(
+            segment,
+            block,
+            settings,
+            source,
+            destination,
+            |start, segments, offset, position| {
+                segments
+                    .par_iter()
+                    .map(|s| {
+                        let (offset, position) = (offset + s.offset, position + s.position);
+                        self.decode_segment(start, settings, s.text, offset, position)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Decodes the entire source from the Ecoji format like
+    /// [`decode_parallel`](#method.decode_parallel) does, but on the given number of threads
+    /// spawned in the given scope instead of the rayon thread pool, so it doesn't need the
+    /// `parallel` feature.
+    ///
+    /// The threads are spawned for every block of the source, and all of them have finished by
+    /// the time this returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use std::thread;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let input = "👶😲🇲👅🍉🔙🌥🌩";
+    /// let workers = thread::available_parallelism()?;
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    /// thread::scope(|scope| {
+    ///     ecoji::VERSION1.decode_parallel_with(scope, workers, &mut input.as_bytes(), &mut output)
+    /// })?;
+    ///
+    /// assert_eq!(output, b"input data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_parallel_with<'scope, 'env, R: Read + ?Sized, W: Write + ?Sized>(
+        &'env self,
+        scope: &'scope Scope<'scope, 'env>,
+        workers: NonZeroUsize,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        self.decode_scoped_by(scope, workers, DECODE_SEGMENT, source, destination)
+    }
+
+    fn decode_scoped_by<'scope, 'env, R: Read + ?Sized, W: Write + ?Sized>(
+        &'env self,
+        scope: &'scope Scope<'scope, 'env>,
+        workers: NonZeroUsize,
+        segment: usize,
+        source: &mut R,
+        destination: &mut W,
+    ) -> io::Result<usize> {
+        let block = segment * workers.get();
+        let settings = Settings::default();
+        self.decode_blocks(
+            segment,
+            block,
+            settings,
+            source,
+            destination,
+            |start, segments, offset, position| {
+                let handles: Vec<_> = segments
+                    .iter()
+                    .map(|s| {
+                        let text = s.text.to_owned();
+                        let (offset, position) = (offset + s.offset, position + s.position);
+                        scope.spawn(move || {
+                            self.decode_segment(start, settings, &text, offset, position)
+                        })
+                    })
+                    .collect();
+                handles.into_iter().map(join).collect()
+            },
+        )
+    }
+
+    /// Reads the source in blocks of about the given size, splits them into segments, and writes
+    /// the decoded segments, which `decode_segments` decodes in parallel starting with the given
+    /// decoder, given the location of the block in the input.
+    fn decode_blocks<'a, R, W, F>(
+        &'a self,
+        segment: usize,
+        block: usize,
+        settings: Settings,
+        source: &mut R,
+        destination: &mut W,
+        decode_segments: F,
+    ) -> io::Result<usize>
+    where
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+        F: Fn(&'a Version, &[Segment], usize, usize) -> Vec<SegmentResult<'a>>,
+    {
+        // The block must be large enough to always contain a whole group of 4 code points after
+        // the remains of the previous block
+        let block = block.max(64);
+        let mut buf = Vec::with_capacity(block);
+        let mut decoder = self;
+        // Location of the beginning of the buffer in the input, and the chunks before it
+        let (mut offset, mut position, mut chunks) = (0, 0, 0);
+        let mut bytes_written = 0;
+
+        loop {
+            let filled = buf.len();
+            buf.resize(block, 0);
+            let n = read_exact(source, &mut buf[filled..])?;
+            buf.truncate(filled + n);
+            let eof = buf.len() < block;
+
+            let text = match str::from_utf8(&buf) {
+                Ok(text) => text,
+                // A character is split between this block and the next one
+                Err(e) if e.error_len().is_none() && !eof => {
+                    str::from_utf8(&buf[..e.valid_up_to()]).unwrap()
+                }
+                // Invalid UTF-8; let the sequential decoder find out where exactly it fails
+                Err(_) => {
+                    let mut rest = buf.as_slice().chain(source);
+                    return self
+                        .decode_from(&mut decoder, settings, &mut rest, destination)
+                        .map(|n| bytes_written + n)
+                        .map_err(|e| count_chunks(shift(e, offset, position), chunks));
+                }
+            };
+
+            let (segments, consumed, chars, groups) =
+                split_groups(text, segment, eof, settings.line_breaks);
+            let mut results = decode_segments(decoder, &segments, offset, position);
+
+            // Once a segment has switched the decoder, the following ones must be decoded by the
+            // switched decoder from the start
+            if let Some(i) = results.iter().position(|(_, d)| !ptr::eq(*d, decoder)) {
+                let switched = results[i].1;
+                let redone = decode_segments(switched, &segments[i + 1..], offset, position);
+                results.truncate(i + 1);
+                results.extend(redone);
+            }
+
+            for ((result, next), s) in results.into_iter().zip(&segments) {
+                // the segments only count the chunks they contain themselves
+                let output = result.map_err(|e| count_chunks(e, chunks + s.chunks))?;
+                destination.write_all(&output)?;
+                bytes_written += output.len();
+                decoder = next;
+            }
+
+            buf.drain(..consumed);
+            offset += consumed;
+            position += chars;
+            chunks += groups;
+
+            if eof {
+                break;
+            }
+        }
+
+        Ok(bytes_written)
+    }
+
+    /// Decodes a segment which starts at the given location in the input, starting with the
+    /// given decoder. Returns the result of decoding along with the decoder to be used for the
+    /// following segment.
+    fn decode_segment<'a>(
+        &'a self,
+        start: &'a Version,
+        settings: Settings,
+        text: &str,
+        offset: usize,
+        position: usize,
+    ) -> SegmentResult<'a> {
+        let mut decoder = start;
+        let mut output = Vec::with_capacity(text.len() / 16 * 5 + 5);
+        let result = self
+            .decode_from(&mut decoder, settings, &mut text.as_bytes(), &mut output)
+            .map(|_| output)
+            .map_err(|e| shift(e, offset, position));
+        (result, decoder)
+    }
+}
+
+/// Waits for a worker thread, propagating its panic.
+fn join<T>(handle: ScopedJoinHandle<T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|panic| panic::resume_unwind(panic))
+}
+
+/// Splits the text into segments of at least `size` bytes, each consisting of whole groups of
+/// 4 code points, not counting line breaks if they are skipped, except for the last one. Unless
+/// `complete` is set, the trailing incomplete group, if any, is left out. Returns the segments
+/// along with the number of bytes, code points and whole groups covered by them.
+fn split_groups(
+    text: &str,
+    size: usize,
+    complete: bool,
+    line_breaks: bool,
+) -> (Vec<Segment<'_>>, usize, usize, usize) {
+    let mut segments = Vec::new();
+    let (mut start, mut start_chars, mut start_symbols) = (0, 0, 0);
+    let (mut boundary, mut boundary_chars) = (0, 0);
+    let (mut chars, mut symbols) = (0, 0);
+
+    for (i, c) in text.char_indices() {
+        if !(line_breaks && is_line_break(c)) {
+            if symbols % 4 == 0 {
+                boundary = i;
+                boundary_chars = chars;
+                if i - start >= size {
+                    segments.push(Segment {
+                        text: &text[start..i],
+                        offset: start,
+                        position: start_chars,
+                        chunks: start_symbols / 4,
+                    });
+                    start = i;
+                    start_chars = chars;
+                    start_symbols = symbols;
+                }
+            }
+            symbols += 1;
+        }
+        chars += 1;
+    }
+
+    let (end, end_chars) = if complete || symbols % 4 == 0 {
+        (text.len(), chars)
+    } else {
+        (boundary, boundary_chars)
+    };
+    if end > start {
+        segments.push(Segment {
+            text: &text[start..end],
+            offset: start,
+            position: start_chars,
+            chunks: start_symbols / 4,
+        });
+    }
+
+    (segments, end, end_chars, symbols / 4)
+}
+
+fn shift(e: io::Error, offset: usize, position: usize) -> io::Error {
+    match DecodeError::from_io(&e) {
+        Some(d) => d.clone().shifted(offset, position).into(),
+        None => e,
+    }
+}
+
+fn count_chunks(e: io::Error, chunks: usize) -> io::Error {
+    match DecodeError::from_io(&e) {
+        Some(d) => d.clone().after_chunks(chunks).into(),
+        None => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emojis::{VERSION1, VERSION2, VERSIONS};
+    use quickcheck::{Arbitrary, Gen};
+    use std::thread;
+
+    /// A character likely to be found in Ecoji input, valid or not.
+    #[derive(Debug, Clone)]
+    struct InputChar(char);
+
+    impl Arbitrary for InputChar {
+        fn arbitrary(g: &mut Gen) -> InputChar {
+            let v = g.choose(&VERSIONS).unwrap();
+            InputChar(match u8::arbitrary(g) % 16 {
+                0 => v.PADDING,
+                1 => v.PADDING_40,
+                2 => v.PADDING_41,
+                3 => 'x',
+                4 => '\n',
+                _ => v.EMOJIS[usize::arbitrary(g) % 1024],
+            })
+        }
+    }
+
+    fn sequential(v: &Version, input: &[u8]) -> Result<Vec<u8>, Option<DecodeError>> {
+        v.decode_to_vec(&mut &input[..])
+            .map_err(|e| DecodeError::from_io(&e).cloned())
+    }
+
+    #[cfg(feature = "parallel")]
+    fn parallel(v: &Version, segment: usize, input: &[u8]) -> Result<Vec<u8>, Option<DecodeError>> {
+        parallel_with(v, segment, Settings::default(), input)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn parallel_with(
+        v: &Version,
+        segment: usize,
+        settings: Settings,
+        input: &[u8],
+    ) -> Result<Vec<u8>, Option<DecodeError>> {
+        let mut output = Vec::new();
+        v.decode_parallel_by(segment, settings, &mut &input[..], &mut output)
+            .map(|_| output)
+            .map_err(|e| DecodeError::from_io(&e).cloned())
+    }
+
+    fn scoped(v: &Version, segment: usize, input: &[u8]) -> Result<Vec<u8>, Option<DecodeError>> {
+        let mut output = Vec::new();
+        let workers = NonZeroUsize::new(3).unwrap();
+        thread::scope(|scope| {
+            v.decode_scoped_by(scope, workers, segment, &mut &input[..], &mut output)
+        })
+        .map(|_| output)
+        .map_err(|e| DecodeError::from_io(&e).cloned())
+    }
+
+    #[cfg(feature = "parallel")]
+    quickcheck! {
+        fn encode_parallel_is_the_same(input: Vec<u8>, segment: u8) -> bool {
+            let segment = (segment as usize % 8 + 1) * 5;
+            VERSIONS.iter().all(|v| {
+                let mut output = Vec::new();
+                v.encode_parallel_by(segment, &mut input.as_slice(), &mut output).unwrap();
+                output == v.encode_to_string(&mut input.as_slice()).unwrap().into_bytes()
+            })
+        }
+
+        fn decode_parallel_is_the_same(input: Vec<InputChar>, segment: u8) -> bool {
+            let segment = segment as usize % 64 + 1;
+            let input: String = input.into_iter().map(|c| c.0).collect();
+            VERSIONS.iter().all(|v| {
+                sequential(v, input.as_bytes()) == parallel(v, segment, input.as_bytes())
+            })
+        }
+
+        fn decode_parallel_of_encoded_is_the_same(input: Vec<u8>, segment: u8) -> bool {
+            let segment = segment as usize % 64 + 1;
+            let encoded = VERSION2.encode_to_string(&mut input.as_slice()).unwrap();
+            parallel(&VERSION1, segment, encoded.as_bytes()) == Ok(input)
+        }
+
+        fn decode_parallel_of_wrapped_is_the_same(input: Vec<u8>, segment: u8, width: u8) -> bool {
+            let segment = segment as usize % 64 + 1;
+            let encoded = VERSION2.encode_to_string(&mut input.as_slice()).unwrap();
+            let chars: Vec<char> = encoded.chars().collect();
+            let wrapped: String = chars
+                .chunks(width as usize % 8 + 1)
+                .map(|line| line.iter().collect::<String>() + "\n")
+                .collect();
+            let settings = Settings {
+                line_breaks: true,
+                ..Settings::default()
+            };
+            parallel_with(&VERSION2, segment, settings, wrapped.as_bytes()) == Ok(input)
+        }
+    }
+
+    quickcheck! {
+        fn encode_scoped_is_the_same(input: Vec<u8>, segment: u8, workers: u8) -> bool {
+            let segment = (segment as usize % 8 + 1) * 5;
+            let workers = NonZeroUsize::new(workers as usize % 4 + 1).unwrap();
+            VERSIONS.iter().all(|v| {
+                let mut output = Vec::new();
+                thread::scope(|scope| {
+                    v.encode_scoped_by(scope, workers, segment, &mut input.as_slice(), &mut output)
+                })
+                .unwrap();
+                output == v.encode_to_string(&mut input.as_slice()).unwrap().into_bytes()
+            })
+        }
+
+        fn decode_scoped_is_the_same(input: Vec<InputChar>, segment: u8) -> bool {
+            let segment = segment as usize % 64 + 1;
+            let input: String = input.into_iter().map(|c| c.0).collect();
+            VERSIONS.iter().all(|v| {
+                sequential(v, input.as_bytes()) == scoped(v, segment, input.as_bytes())
+            })
+        }
+
+        fn decode_scoped_of_encoded_is_the_same(input: Vec<u8>, segment: u8) -> bool {
+            let segment = segment as usize % 64 + 1;
+            let encoded = VERSION2.encode_to_string(&mut input.as_slice()).unwrap();
+            scoped(&VERSION1, segment, encoded.as_bytes()) == Ok(input)
+        }
+    }
+
+    #[test]
+    fn decode_parallel_invalid_utf8() {
+        let mut input = "👖📸🎈☕".repeat(40).into_bytes();
+        input.extend_from_slice(b"\xf0\x9f\x91");
+        input.extend_from_slice("👖📸🎈☕".as_bytes());
+        for segment in 1..20 {
+            #[cfg(feature = "parallel")]
+            assert_eq!(
+                sequential(&VERSION1, &input),
+                parallel(&VERSION1, segment, &input)
+            );
+            assert_eq!(
+                sequential(&VERSION1, &input),
+                scoped(&VERSION1, segment, &input)
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Decoding of the complete chunks in a buffer of encoded data which is still arriving.
+
+use std::io;
+use std::str;
+
+use crate::emojis::Version;
+
+/// The result of [`Version::decode_partial`](emojis/struct.Version.html#method.decode_partial).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PartialDecode {
+    data: Vec<u8>,
+    consumed: usize,
+}
+
+impl PartialDecode {
+    /// Returns the data decoded from the complete chunks.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the data decoded from the complete chunks, consuming the result.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns the number of input bytes which have been decoded. The rest of the input should
+    /// be passed again, followed by more data.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+}
+
+impl Version {
+    /// Decodes all complete chunks at the start of the input, which may end anywhere, even in the
+    /// middle of a character, e.g. because it is a buffer being filled from a socket. Unlike
+    /// [`decode`](#method.decode), this does not treat a truncated chunk as an error, but leaves
+    /// it undecoded, and reports how much of the input was consumed.
+    ///
+    /// Since the last chunk of version 2 may be shorter than 4 symbols, a complete chunk cannot be
+    /// told apart from a truncated one at the end of the input. Once no more data arrives, the
+    /// remaining input has to be decoded with `decode`.
+    ///
+    /// Every call starts decoding with this version, and fails under the same conditions as
+    /// `decode`, with offsets relative to the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let input = "👶😲🇲👅🍉🔙🌥🌩".as_bytes();
+    ///
+    /// let partial = ecoji::VERSION1.decode_partial(&input[..27])?;
+    /// assert_eq!(partial.data(), b"input");
+    /// assert_eq!(partial.consumed(), 16);
+    ///
+    /// let partial = ecoji::VERSION1.decode_partial(&input[partial.consumed()..])?;
+    /// assert_eq!(partial.data(), b" data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_partial(&self, input: &[u8]) -> io::Result<PartialDecode> {
+        let text = match str::from_utf8(input) {
+            Ok(text) => text,
+            // the last character is incomplete
+            Err(e) if e.error_len().is_none() => unsafe {
+                str::from_utf8_unchecked(&input[..e.valid_up_to()])
+            },
+            // let the decoder report the invalid UTF-8
+            Err(_) => {
+                self.decode_to_vec(&mut &input[..])?;
+                unreachable!("Invalid UTF-8 decoded successfully");
+            }
+        };
+
+        let mut consumed = 0;
+        for (symbols, (i, c)) in text.char_indices().enumerate() {
+            if symbols % 4 == 3 {
+                consumed = i + c.len_utf8();
+            }
+        }
+
+        let mut source = &text.as_bytes()[..consumed];
+        let data = self.decode_to_vec(&mut source)?;
+        Ok(PartialDecode { data, consumed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::VERSIONS;
+
+    quickcheck! {
+        fn decode_partial_resumes(input: Vec<u8>, split: usize) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let encoded = encoded.as_bytes();
+                let split = split % (encoded.len() + 1);
+
+                let first = v.decode_partial(&encoded[..split]).unwrap();
+                let rest = &encoded[first.consumed()..];
+                let mut output = first.into_data();
+                output.extend(v.decode_to_vec(&mut &rest[..]).unwrap());
+                output == input
+            })
+        }
+    }
+}
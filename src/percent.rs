@@ -0,0 +1,116 @@
+//! Percent-encoded output, for embedding encoded data in URLs.
+
+use std::io;
+
+use crate::emojis::Version;
+
+impl Version {
+    /// Encodes the data and percent-encodes every byte of the result, so that it can be put into
+    /// any part of a URL, such as a query string, as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let url = format!("https://example.com/?data={}", ecoji::VERSION1.encode_url(b"abc"));
+    ///
+    /// assert_eq!(
+    ///     url,
+    ///     "https://example.com/?data=%F0%9F%91%96%F0%9F%93%B8%F0%9F%8E%88%E2%98%95"
+    /// );
+    /// ```
+    pub fn encode_url(&self, input: &[u8]) -> String {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+        let encoded = self
+            .encode_to_string(&mut &input[..])
+            .expect("Encoding a slice failed");
+        let mut output = String::with_capacity(encoded.len() * 3);
+        for b in encoded.bytes() {
+            output.push('%');
+            output.push(HEX[(b >> 4) as usize] as char);
+            output.push(HEX[(b & 0xf) as usize] as char);
+        }
+        output
+    }
+
+    /// Percent-decodes the input and decodes the result, like [`decode`](#method.decode) does.
+    /// Characters which are not percent-encoded are taken as they are, since URLs with emoji in
+    /// them are often unescaped along the way.
+    ///
+    /// Fails under the same conditions as `decode`, with a `%` which does not start a valid
+    /// escape treated as an invalid character. Error offsets refer to the percent-decoded input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let output = ecoji::VERSION1.decode_url("%F0%9F%91%96%F0%9F%93%B8%F0%9F%8E%88☕")?;
+    ///
+    /// assert_eq!(output, b"abc");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_url(&self, input: &str) -> io::Result<Vec<u8>> {
+        let input = input.as_bytes();
+        let mut unescaped = Vec::with_capacity(input.len());
+        let mut i = 0;
+        while i < input.len() {
+            let escaped = match input[i] {
+                b'%' if i + 2 < input.len() => hex_value(input[i + 1]).zip(hex_value(input[i + 2])),
+                _ => None,
+            };
+            match escaped {
+                Some((high, low)) => {
+                    unescaped.push(high << 4 | low);
+                    i += 3;
+                }
+                None => {
+                    unescaped.push(input[i]);
+                    i += 1;
+                }
+            }
+        }
+        self.decode_to_vec(&mut unescaped.as_slice())
+    }
+}
+
+/// Returns the value of a hexadecimal digit.
+pub(crate) fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::{VERSION1, VERSIONS};
+    use crate::error::{DecodeError, DecodeErrorKind};
+
+    #[test]
+    fn test_invalid_escape() {
+        for input in ["%F0%9F%91%96%F0%9F%93%B8%F0%9F%8E%88%E2%98%9", "%zz"] {
+            let e = VERSION1.decode_url(input).unwrap_err();
+            let kind = DecodeError::from_io(&e).map(DecodeError::kind);
+            assert!(kind.is_some(), "Unexpected error {:?}", e);
+        }
+        let e = VERSION1.decode_url("%").unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&e).map(DecodeError::kind),
+            Some(&DecodeErrorKind::InvalidChar('%'))
+        );
+    }
+
+    quickcheck! {
+        fn decode_url_reverses_encode_url(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let url = v.encode_url(&input);
+                url.bytes().all(|b| b == b'%' || b.is_ascii_hexdigit())
+                    && v.decode_url(&url).unwrap() == input
+            })
+        }
+    }
+}
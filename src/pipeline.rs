@@ -0,0 +1,215 @@
+//! Encoding and decoding of messages passed through channels on background threads.
+
+use std::io;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+
+use crate::emojis::Version;
+
+/// The receiving end of a channel which a pipeline thread takes its input from.
+///
+/// Implemented for the receivers of `std::sync::mpsc`, and with the `crossbeam-channel` feature,
+/// of `crossbeam_channel`.
+pub trait MessageReceiver<T> {
+    /// Blocks until a message arrives, returning `None` once the channel is disconnected.
+    fn receive(&self) -> Option<T>;
+}
+
+/// The sending end of a channel which a pipeline thread sends its output to.
+///
+/// Implemented for the senders of `std::sync::mpsc`, and with the `crossbeam-channel` feature,
+/// of `crossbeam_channel`. Bounded channels, like those created by `mpsc::sync_channel`, make
+/// the thread wait while the consumer is behind.
+pub trait MessageSender<T> {
+    /// Sends the message, blocking if the channel is full, and returns whether the channel is
+    /// still connected.
+    fn send_message(&self, message: T) -> bool;
+}
+
+impl<T> MessageReceiver<T> for mpsc::Receiver<T> {
+    fn receive(&self) -> Option<T> {
+        self.recv().ok()
+    }
+}
+
+impl<T> MessageSender<T> for mpsc::Sender<T> {
+    fn send_message(&self, message: T) -> bool {
+        self.send(message).is_ok()
+    }
+}
+
+impl<T> MessageSender<T> for mpsc::SyncSender<T> {
+    fn send_message(&self, message: T) -> bool {
+        self.send(message).is_ok()
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<T> MessageReceiver<T> for crossbeam_channel::Receiver<T> {
+    fn receive(&self) -> Option<T> {
+        self.recv().ok()
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<T> MessageSender<T> for crossbeam_channel::Sender<T> {
+    fn send_message(&self, message: T) -> bool {
+        self.send(message).is_ok()
+    }
+}
+
+/// Spawns a thread which encodes the data received from `rx` and sends the encoded text to `tx`.
+///
+/// The messages are treated as parts of a single stream: every message sent is the encoding of
+/// whole chunks, with the bytes which don't fill a chunk carried over to the next one, so the
+/// concatenation of the output is exactly the encoding of the concatenated input. The thread
+/// finishes once `rx` is disconnected and the rest of the data is sent, or once `tx` is
+/// disconnected.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::mpsc;
+///
+/// let (input, rx) = mpsc::sync_channel(4);
+/// let (tx, output) = mpsc::sync_channel(4);
+/// let encoder = ecoji::spawn_encoder(&ecoji::VERSION1, rx, tx);
+///
+/// input.send(b"input".to_vec()).unwrap();
+/// input.send(b" da".to_vec()).unwrap();
+/// input.send(b"ta".to_vec()).unwrap();
+/// drop(input);
+///
+/// let encoded: Vec<String> = output.iter().collect();
+/// assert_eq!(encoded, ["👶😲🇲👅", "🍉🔙🌥🌩"]);
+/// encoder.join().unwrap();
+/// ```
+pub fn spawn_encoder<R, S>(version: &'static Version, rx: R, tx: S) -> JoinHandle<()>
+where
+    R: MessageReceiver<Vec<u8>> + Send + 'static,
+    S: MessageSender<String> + Send + 'static,
+{
+    thread::spawn(move || {
+        let encode = |data: &[u8]| {
+            version
+                .encode_to_string(&mut &data[..])
+                .expect("Encoding a slice failed")
+        };
+
+        let mut pending = Vec::new();
+        while let Some(data) = rx.receive() {
+            pending.extend_from_slice(&data);
+            let complete = pending.len() - pending.len() % 5;
+            if complete == 0 {
+                continue;
+            }
+            if !tx.send_message(encode(&pending[..complete])) {
+                return;
+            }
+            pending.drain(..complete);
+        }
+        if !pending.is_empty() {
+            tx.send_message(encode(&pending));
+        }
+    })
+}
+
+/// Spawns a thread which decodes the text received from `rx`, starting with the given version,
+/// and sends the decoded data to `tx`.
+///
+/// The messages are treated as parts of a single stream, which may be split anywhere, even in
+/// the middle of a chunk: the complete chunks are decoded as they arrive, like
+/// [`Version::decode_partial`](emojis/struct.Version.html#method.decode_partial) does, and the
+/// rest is carried over to the next message. The thread finishes once `rx` is disconnected and
+/// the rest of the text is decoded, or once `tx` is disconnected, and returns the error if the
+/// text fails to decode, with offsets relative to the text which was not decoded yet.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::mpsc;
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let (input, rx) = mpsc::channel();
+/// let (tx, output) = mpsc::channel();
+/// let decoder = ecoji::spawn_decoder(&ecoji::VERSION1, rx, tx);
+///
+/// input.send("👶😲🇲👅🍉".to_owned()).unwrap();
+/// input.send("🔙🌥🌩".to_owned()).unwrap();
+/// drop(input);
+///
+/// let decoded: Vec<Vec<u8>> = output.iter().collect();
+/// assert_eq!(decoded, [b"input", b" data"]);
+/// decoder.join().unwrap()?;
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn spawn_decoder<R, S>(version: &'static Version, rx: R, tx: S) -> JoinHandle<io::Result<()>>
+where
+    R: MessageReceiver<String> + Send + 'static,
+    S: MessageSender<Vec<u8>> + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut pending = Vec::new();
+        while let Some(text) = rx.receive() {
+            pending.extend_from_slice(text.as_bytes());
+            let partial = version.decode_partial(&pending)?;
+            pending.drain(..partial.consumed());
+            if !partial.data().is_empty() && !tx.send_message(partial.into_data()) {
+                return Ok(());
+            }
+        }
+        let data = version.decode_to_vec(&mut pending.as_slice())?;
+        if !data.is_empty() {
+            tx.send_message(data);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::{spawn_decoder, spawn_encoder};
+    use crate::emojis::VERSIONS;
+
+    quickcheck! {
+        fn pipelines_round_trip(messages: Vec<Vec<u8>>) -> bool {
+            VERSIONS.iter().all(|&v| {
+                let (input, rx) = mpsc::sync_channel(1);
+                let (tx, encoded) = mpsc::sync_channel(1);
+                let (encoded_tx, decoded) = mpsc::channel();
+                let encoder = spawn_encoder(v, rx, tx);
+                let decoder = spawn_decoder(v, encoded, encoded_tx);
+
+                for message in &messages {
+                    input.send(message.clone()).unwrap();
+                }
+                drop(input);
+
+                let output: Vec<u8> = decoded.iter().flatten().collect();
+                encoder.join().unwrap();
+                decoder.join().unwrap().unwrap();
+                output == messages.concat()
+            })
+        }
+    }
+
+    #[cfg(feature = "crossbeam-channel")]
+    #[test]
+    fn test_crossbeam() {
+        let (input, rx) = crossbeam_channel::bounded(1);
+        let (tx, output) = crossbeam_channel::bounded(1);
+        let decoder = spawn_decoder(&crate::VERSION2, rx, tx);
+
+        input.send("👶😲⛵👅🍉🧴".to_owned()).unwrap();
+        input.send("🦪🦮".to_owned()).unwrap();
+        drop(input);
+
+        let decoded: Vec<u8> = output.iter().flatten().collect();
+        assert_eq!(decoded, b"input data");
+        decoder.join().unwrap().unwrap();
+    }
+}
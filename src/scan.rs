@@ -0,0 +1,149 @@
+//! Extraction of encoded data from arbitrary text.
+
+use std::io;
+use std::ops::Range;
+
+use crate::alphabet::AlphabetSet;
+use crate::decode::{is_line_break, Settings};
+use crate::emojis::Version;
+
+/// A maximal run of characters of the Ecoji alphabets found in text, yielded by
+/// [`Scanner`](struct.Scanner.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Run<'a> {
+    version: &'a Version,
+    start: usize,
+    text: &'a str,
+}
+
+impl<'a> Run<'a> {
+    /// Returns the byte range of the run in the scanned text.
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.start + self.text.len()
+    }
+
+    /// Returns the text of the run, which may include line breaks, but does not start or end with
+    /// them.
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Decodes the run with the version which was scanned for, like
+    /// [`Version::decode`](emojis/struct.Version.html#method.decode) does, but skipping the line
+    /// breaks. This fails in the same way for runs which are not encoded data, like a lone emoji
+    /// in a sentence.
+    pub fn decode(&self) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut decoder = self.version;
+        self.version.decode_from(
+            &mut decoder,
+            Settings::wrapped(),
+            &mut self.text.as_bytes(),
+            &mut output,
+        )?;
+        Ok(output)
+    }
+}
+
+/// An iterator over the runs of characters of the Ecoji alphabets in text, returned by
+/// [`Version::scan`](emojis/struct.Version.html#method.scan).
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    version: &'a Version,
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn is_symbol(&self, c: char) -> bool {
+        AlphabetSet::all().contains(c)
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Run<'a>;
+
+    fn next(&mut self) -> Option<Run<'a>> {
+        let rest = &self.text[self.position..];
+        let (offset, _) = rest.char_indices().find(|&(_, c)| self.is_symbol(c))?;
+        let start = self.position + offset;
+
+        // the run continues over line breaks followed by more symbols
+        let mut end = start;
+        for (i, c) in self.text[start..].char_indices() {
+            if self.is_symbol(c) {
+                end = start + i + c.len_utf8();
+            } else if !is_line_break(c) {
+                break;
+            }
+        }
+        self.position = end;
+        Some(Run {
+            version: self.version,
+            start,
+            text: &self.text[start..end],
+        })
+    }
+}
+
+impl Version {
+    /// Returns an iterator over the maximal runs of characters of either alphabet in the text,
+    /// e.g. a message or a document with encoded data somewhere in it. Runs continue over line
+    /// breaks, so wrapped data forms a single run; anything else ends them.
+    ///
+    /// Any emoji of the alphabets forms a run, so not every run decodes successfully; the runs
+    /// are decoded starting with this version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let text = "Here it is: 👖📸🎈☕ thanks!";
+    /// let runs: Vec<_> = ecoji::VERSION1.scan(text).collect();
+    ///
+    /// assert_eq!(runs.len(), 1);
+    /// assert_eq!(runs[0].span(), 12..27);
+    /// assert_eq!(runs[0].decode()?, b"abc");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn scan<'a>(&'a self, text: &'a str) -> Scanner<'a> {
+        Scanner {
+            version: self,
+            text,
+            position: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::{VERSION1, VERSION2};
+
+    #[test]
+    fn test_scan() {
+        let first = VERSION1.encode_to_string(&mut &b"first"[..]).unwrap();
+        let second = VERSION2.encode_to_string(&mut &b"second"[..]).unwrap();
+        let split = second.char_indices().nth(3).unwrap().0;
+        let text = format!(
+            "> {}\n> 👍 and\n{}\r\n{}\nbye 👍",
+            first,
+            &second[..split],
+            &second[split..]
+        );
+
+        let runs: Vec<_> = VERSION1.scan(&text).collect();
+        assert_eq!(runs.len(), 4);
+        assert_eq!(runs[0].as_str(), first);
+        assert_eq!(runs[0].decode().unwrap(), b"first");
+        assert_eq!(&text[runs[1].span()], "👍");
+        assert!(runs[1].decode().is_err());
+        assert_eq!(
+            runs[2].as_str(),
+            format!("{}\r\n{}", &second[..split], &second[split..])
+        );
+        assert_eq!(runs[2].decode().unwrap(), b"second");
+        assert_eq!(runs[3].span(), text.len() - 4..text.len());
+    }
+}
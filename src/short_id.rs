@@ -0,0 +1,172 @@
+//! Short identifiers with a checksum symbol, e.g. for coupon codes and invite links.
+//!
+//! A short ID is a value of 4 to 16 bytes encoded like [`encode`](../fn.encode.html) does,
+//! followed by one more symbol of the alphabet holding a 10-bit checksum of the value, a CRC-10.
+//! It detects any change of a single symbol, since that changes at most 10 consecutive bits of
+//! the value, as well as most other typos, like swapped or missing symbols, or text which has
+//! been cut off while being pasted.
+//!
+//! The length of the value is a part of the type, so that it is checked at compile time.
+//!
+//! # Examples
+//!
+//! ```
+//! use ecoji::{short_id, DecodeError, DecodeErrorKind};
+//!
+//! # fn test() -> ::std::io::Result<()> {
+//! let code = short_id::encode(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc]);
+//! assert_eq!(code.chars().count(), 9);
+//! assert_eq!(short_id::decode::<6>(&code)?, [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc]);
+//!
+//! let mistyped = code.replacen(code.chars().nth(2).unwrap(), "🍉", 1);
+//! let e = short_id::decode::<6>(&mistyped).unwrap_err();
+//! assert_eq!(
+//!     DecodeError::from_io(&e).map(DecodeError::kind),
+//!     Some(&DecodeErrorKind::ChecksumMismatch)
+//! );
+//! #  Ok(())
+//! # }
+//! # test().unwrap();
+//! ```
+
+use std::convert::TryInto;
+use std::io;
+
+use crate::array::wrong_length;
+use crate::decode::is_line_break;
+use crate::emojis::Version;
+use crate::error::{DecodeError, DecodeErrorKind};
+use crate::DEFAULT_VERSION;
+
+/// The generator polynomial of the CRC-10, `x^10 + x^9 + x^5 + x^4 + x + 1`, without the
+/// highest term.
+const POLYNOMIAL: u16 = 0x233;
+
+/// Encodes the value with the [default version](../constant.DEFAULT_VERSION.html), appending
+/// the checksum symbol; see [`encode_with`](fn.encode_with.html).
+pub fn encode<const N: usize>(value: &[u8; N]) -> String {
+    encode_with(DEFAULT_VERSION, value)
+}
+
+/// Encodes the value with the given version, appending the checksum symbol.
+///
+/// Fails to compile unless the value is 4 to 16 bytes long.
+pub fn encode_with<const N: usize>(version: &Version, value: &[u8; N]) -> String {
+    const { assert!(N >= 4 && N <= 16, "Short IDs are 4 to 16 bytes long") };
+
+    let mut id = version.encode_array(value).to_string();
+    id.push(version.EMOJIS[checksum(value)]);
+    id
+}
+
+/// Decodes a short ID encoded with the default version, validating its checksum symbol; see
+/// [`decode_with`](fn.decode_with.html).
+pub fn decode<const N: usize>(input: &str) -> io::Result<[u8; N]> {
+    decode_with(DEFAULT_VERSION, input)
+}
+
+/// Decodes a short ID encoded with the given version, validating its checksum symbol. Line
+/// breaks are ignored, like [`Version::decode`](../emojis/struct.Version.html#method.decode)
+/// ignores them.
+///
+/// Only the canonical encoding of the value is accepted, so that each value has a single ID.
+/// Fails under the same conditions as
+/// [`Version::decode_strict`](../emojis/struct.Version.html#method.decode_strict), with an error
+/// of the `io::ErrorKind::InvalidData` kind if the value is not `N` bytes long, and with an
+/// error of the
+/// [`DecodeErrorKind::ChecksumMismatch`](../enum.DecodeErrorKind.html#variant.ChecksumMismatch)
+/// kind if the checksum symbol does not match the value. Fails to compile unless the value is 4
+/// to 16 bytes long.
+pub fn decode_with<const N: usize>(version: &Version, input: &str) -> io::Result<[u8; N]> {
+    const { assert!(N >= 4 && N <= 16, "Short IDs are 4 to 16 bytes long") };
+
+    let input = input.trim_end_matches(is_line_break);
+    let (offset, symbol) = input.char_indices().next_back().ok_or_else(|| {
+        let kind = DecodeErrorKind::UnexpectedEof {
+            symbols: 0,
+            chunks: 0,
+        };
+        DecodeError::new(kind, 0, 0)
+    })?;
+    let body = &input[..offset];
+    let mut data = Vec::with_capacity(N);
+    version.decode_strict(&mut body.as_bytes(), &mut data)?;
+    let value: [u8; N] = data
+        .try_into()
+        .map_err(|data: Vec<u8>| wrong_length(data.len(), N))?;
+    if version.EMOJIS_REV.get(&symbol) != Some(&checksum(&value)) {
+        let position = body.chars().count();
+        return Err(DecodeError::new(DecodeErrorKind::ChecksumMismatch, offset, position).into());
+    }
+    Ok(value)
+}
+
+/// Computes the CRC-10 of the data, which is the index of the checksum symbol.
+fn checksum(data: &[u8]) -> usize {
+    let mut crc: u16 = 0;
+    for &b in data {
+        crc ^= (b as u16) << 2;
+        for _ in 0..8 {
+            crc = if crc & 0x200 != 0 {
+                (crc << 1) ^ POLYNOMIAL
+            } else {
+                crc << 1
+            };
+        }
+    }
+    (crc & 0x3ff) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_checksum() {
+        // the CRC-10/ATM check value
+        assert_eq!(checksum(b"123456789"), 0x199);
+        assert_eq!(checksum(&[0; 16]), 0);
+    }
+
+    #[test]
+    fn test_invalid_ids() {
+        let id = encode(b"abcdefgh");
+        for input in ["", "\n", &id[..id.len() - 4]] {
+            assert!(decode::<8>(input).is_err());
+        }
+        assert!(decode::<7>(&id).is_err());
+        assert_eq!(decode::<8>(&format!("{}\r\n", id)).unwrap(), *b"abcdefgh");
+
+        let e = decode_with::<8>(&crate::VERSION2, &id).unwrap_err();
+        assert!(DecodeError::from_io(&e).is_some());
+    }
+
+    /// Checks that replacing the symbol at the position is detected, unless it's the same.
+    fn substitution_detected<const N: usize>(value: &[u8; N], position: u8, symbol: u16) -> bool {
+        VERSIONS.iter().all(|&v| {
+            let id = encode_with(v, value);
+            let mut symbols: Vec<char> = id.chars().collect();
+            let position = position as usize % symbols.len();
+            let replacement = v.EMOJIS[symbol as usize % 1024];
+            let changed = symbols[position] != replacement;
+            symbols[position] = replacement;
+            let mistyped: String = symbols.into_iter().collect();
+
+            decode_with::<N>(v, &id).unwrap() == *value
+                && decode_with::<N>(v, &mistyped).is_err() == changed
+        })
+    }
+
+    quickcheck! {
+        fn substitutions_are_detected(value: (u64, u64), position: u8, symbol: u16) -> bool {
+            let mut bytes = [0; 16];
+            bytes[..8].copy_from_slice(&value.0.to_le_bytes());
+            bytes[8..].copy_from_slice(&value.1.to_le_bytes());
+            let short: [u8; 6] = bytes[..6].try_into().unwrap();
+
+            substitution_detected(&bytes, position, symbol)
+                && substitution_detected(&short, position, symbol)
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Output destinations of both kinds, byte streams and text.
+
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::emojis::Version;
+
+mod private {
+    use std::fmt;
+    use std::io;
+
+    pub trait Sealed {
+        /// Returns the byte stream to write to, unless the destination only takes text.
+        fn byte_stream(&mut self) -> Option<&mut dyn io::Write>;
+
+        /// Writes the text to a destination which only takes text.
+        fn write_text(&mut self, s: &str) -> io::Result<()>;
+    }
+
+    pub fn fmt_error(e: fmt::Error) -> io::Error {
+        io::Error::other(e)
+    }
+}
+
+use self::private::{fmt_error, Sealed};
+
+/// A destination of encoded or decoded output, either a byte stream or text.
+///
+/// Implemented for `Vec<u8>`, `String`, `fmt::Formatter` and the trait objects of `io::Write` and
+/// `fmt::Write`; any other writer can be passed as one of the latter. This trait is sealed, it
+/// can't be implemented outside of this crate.
+///
+/// Text destinations receive the output at once, after the whole source is processed, because
+/// parts of the output are not necessarily valid UTF-8 on their own.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt;
+///
+/// struct Encoded<'a>(&'a [u8]);
+///
+/// impl fmt::Display for Encoded<'_> {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         ecoji::VERSION1
+///             .encode_to_sink(&mut &self.0[..], f)
+///             .map(|_| ())
+///             .map_err(|_| fmt::Error)
+///     }
+/// }
+///
+/// assert_eq!(Encoded(b"abc").to_string(), "👖📸🎈☕");
+/// ```
+pub trait Sink: Sealed {}
+
+impl Sealed for Vec<u8> {
+    fn byte_stream(&mut self) -> Option<&mut dyn io::Write> {
+        Some(self)
+    }
+
+    fn write_text(&mut self, s: &str) -> io::Result<()> {
+        self.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl Sink for Vec<u8> {}
+
+impl Sealed for String {
+    fn byte_stream(&mut self) -> Option<&mut dyn io::Write> {
+        None
+    }
+
+    fn write_text(&mut self, s: &str) -> io::Result<()> {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl Sink for String {}
+
+impl Sealed for fmt::Formatter<'_> {
+    fn byte_stream(&mut self) -> Option<&mut dyn io::Write> {
+        None
+    }
+
+    fn write_text(&mut self, s: &str) -> io::Result<()> {
+        self.write_str(s).map_err(fmt_error)
+    }
+}
+
+impl Sink for fmt::Formatter<'_> {}
+
+impl Sealed for dyn io::Write + '_ {
+    fn byte_stream(&mut self) -> Option<&mut dyn io::Write> {
+        Some(self)
+    }
+
+    fn write_text(&mut self, s: &str) -> io::Result<()> {
+        self.write_all(s.as_bytes())
+    }
+}
+
+impl Sink for dyn io::Write + '_ {}
+
+impl Sealed for dyn fmt::Write + '_ {
+    fn byte_stream(&mut self) -> Option<&mut dyn io::Write> {
+        None
+    }
+
+    fn write_text(&mut self, s: &str) -> io::Result<()> {
+        self.write_str(s).map_err(fmt_error)
+    }
+}
+
+impl Sink for dyn fmt::Write + '_ {}
+
+impl Version {
+    /// Encodes the entire source into the Ecoji format like [`encode`](#method.encode) does, and
+    /// writes the encoded symbols to a destination of any kind.
+    ///
+    /// If successful, returns the number of bytes of the UTF-8 representation of the symbols.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let mut text = String::new();
+    /// ecoji::VERSION1.encode_to_sink(&mut "abc".as_bytes(), &mut text)?;
+    /// assert_eq!(text, "👖📸🎈☕");
+    ///
+    /// let mut bytes = Vec::new();
+    /// let writer: &mut dyn io::Write = &mut bytes;
+    /// ecoji::VERSION1.encode_to_sink(&mut "abc".as_bytes(), writer)?;
+    /// assert_eq!(bytes, text.as_bytes());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn encode_to_sink<R: Read + ?Sized, S: Sink + ?Sized>(
+        &self,
+        source: &mut R,
+        sink: &mut S,
+    ) -> io::Result<usize> {
+        match sink.byte_stream() {
+            Some(destination) => self.encode(source, destination),
+            None => {
+                let text = self.encode_to_string(source)?;
+                sink.write_text(&text)?;
+                Ok(text.len())
+            }
+        }
+    }
+
+    /// Decodes the entire source from the Ecoji format like [`decode`](#method.decode) does, and
+    /// writes the decoded data to a destination of any kind.
+    ///
+    /// If successful, returns the number of bytes of decoded data. Besides the failure conditions
+    /// of `decode`, decoding to text fails with `io::ErrorKind::InvalidData` if the decoded data
+    /// is not valid UTF-8, in which case nothing is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let mut text = String::from("decoded: ");
+    /// let writer: &mut dyn Write = &mut text;
+    /// ecoji::VERSION1.decode_to_sink(&mut "👖📸🎈☕".as_bytes(), writer)?;
+    /// assert_eq!(text, "decoded: abc");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn decode_to_sink<R: Read + ?Sized, S: Sink + ?Sized>(
+        &self,
+        source: &mut R,
+        sink: &mut S,
+    ) -> io::Result<usize> {
+        match sink.byte_stream() {
+            Some(destination) => self.decode(source, destination),
+            None => {
+                let text = self.decode_to_string(source)?;
+                sink.write_text(&text)?;
+                Ok(text.len())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::io;
+
+    use crate::emojis::VERSIONS;
+
+    quickcheck! {
+        fn sinks_round_trip(input: String) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_bytes()).unwrap();
+
+                let mut text = String::new();
+                let mut bytes = Vec::new();
+                let mut formatted = String::new();
+                v.encode_to_sink(&mut input.as_bytes(), &mut text).unwrap();
+                v.encode_to_sink(&mut input.as_bytes(), &mut bytes as &mut dyn io::Write)
+                    .unwrap();
+                v.encode_to_sink(&mut input.as_bytes(), &mut formatted as &mut dyn fmt::Write)
+                    .unwrap();
+
+                let mut decoded = String::new();
+                let n = v.decode_to_sink(&mut encoded.as_bytes(), &mut decoded).unwrap();
+
+                text == encoded
+                    && bytes == encoded.as_bytes()
+                    && formatted == encoded
+                    && decoded == input
+                    && n == input.len()
+            })
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_text() {
+        let encoded = crate::VERSION1.encode_to_string(&mut &b"\xff"[..]).unwrap();
+        let mut text = String::new();
+        let e = crate::VERSION1
+            .decode_to_sink(&mut encoded.as_bytes(), &mut text)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(text.is_empty());
+
+        let mut bytes = Vec::new();
+        crate::VERSION1
+            .decode_to_sink(&mut encoded.as_bytes(), &mut bytes)
+            .unwrap();
+        assert_eq!(bytes, b"\xff");
+    }
+}
@@ -0,0 +1,179 @@
+//! Encoding and decoding into buffers provided by the caller.
+
+use std::io::{self, Write};
+
+use crate::decode::Settings;
+use crate::emojis::Version;
+use crate::error::{DecodeError, DecodeErrorKind, EncodeError};
+
+/// A writer filling a slice, which counts the bytes which do not fit instead of failing.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    written: usize,
+}
+
+impl<'a> Write for SliceWriter<'a> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if let Some(space) = self.buf.get_mut(self.written..) {
+            let n = data.len().min(space.len());
+            space[..n].copy_from_slice(&data[..n]);
+        }
+        self.written += data.len();
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Version {
+    /// Returns the exact number of bytes of the UTF-8 text which
+    /// [`encode`](#method.encode) produces for the input. The symbols differ in length, so this
+    /// depends on the data, not only on its length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(ecoji::VERSION1.encoded_len(b"abc"), 15);
+    /// assert_eq!(ecoji::VERSION2.encoded_len(b"abc"), 15);
+    /// assert_eq!(ecoji::VERSION2.encoded_len(b"a"), 7);
+    /// ```
+    pub fn encoded_len(&self, input: &[u8]) -> usize {
+        input
+            .chunks(5)
+            .map(|chunk| {
+                self.encode_chunk(chunk, &mut io::sink())
+                    .expect("Writing to a sink failed")
+            })
+            .sum()
+    }
+
+    /// Encodes the input like [`encode`](#method.encode), writing the UTF-8 text to the start of
+    /// the output buffer without allocating, e.g. to a buffer on the stack. Use
+    /// [`encoded_len`](#method.encoded_len) to size the buffer exactly.
+    ///
+    /// If successful, returns the number of bytes written, which the caller may convert with
+    /// `std::str::from_utf8`. If the text does not fit, returns an error with the size needed, in
+    /// which case the contents of the buffer are unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut buf = [0; 64];
+    /// let n = ecoji::VERSION1.encode_into(b"input data", &mut buf).unwrap();
+    /// assert_eq!(std::str::from_utf8(&buf[..n]).unwrap(), "👶😲🇲👅🍉🔙🌥🌩");
+    ///
+    /// let e = ecoji::VERSION1.encode_into(b"input data", &mut buf[..16]).unwrap_err();
+    /// assert_eq!(e.needed(), n);
+    /// ```
+    pub fn encode_into(&self, input: &[u8], out: &mut [u8]) -> Result<usize, EncodeError> {
+        let capacity = out.len();
+        let mut writer = SliceWriter {
+            buf: out,
+            written: 0,
+        };
+        for chunk in input.chunks(5) {
+            self.encode_chunk(chunk, &mut writer)
+                .expect("Writing to a slice failed");
+        }
+
+        if writer.written > capacity {
+            return Err(EncodeError::new(writer.written));
+        }
+        Ok(writer.written)
+    }
+
+    /// Decodes the input like [`decode`](#method.decode), writing the data to the start of the
+    /// output buffer without allocating, e.g. for callers which manage their own memory.
+    ///
+    /// If successful, returns the number of bytes written. If the data does not fit, returns an
+    /// error of the [`DecodeErrorKind::BufferTooSmall`](enum.DecodeErrorKind.html#variant.BufferTooSmall)
+    /// kind with the exact size needed, in which case the contents of the buffer are unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ecoji::DecodeErrorKind;
+    ///
+    /// let mut buf = [0; 16];
+    /// let n = ecoji::VERSION1.decode_into("👶😲🇲👅🍉🔙🌥🌩", &mut buf).unwrap();
+    /// assert_eq!(&buf[..n], b"input data");
+    ///
+    /// let e = ecoji::VERSION1.decode_into("👶😲🇲👅🍉🔙🌥🌩", &mut buf[..8]).unwrap_err();
+    /// assert_eq!(e.kind(), &DecodeErrorKind::BufferTooSmall { needed: 10 });
+    /// ```
+    pub fn decode_into(&self, input: &str, out: &mut [u8]) -> Result<usize, DecodeError> {
+        let capacity = out.len();
+        let mut writer = SliceWriter {
+            buf: out,
+            written: 0,
+        };
+        let mut decoder = self;
+        if let Err(e) = self.decode_from(
+            &mut decoder,
+            Settings::default(),
+            &mut input.as_bytes(),
+            &mut writer,
+        ) {
+            // neither the input nor the output can fail on their own
+            let e = DecodeError::from_io(&e).expect("Reading a slice failed");
+            return Err(e.clone());
+        }
+
+        if writer.written > capacity {
+            let kind = DecodeErrorKind::BufferTooSmall {
+                needed: writer.written,
+            };
+            return Err(DecodeError::new(kind, input.len(), input.chars().count()));
+        }
+        Ok(writer.written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::VERSIONS;
+    use crate::error::DecodeErrorKind;
+
+    quickcheck! {
+        fn encode_into_is_exact(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let mut buf = vec![0; v.encoded_len(&input)];
+                let fits = v.encode_into(&input, &mut buf) == Ok(encoded.len())
+                    && buf == encoded.as_bytes();
+                let too_small = input.is_empty()
+                    || v.encode_into(&input, &mut buf[..encoded.len() - 1])
+                        .unwrap_err()
+                        .needed()
+                        == encoded.len();
+                fits && too_small
+            })
+        }
+
+        fn decode_into_is_exact(input: Vec<u8>, spare: u8) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let mut buf = vec![0; input.len() + spare as usize % 8];
+                let fits = v.decode_into(&encoded, &mut buf) == Ok(input.len())
+                    && buf[..input.len()] == input[..];
+                let too_small = input.is_empty()
+                    || v.decode_into(&encoded, &mut buf[..input.len() - 1])
+                        .unwrap_err()
+                        .kind()
+                        == &DecodeErrorKind::BufferTooSmall { needed: input.len() };
+                fits && too_small
+            })
+        }
+    }
+
+    #[test]
+    fn test_decode_into_error() {
+        let e = crate::VERSION1
+            .decode_into("👖📸🎈x", &mut [0; 8])
+            .unwrap_err();
+        assert_eq!(e.kind(), &DecodeErrorKind::InvalidChar('x'));
+        assert_eq!(e.position(), 3);
+    }
+}
@@ -0,0 +1,726 @@
+//! Streaming adapters performing the encoding and the decoding on the fly.
+
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+use crate::chars::{Chars, CharsError};
+use crate::decode::{is_line_break, Progress, Settings, State};
+use crate::emojis::{Version, VERSIONS};
+
+/// The number of bytes the decoding reader decodes ahead, a multiple of the chunk size.
+const DECODER_BUFFER_SIZE: usize = 4000;
+
+/// A writer which encodes all data written to it into the Ecoji format and writes the encoded
+/// data to the underlying writer.
+///
+/// Data is encoded in chunks of 5 bytes; an incomplete chunk is kept in the buffer until more
+/// data arrives or until the writer is finished. Since an incomplete chunk can only be encoded
+/// at the very end of the data, [`flush`](#method.flush) does not write it; call
+/// [`finish`](#method.finish) once all data is written. If the writer is dropped without being
+/// finished, the remaining chunk is written on a best-effort basis, ignoring errors.
+///
+/// Each chunk is written to the underlying writer with `Write::write_all`, so short writes are
+/// continued. If writing fails, the call fails, and an unknown part of the encoded data given to
+/// it may have been written already, so the writer should not be used any further; a
+/// [`Codec`](struct.Codec.html) configured with
+/// [`Builder::short_writes`](struct.Builder.html#method.short_writes) reports exactly how much
+/// output has been written.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut writer = ecoji::EncoderWriter::new(&ecoji::VERSION1, Vec::new());
+/// writer.write_all(b"input ")?;
+/// writer.write_all(b"data")?;
+/// let output = writer.finish()?;
+///
+/// assert_eq!(output, "👶😲🇲👅🍉🔙🌥🌩".as_bytes());
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct EncoderWriter<'v, W: Write> {
+    version: &'v Version,
+    inner: Option<W>,
+    buf: [u8; 5],
+    len: usize,
+}
+
+impl<'v, W: Write> EncoderWriter<'v, W> {
+    /// Creates a new writer encoding data with the given version and writing the encoded data
+    /// to `inner`.
+    pub fn new(version: &'v Version, inner: W) -> EncoderWriter<'v, W> {
+        EncoderWriter {
+            version,
+            inner: Some(inner),
+            buf: [0; 5],
+            len: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Encodes the incomplete chunk remaining in the buffer, if any, and returns the underlying
+    /// writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_remaining()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn write_remaining(&mut self) -> io::Result<()> {
+        if self.len > 0 {
+            let len = self.len;
+            self.len = 0;
+            let inner = self.inner.as_mut().unwrap();
+            self.version.encode_chunk(&self.buf[..len], inner)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'v, W: Read + Write + Seek> EncoderWriter<'v, W> {
+    /// Moves the underlying writer to the end of the encoding of the first `n` bytes of data, so
+    /// that writing the data from offset `n` on continues the encoded output, e.g. to resume an
+    /// upload or a copy which was interrupted. Returns the new position of the underlying writer.
+    ///
+    /// The encoding of a chunk depends only on its own 5 bytes, so `n` must be a multiple of 5.
+    /// Since the symbols don't all take the same number of bytes, the encoded output up to the
+    /// position is read back from the underlying writer, skipping line breaks. An incomplete
+    /// chunk kept in the buffer is discarded.
+    ///
+    /// Whatever follows the position in the underlying writer is overwritten by the data written
+    /// next, but not removed; e.g. truncate a file to the returned position with
+    /// `File::set_len`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if `n` is not a multiple of 5, with
+    /// `io::ErrorKind::UnexpectedEof` if the encoded output is shorter, and with
+    /// `io::ErrorKind::InvalidData` if it is not UTF-8 or ends with padding before the position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Cursor, Write};
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// // The first attempt got as far as the first chunk and a half
+    /// let mut output = Cursor::new(Vec::new());
+    /// output.write_all("👶😲🇲👅🍉".as_bytes())?;
+    ///
+    /// let mut writer = ecoji::EncoderWriter::new(&ecoji::VERSION1, output);
+    /// writer.seek_to_input_offset(5)?;
+    /// writer.write_all(b" data")?;
+    /// let output = writer.finish()?.into_inner();
+    ///
+    /// assert_eq!(output, "👶😲🇲👅🍉🔙🌥🌩".as_bytes());
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn seek_to_input_offset(&mut self, n: u64) -> io::Result<u64> {
+        if !n.is_multiple_of(5) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Input offset is not a multiple of 5",
+            ));
+        }
+        self.len = 0;
+
+        let inner = self.inner.as_mut().unwrap();
+        inner.seek(SeekFrom::Start(0))?;
+        let mut chars = Chars::new(BufReader::new(&mut *inner));
+        let mut symbols = 0;
+        while symbols < n / 5 * 4 {
+            match chars.next() {
+                Some(Ok(c)) if is_line_break(c) => {}
+                Some(Ok(c)) if self.version.is_padding(c) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Encoded output ends before the input offset",
+                    ))
+                }
+                Some(Ok(_)) => symbols += 1,
+                Some(Err(CharsError::NotUtf8)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        CharsError::NotUtf8,
+                    ))
+                }
+                Some(Err(CharsError::Other(e))) => return Err(e),
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            }
+        }
+        let position = chars.bytes_read() as u64;
+        drop(chars);
+        inner.seek(SeekFrom::Start(position))
+    }
+}
+
+impl<'v, W: Write> Write for EncoderWriter<'v, W> {
+    fn write(&mut self, mut data: &[u8]) -> io::Result<usize> {
+        let written = data.len();
+        let inner = self.inner.as_mut().unwrap();
+
+        if self.len > 0 {
+            let n = data.len().min(5 - self.len);
+            self.buf[self.len..self.len + n].copy_from_slice(&data[..n]);
+            self.len += n;
+            data = &data[n..];
+            if self.len < 5 {
+                return Ok(written);
+            }
+            self.len = 0;
+            self.version.encode_chunk(&self.buf, inner)?;
+        }
+
+        let mut chunks = data.chunks_exact(5);
+        for chunk in &mut chunks {
+            self.version.encode_chunk(chunk, inner)?;
+        }
+        let rest = chunks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<'v, W: Write> Drop for EncoderWriter<'v, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.write_remaining();
+        }
+    }
+}
+
+/// A reader which decodes the Ecoji text read from the underlying reader, starting with the given
+/// version like [`Version::decode`](emojis/struct.Version.html#method.decode) does.
+///
+/// The decoded data is buffered, and exposed through the `BufRead` implementation, so that
+/// `read_line`, `lines` and the like work on the decoded data directly. A decoding error is
+/// returned once the data decoded before it has been consumed; offsets in the error are relative
+/// to the start of the underlying reader.
+///
+/// The underlying reader is read a few bytes at a time, so it should be buffered, e.g. with
+/// `std::io::BufReader`, unless it is in memory.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::BufRead;
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let encoded = ecoji::encode_bytes("first\nsecond\n");
+/// let reader = ecoji::DecoderReader::new(&ecoji::VERSION1, encoded.as_bytes());
+///
+/// let lines = reader.lines().collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(lines, ["first", "second"]);
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DecoderReader<'v, R: Read> {
+    version: &'v Version,
+    decoder: &'v Version,
+    settings: Settings,
+    state: State,
+    input: Chars<R>,
+    buf: Vec<u8>,
+    pos: usize,
+    error: Option<io::Error>,
+    eof: bool,
+    /// Number of decoded bytes consumed so far.
+    consumed: usize,
+}
+
+impl<'v, R: Read> DecoderReader<'v, R> {
+    /// Creates a new reader decoding the text read from `inner` starting with the given version.
+    pub fn new(version: &'v Version, inner: R) -> DecoderReader<'v, R> {
+        DecoderReader::with_settings(version, inner, Settings::default())
+    }
+
+    /// Creates a new reader decoding with the given settings, e.g. those of a
+    /// [`Codec`](struct.Codec.html).
+    pub(crate) fn with_settings(
+        version: &'v Version,
+        inner: R,
+        settings: Settings,
+    ) -> DecoderReader<'v, R> {
+        DecoderReader {
+            version,
+            decoder: version,
+            settings,
+            state: State::default(),
+            input: Chars::new(inner),
+            buf: Vec::with_capacity(DECODER_BUFFER_SIZE),
+            pos: 0,
+            error: None,
+            eof: false,
+            consumed: 0,
+        }
+    }
+
+    /// Records where the decoding stands, so that it can be continued later, even by another
+    /// process, with [`resume`](#method.resume). Returns `None` if the reader has run into a
+    /// decoding error whose data has not been consumed yet, as there's nothing to continue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let encoded = ecoji::encode_bytes("input data");
+    ///
+    /// let mut reader = ecoji::DecoderReader::new(&ecoji::VERSION1, encoded.as_bytes());
+    /// let mut first = [0; 3];
+    /// reader.read_exact(&mut first)?;
+    /// let checkpoint = reader.checkpoint().unwrap();
+    /// drop(reader);
+    ///
+    /// let rest = &encoded.as_bytes()[checkpoint.input_offset()..];
+    /// let mut reader = ecoji::DecoderReader::resume(checkpoint, rest);
+    /// let mut second = Vec::new();
+    /// reader.read_to_end(&mut second)?;
+    ///
+    /// assert_eq!(first, *b"inp");
+    /// assert_eq!(second, b"ut data");
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn checkpoint(&self) -> Option<DecoderCheckpoint> {
+        if self.error.is_some() {
+            return None;
+        }
+        Some(DecoderCheckpoint {
+            initial_version: VERSIONS[self.version.VERSION_NUMBER - 1],
+            version: VERSIONS[self.decoder.VERSION_NUMBER - 1],
+            input_offset: self.input.bytes_read(),
+            input_position: self.input.chars_read(),
+            output_offset: self.consumed,
+            pending: self.buf[self.pos..].to_vec(),
+            settings: self.settings,
+            progress: self.state.progress(),
+        })
+    }
+
+    /// Creates a reader continuing the decoding recorded in the checkpoint, reading the rest of
+    /// the text from `inner`, which must start at the
+    /// [`input_offset`](struct.DecoderCheckpoint.html#method.input_offset) of the checkpoint.
+    /// The data which was decoded, but not consumed, is read first; offsets in errors are
+    /// relative to the start of the whole text. The resumed reader keeps the settings of the
+    /// reader which recorded the checkpoint, and carries on with the checks of strict decoding,
+    /// e.g. that nothing follows a padded chunk.
+    pub fn resume(checkpoint: DecoderCheckpoint, inner: R) -> DecoderReader<'v, R> {
+        let mut buf = checkpoint.pending;
+        buf.reserve(DECODER_BUFFER_SIZE.saturating_sub(buf.len()));
+        DecoderReader {
+            version: checkpoint.initial_version,
+            decoder: checkpoint.version,
+            settings: checkpoint.settings,
+            state: State::resumed(checkpoint.progress),
+            input: Chars::resumed(inner, checkpoint.input_offset, checkpoint.input_position),
+            buf,
+            pos: 0,
+            error: None,
+            eof: false,
+            consumed: checkpoint.output_offset,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.input.get_ref()
+    }
+
+    /// Returns the underlying reader. Text which has been read from it, but whose decoded data
+    /// has not been consumed, is lost.
+    pub fn into_inner(self) -> R {
+        self.input.into_inner()
+    }
+
+    /// Turns the reader into an iterator over the decoded bytes, e.g. for parsers working on
+    /// iterators. Unlike `Read::bytes`, it takes the bytes straight from the buffer, and it ends
+    /// after yielding an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let reader = ecoji::DecoderReader::new(&ecoji::VERSION1, "👶😲🇲👅🍉🔙🌥🌩".as_bytes());
+    /// let letters = reader
+    ///     .bytes_iter()
+    ///     .filter(|b| b.as_ref().map_or(true, |b| b.is_ascii_lowercase()))
+    ///     .count();
+    ///
+    /// assert_eq!(letters, 9);
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn bytes_iter(self) -> DecodedBytes<'v, R> {
+        DecodedBytes {
+            reader: self,
+            failed: false,
+        }
+    }
+}
+
+/// An iterator over the bytes decoded by a [`DecoderReader`](struct.DecoderReader.html), returned
+/// by [`DecoderReader::bytes_iter`](struct.DecoderReader.html#method.bytes_iter).
+#[derive(Debug)]
+pub struct DecodedBytes<'v, R: Read> {
+    reader: DecoderReader<'v, R>,
+    failed: bool,
+}
+
+impl<'v, R: Read> Iterator for DecodedBytes<'v, R> {
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        if self.failed {
+            return None;
+        }
+        let byte = match self.reader.fill_buf() {
+            Ok(buf) => *buf.first()?,
+            Err(e) => {
+                self.failed = true;
+                return Some(Err(e));
+            }
+        };
+        self.reader.consume(1);
+        Some(Ok(byte))
+    }
+}
+
+impl<'v, R: Read> Read for DecoderReader<'v, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = {
+            let available = self.fill_buf()?;
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        };
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<'v, R: Read> BufRead for DecoderReader<'v, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.buf.len() {
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            self.buf.clear();
+            self.pos = 0;
+            while !self.eof && self.buf.len() < DECODER_BUFFER_SIZE {
+                let chunk = self.version.next_chunk(
+                    &mut self.decoder,
+                    self.settings,
+                    &mut self.state,
+                    &mut self.input,
+                );
+                match chunk {
+                    Ok(Some((out, len))) => self.buf.extend_from_slice(&out[..len]),
+                    Ok(None) => self.eof = true,
+                    // keep the error until the data decoded before it is consumed
+                    Err(e) if !self.buf.is_empty() => {
+                        self.error = Some(e);
+                        break;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        let pos = (self.pos + amount).min(self.buf.len());
+        self.consumed += pos - self.pos;
+        self.pos = pos;
+    }
+}
+
+/// Where the decoding of a [`DecoderReader`](struct.DecoderReader.html) stands, recorded by
+/// [`DecoderReader::checkpoint`](struct.DecoderReader.html#method.checkpoint) to continue it
+/// later with [`DecoderReader::resume`](struct.DecoderReader.html#method.resume).
+///
+/// With the `serde` feature, checkpoints can be serialized, e.g. to be stored along with the
+/// output of a long-running batch job, so that it survives a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecoderCheckpoint {
+    initial_version: &'static Version,
+    version: &'static Version,
+    input_offset: usize,
+    input_position: usize,
+    output_offset: usize,
+    pending: Vec<u8>,
+    settings: Settings,
+    progress: Progress,
+}
+
+impl DecoderCheckpoint {
+    /// Returns the offset, in bytes, of the rest of the text in the whole text.
+    pub fn input_offset(&self) -> usize {
+        self.input_offset
+    }
+
+    /// Returns the number of decoded bytes consumed from the reader before the checkpoint, i.e.
+    /// the offset in the decoded data at which the resumed reader continues.
+    pub fn output_offset(&self) -> usize {
+        self.output_offset
+    }
+
+    /// Returns the data which was decoded, but not consumed yet, which the resumed reader yields
+    /// before decoding the rest of the text.
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Returns the version the rest of the text is decoded with, which differs from the version
+    /// the reader was created with once it has switched to the other version.
+    pub fn version(&self) -> &'static Version {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emojis::VERSIONS;
+    use crate::error::{DecodeError, DecodeErrorKind};
+
+    quickcheck! {
+        fn encoder_writer_is_the_same_as_encode(input: Vec<u8>, pieces: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let mut writer = EncoderWriter::new(v, Vec::new());
+                let mut rest = input.as_slice();
+                for n in &pieces {
+                    let n = (*n as usize % 8).min(rest.len());
+                    writer.write_all(&rest[..n]).unwrap();
+                    rest = &rest[n..];
+                }
+                writer.write_all(rest).unwrap();
+                let output = writer.finish().unwrap();
+                output == v.encode_to_string(&mut input.as_slice()).unwrap().into_bytes()
+            })
+        }
+
+        fn decoder_reader_is_the_same_as_decode(input: Vec<u8>, size: u16) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let mut reader = DecoderReader::new(v, encoded.as_bytes());
+                let mut buf = vec![0; size as usize % 16 + 1];
+                let mut output = Vec::new();
+                loop {
+                    match reader.read(&mut buf).unwrap() {
+                        0 => break,
+                        n => output.extend_from_slice(&buf[..n]),
+                    }
+                }
+                output == input
+            })
+        }
+
+        fn encoder_writer_resumes(input: Vec<u8>, resume: usize, written: usize) -> bool {
+            let resume = resume % (input.len() / 5 + 1) * 5;
+            let written = resume + written % (input.len() - resume + 1);
+            VERSIONS.iter().all(|v| {
+                let previous = v.encode_to_string(&mut &input[..written]).unwrap();
+                let mut writer = EncoderWriter::new(v, io::Cursor::new(previous.into_bytes()));
+                let position = writer.seek_to_input_offset(resume as u64).unwrap() as usize;
+                writer.write_all(&input[resume..]).unwrap();
+                let output = writer.finish().unwrap().into_inner();
+
+                let rest = v.encode_to_string(&mut &input[resume..]).unwrap();
+                output[..position + rest.len()] == *v.encode_to_string(&mut input.as_slice()).unwrap().as_bytes()
+            })
+        }
+    }
+
+    quickcheck! {
+        fn decoder_reader_resumes(input: Vec<u8>, first: usize, switch: bool) -> bool {
+            let first = first % (input.len() + 1);
+            VERSIONS.iter().all(|v| {
+                let (start, rest) = input.split_at(input.len() / 10 * 5);
+                let mut encoded = v.encode_to_string(&mut &start[..]).unwrap();
+                let other = if switch { v.other_version() } else { v };
+                encoded += &other.encode_to_string(&mut &rest[..]).unwrap();
+
+                let mut reader = DecoderReader::new(v, encoded.as_bytes());
+                let mut output = vec![0; first];
+                reader.read_exact(&mut output).unwrap();
+                let checkpoint = reader.checkpoint().unwrap();
+                let mut reader = DecoderReader::resume(
+                    checkpoint.clone(),
+                    &encoded.as_bytes()[checkpoint.input_offset()..],
+                );
+                reader.read_to_end(&mut output).unwrap();
+                checkpoint.output_offset() == first && output == input
+            })
+        }
+    }
+
+    #[test]
+    fn test_resumed_truncation() {
+        use crate::error::{DecodeError, DecodeErrorKind};
+
+        // long enough not to be decoded ahead to the end before the checkpoint
+        let data: Vec<u8> = (0..=255).cycle().take(10000).collect();
+        // a chunk padded mid-stream decodes into less than 5 bytes
+        for (prefix, chunks) in [(&b""[..], 1999), (&b"abc"[..], 2000)] {
+            let v = &crate::VERSION1;
+            let encoded = v.encode_to_string(&mut &prefix[..]).unwrap()
+                + &v.encode_to_string(&mut data.as_slice()).unwrap();
+            // cut off the last 2 symbols
+            let (cut, _) = encoded.char_indices().nth_back(1).unwrap();
+            let truncated = &encoded[..cut];
+
+            let mut reader = DecoderReader::new(v, truncated.as_bytes());
+            let mut output = vec![0; 3];
+            reader.read_exact(&mut output).unwrap();
+            let checkpoint = reader.checkpoint().unwrap();
+            let rest = &truncated.as_bytes()[checkpoint.input_offset()..];
+            let e = DecoderReader::resume(checkpoint, rest)
+                .read_to_end(&mut output)
+                .unwrap_err();
+            assert_eq!(
+                DecodeError::from_io(&e).map(DecodeError::kind),
+                Some(&DecodeErrorKind::UnexpectedEof { symbols: 2, chunks })
+            );
+            assert_eq!(output, [prefix, &data[..9995]].concat());
+        }
+    }
+
+    #[test]
+    fn test_resumed_strict_checks() {
+        use crate::error::Deviation;
+
+        let strict = Settings {
+            strict: true,
+            ..Settings::default()
+        };
+        let resume = |start: &str, rest: &str| {
+            let mut reader =
+                DecoderReader::with_settings(&crate::VERSION1, start.as_bytes(), strict);
+            reader.read_to_end(&mut Vec::new()).unwrap();
+            let checkpoint = reader.checkpoint().unwrap();
+            let e = DecoderReader::resume(checkpoint, rest.as_bytes())
+                .read_to_end(&mut Vec::new())
+                .unwrap_err();
+            DecodeError::from_io(&e).unwrap().kind().clone()
+        };
+        let deviation = |d| DecodeErrorKind::NotCanonical(d);
+
+        // nothing may follow a padded chunk, even if it comes after the checkpoint
+        let padded = crate::VERSION1.encode_to_string(&mut &b"abc"[..]).unwrap();
+        let full = crate::VERSION1
+            .encode_to_string(&mut &b"abcde"[..])
+            .unwrap();
+        assert_eq!(resume(&padded, &full), deviation(Deviation::TrailingData));
+
+        // the versions may not be mixed across the checkpoint either
+        let exclusive = |v: &'static Version| {
+            (0..=255u8)
+                .map(|b| v.encode_to_string(&mut &[b; 5][..]).unwrap())
+                .find(|e| {
+                    e.chars()
+                        .any(|c| !v.other_version().is_valid_alphabet_char(c))
+                })
+                .unwrap()
+        };
+        assert_eq!(
+            resume(&exclusive(&crate::VERSION1), &exclusive(&crate::VERSION2)),
+            deviation(Deviation::MixedVersions)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_checkpoint_serde() {
+        let encoded = crate::VERSION2
+            .encode_to_string(&mut &b"abcdefghij"[..])
+            .unwrap();
+        let mut reader = DecoderReader::new(&crate::VERSION1, encoded.as_bytes());
+        let mut first = [0; 2];
+        reader.read_exact(&mut first).unwrap();
+        let checkpoint = reader.checkpoint().unwrap();
+        assert_eq!(checkpoint.version(), &crate::VERSION2);
+
+        let json = serde_json::to_string(&checkpoint).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"initial_version":1,"version":2,"input_offset":{},"input_position":8,"output_offset":2,"pending":[99,100,101,102,103,104,105,106],"settings":{{"strict":false,"locked":false,"line_breaks":false,"ignore_whitespace":false}},"progress":{{"switched":false,"exclusive":false,"finished":false,"chunks":2}}}}"#,
+                encoded.len()
+            )
+        );
+        assert_eq!(
+            serde_json::from_str::<DecoderCheckpoint>(&json).unwrap(),
+            checkpoint
+        );
+    }
+
+    #[test]
+    fn test_seek_to_input_offset_error() {
+        let v = &crate::VERSION1;
+        let kind = |encoded: String, n| {
+            let mut writer = EncoderWriter::new(v, io::Cursor::new(encoded.into_bytes()));
+            writer.seek_to_input_offset(n).unwrap_err().kind()
+        };
+        let encode = |input: &[u8]| v.encode_to_string(&mut &input[..]).unwrap();
+
+        assert_eq!(kind(encode(b"abcdefghij"), 3), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            kind(encode(b"abcdefghij"), 15),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(kind(encode(b"abcdefg"), 10), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_bytes_iter() {
+        let mut encoded = crate::VERSION2
+            .encode_to_string(&mut &b"abcdefghij"[..])
+            .unwrap();
+        encoded.push('x');
+        let reader = DecoderReader::new(&crate::VERSION2, encoded.as_bytes());
+        let bytes: Vec<_> = reader.bytes_iter().collect();
+        assert_eq!(bytes.len(), 11);
+        let data: Vec<u8> = bytes[..10].iter().map(|b| *b.as_ref().unwrap()).collect();
+        assert_eq!(data, b"abcdefghij");
+        assert!(bytes[10].is_err());
+    }
+
+    #[test]
+    fn test_decoder_reader_error() {
+        let mut encoded = crate::VERSION1
+            .encode_to_string(&mut &[7; 5000][..])
+            .unwrap();
+        encoded.push('x');
+        let mut reader = DecoderReader::new(&crate::VERSION1, encoded.as_bytes());
+
+        let mut output = Vec::new();
+        let e = reader.read_to_end(&mut output).unwrap_err();
+        assert_eq!(output, [7; 5000]);
+        let e = DecodeError::from_io(&e).unwrap();
+        assert_eq!(e.kind(), &DecodeErrorKind::InvalidChar('x'));
+        assert_eq!(e.position(), 4000);
+    }
+}
@@ -0,0 +1,123 @@
+//! The alphabets and padding symbols of both versions of Ecoji, as plain constants.
+//!
+//! These are meant for tools which only need to recognize or list the symbols, like syntax
+//! highlighters, input method plugins and validators, without using the encoder and decoder.
+//! [`Version`](../emojis/struct.Version.html) gives access to the same tables along with the
+//! operations on them.
+//!
+//! # Examples
+//!
+//! ```
+//! use ecoji::tables;
+//!
+//! let is_symbol = |c| {
+//!     tables::V1_ALPHABET.contains(&c)
+//!         || tables::V2_ALPHABET.contains(&c)
+//!         || tables::V1_PADDING.contains(&c)
+//!         || tables::V2_PADDING.contains(&c)
+//! };
+//! assert!("👖📸🎈☕".chars().all(is_symbol));
+//! assert!(!is_symbol('x'));
+//! ```
+
+use crate::emojis::{VERSION1, VERSION2};
+
+/// The numbers of the versions of the alphabet.
+pub const VERSION_NUMBERS: [usize; 2] = [1, 2];
+
+/// The number of symbols in the alphabet of either version, not counting padding symbols.
+pub const ALPHABET_SIZE: usize = 1024;
+
+/// The number of bits of data each symbol, other than a padding symbol, stands for.
+pub const BITS_PER_SYMBOL: usize = 10;
+
+/// The number of bytes of data encoded in a chunk.
+pub const BYTES_PER_CHUNK: usize = 5;
+
+/// The number of symbols a chunk is encoded with.
+pub const SYMBOLS_PER_CHUNK: usize = 4;
+
+/// The alphabet of version 1, indexed by the 10-bit values the symbols stand for.
+pub const V1_ALPHABET: &[char; ALPHABET_SIZE] = &VERSION1.EMOJIS;
+
+/// The alphabet of version 2, indexed by the 10-bit values the symbols stand for.
+pub const V2_ALPHABET: &[char; ALPHABET_SIZE] = &VERSION2.EMOJIS;
+
+/// The padding symbol which fills up a final chunk of less than 4 bytes in version 1, and
+/// terminates one in version 2.
+pub const PADDING: char = '\u{2615}';
+
+/// The padding symbols of version 1 which end a final chunk of 4 bytes, indexed by the two lowest
+/// bits of the last byte.
+pub const V1_PADDING_4: [char; 4] = [
+    VERSION1.PADDING_40,
+    VERSION1.PADDING_41,
+    VERSION1.PADDING_42,
+    VERSION1.PADDING_43,
+];
+
+/// The padding symbols of version 2 which end a final chunk of 4 bytes, indexed by the two lowest
+/// bits of the last byte.
+pub const V2_PADDING_4: [char; 4] = [
+    VERSION2.PADDING_40,
+    VERSION2.PADDING_41,
+    VERSION2.PADDING_42,
+    VERSION2.PADDING_43,
+];
+
+/// All the padding symbols of version 1.
+pub const V1_PADDING: [char; 5] = [
+    PADDING,
+    V1_PADDING_4[0],
+    V1_PADDING_4[1],
+    V1_PADDING_4[2],
+    V1_PADDING_4[3],
+];
+
+/// All the padding symbols of version 2.
+pub const V2_PADDING: [char; 5] = [
+    PADDING,
+    V2_PADDING_4[0],
+    V2_PADDING_4[1],
+    V2_PADDING_4[2],
+    V2_PADDING_4[3],
+];
+
+/// The marker which prefixes text encoded with version 1 when the version is declared in the
+/// text itself, see [`Builder::version_marker`](../struct.Builder.html#method.version_marker).
+pub const V1_MARKER: char = '\u{2776}';
+
+/// The marker which prefixes text encoded with version 2 when the version is declared in the
+/// text itself.
+pub const V2_MARKER: char = '\u{2777}';
+
+/// The range of characters reserved as version markers, for version 1 up to version 10.
+pub const MARKERS: std::ops::RangeInclusive<char> = '\u{2776}'..='\u{277f}';
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_tables() {
+        let tables = [
+            (V1_ALPHABET, V1_PADDING_4, V1_PADDING),
+            (V2_ALPHABET, V2_PADDING_4, V2_PADDING),
+        ];
+        for (v, (alphabet, padding_4, padding)) in VERSIONS.iter().zip(tables) {
+            assert!(VERSION_NUMBERS.contains(&v.VERSION_NUMBER));
+            assert_eq!(v.padding(), PADDING);
+            for (i, c) in alphabet.iter().enumerate() {
+                assert_eq!(v.EMOJIS_REV.get(c), Some(&i));
+                assert!(!v.is_padding(*c));
+            }
+            for (bits, c) in padding_4.iter().enumerate() {
+                assert_eq!(v.padding_4(bits), *c);
+            }
+            assert!(padding.iter().all(|&c| v.is_padding(c)));
+            assert!(MARKERS.contains(&v.marker()));
+            assert!(!v.is_valid_alphabet_char(v.marker()));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Random test data for code which handles encoded text.
+//!
+//! The functions of this module generate random data together with its encoded text, in the
+//! variants the encoders of this library produce, and apply random mutations to encoded text, so
+//! that crates which store, transmit or validate encoded text can test how they handle both valid
+//! and damaged input. They take any random number generator of the `rand` crate, version 0.8,
+//! which is re-exported as [`rand`](rand/index.html), so that the tests can be reproduced with a
+//! seeded generator.
+//!
+//! Requires the `testing` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use ecoji::testing::{self, rand::SeedableRng};
+//!
+//! # fn test() -> ::std::io::Result<()> {
+//! let mut rng = testing::rand::rngs::StdRng::seed_from_u64(42);
+//! let sample = testing::random_sample(&mut rng, &ecoji::VERSION2);
+//! let codec = ecoji::Builder::new().ignore_line_breaks(true).build();
+//! assert_eq!(codec.decode_to_vec(&mut sample.encoded().as_bytes())?, sample.data());
+//!
+//! let damaged = testing::insert_garbage(&mut rng, sample.encoded());
+//! assert!(codec.decode_to_vec(&mut damaged.as_bytes()).is_err());
+//! #  Ok(())
+//! # }
+//! # test().unwrap();
+//! ```
+
+pub use rand;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::alphabet::AlphabetSet;
+use crate::codec::Codec;
+use crate::decode::is_line_break;
+use crate::emojis::Version;
+
+/// The maximum length of the data generated by [`random_sample`](fn.random_sample.html).
+const MAX_SAMPLE_LEN: usize = 256;
+
+/// Random data and its encoded text, returned by [`sample`](fn.sample.html) and
+/// [`random_sample`](fn.random_sample.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    data: Vec<u8>,
+    encoded: String,
+}
+
+impl Sample {
+    /// Returns the data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the encoded text of the data.
+    pub fn encoded(&self) -> &str {
+        &self.encoded
+    }
+
+    /// Returns the data and its encoded text.
+    pub fn into_parts(self) -> (Vec<u8>, String) {
+        (self.data, self.encoded)
+    }
+}
+
+/// Generates `len` random bytes and encodes them with the codec, so that the encoded text is
+/// wrapped and padded the way the codec is configured to.
+pub fn sample<R: Rng + ?Sized>(rng: &mut R, codec: &Codec, len: usize) -> Sample {
+    let mut data = vec![0; len];
+    rng.fill(&mut data[..]);
+    let encoded = codec
+        .encode_to_string(&mut data.as_slice())
+        .expect("Encoding a slice failed");
+    Sample { data, encoded }
+}
+
+/// Generates up to 256 random bytes and encodes them with the version, randomly choosing whether
+/// the encoded text is wrapped, and at which width, and whether the final chunk is
+/// [fully padded](../struct.Builder.html#method.full_padding). Wrapped text decodes only with
+/// line breaks [skipped](../struct.Builder.html#method.ignore_line_breaks).
+pub fn random_sample<R: Rng + ?Sized>(rng: &mut R, version: &'static Version) -> Sample {
+    let wrap = if rng.gen_bool(0.5) {
+        rng.gen_range(1..=80)
+    } else {
+        0
+    };
+    let codec = Codec::builder()
+        .version(version)
+        .wrap(wrap)
+        .full_padding(rng.gen_bool(0.5))
+        .build();
+    let len = rng.gen_range(0..=MAX_SAMPLE_LEN);
+    sample(rng, &codec, len)
+}
+
+/// Swaps two neighbouring symbols of the encoded text which differ, skipping any line breaks
+/// between them.
+///
+/// Returns `None` if the text has no two neighbouring symbols which differ. The mutated text
+/// may still be valid, and decode into different data.
+pub fn swap_symbols<R: Rng + ?Sized>(rng: &mut R, input: &str) -> Option<String> {
+    let mut chars: Vec<char> = input.chars().collect();
+    let symbols: Vec<usize> = (0..chars.len())
+        .filter(|&i| !is_line_break(chars[i]))
+        .collect();
+    let pairs: Vec<(usize, usize)> = symbols
+        .windows(2)
+        .map(|w| (w[0], w[1]))
+        .filter(|&(i, j)| chars[i] != chars[j])
+        .collect();
+    let &(i, j) = pairs.choose(rng)?;
+    chars.swap(i, j);
+    Some(chars.into_iter().collect())
+}
+
+/// Replaces a symbol of the encoded text with a different symbol of the alphabet of the
+/// version, like a single typo would.
+///
+/// Returns `None` if the text has no symbols. The mutated text may still be valid, and decode
+/// into different data.
+pub fn replace_symbol<R: Rng + ?Sized>(
+    rng: &mut R,
+    version: &Version,
+    input: &str,
+) -> Option<String> {
+    let mut chars: Vec<char> = input.chars().collect();
+    let symbols: Vec<usize> = (0..chars.len())
+        .filter(|&i| !is_line_break(chars[i]))
+        .collect();
+    let &i = symbols.choose(rng)?;
+    let replacement = loop {
+        let c = version.EMOJIS[rng.gen_range(0..version.EMOJIS.len())];
+        if c != chars[i] {
+            break c;
+        }
+    };
+    chars[i] = replacement;
+    Some(chars.into_iter().collect())
+}
+
+/// Inserts a random character which is neither a symbol of either version nor whitespace at a
+/// random position of the encoded text.
+///
+/// The mutated text never decodes, unless the decoder is configured to ignore garbage.
+pub fn insert_garbage<R: Rng + ?Sized>(rng: &mut R, input: &str) -> String {
+    let garbage = loop {
+        // mostly ASCII, like stray text, otherwise any character
+        let c = if rng.gen_bool(0.5) {
+            rng.gen_range(' '..='~')
+        } else {
+            rng.gen::<char>()
+        };
+        if !c.is_whitespace() && !AlphabetSet::all().contains(c) {
+            break c;
+        }
+    };
+    let boundaries: Vec<usize> = input
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([input.len()])
+        .collect();
+    let &at = boundaries.choose(rng).expect("No position to insert at");
+    let mut output = String::with_capacity(input.len() + garbage.len_utf8());
+    output.push_str(&input[..at]);
+    output.push(garbage);
+    output.push_str(&input[at..]);
+    output
+}
+
+/// Applies one of the mutations of this module, chosen at random, to the encoded text.
+///
+/// Returns the text with garbage inserted if the chosen mutation is not possible, so the result
+/// always differs from the input.
+pub fn mutate<R: Rng + ?Sized>(rng: &mut R, version: &Version, input: &str) -> String {
+    let mutated = match rng.gen_range(0..3) {
+        0 => swap_symbols(rng, input),
+        1 => replace_symbol(rng, version, input),
+        _ => None,
+    };
+    mutated.unwrap_or_else(|| insert_garbage(rng, input))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_samples() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for &v in VERSIONS.iter() {
+            let codec = Codec::builder().version(v).ignore_line_breaks(true).build();
+            for _ in 0..200 {
+                let sample = random_sample(&mut rng, v);
+                assert!(sample.data().len() <= MAX_SAMPLE_LEN);
+                assert_eq!(
+                    codec
+                        .decode_to_vec(&mut sample.encoded().as_bytes())
+                        .unwrap(),
+                    sample.data()
+                );
+            }
+        }
+
+        let codec = Codec::builder().wrap(3).build();
+        let (data, encoded) = sample(&mut rng, &codec, 10).into_parts();
+        assert_eq!(data.len(), 10);
+        assert_eq!(encoded.lines().map(|l| l.chars().count()).max(), Some(3));
+    }
+
+    #[test]
+    fn test_impossible_mutations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(swap_symbols(&mut rng, ""), None);
+        assert_eq!(swap_symbols(&mut rng, "🀄\n🀄🀄"), None);
+        assert_eq!(replace_symbol(&mut rng, &crate::VERSION1, "\n"), None);
+        assert_eq!(insert_garbage(&mut rng, "").chars().count(), 1);
+    }
+
+    quickcheck! {
+        fn mutations_change_text(seed: u64) -> bool {
+            let mut rng = StdRng::seed_from_u64(seed);
+            VERSIONS.iter().all(|&v| {
+                let sample = random_sample(&mut rng, v);
+                let input = sample.encoded();
+                let garbage = insert_garbage(&mut rng, input);
+                let swapped = swap_symbols(&mut rng, input);
+                let replaced = replace_symbol(&mut rng, v, input);
+                let codec = Codec::builder().version(v).ignore_line_breaks(true).build();
+
+                codec.decode_to_vec(&mut garbage.as_bytes()).is_err()
+                    && garbage.chars().count() == input.chars().count() + 1
+                    && swapped.is_none_or(|s| s != input && s.len() == input.len())
+                    && replaced.is_some() != input.is_empty()
+                    && replaced.is_none_or(|s| s != input)
+                    && mutate(&mut rng, v, input) != input
+            })
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Conversion of data from and to other textual encodings, without decoding it in full first.
+
+use std::ascii;
+use std::io::{self, Read};
+
+use crate::emojis::Version;
+use crate::percent::hex_value;
+use crate::stream::DecoderReader;
+use crate::DEFAULT_VERSION;
+
+/// Converts standard Base64 text with padding into text encoded with the
+/// [default version](constant.DEFAULT_VERSION.html); see
+/// [`transcode_from_base64_with`](fn.transcode_from_base64_with.html).
+///
+/// Requires the `base64` feature.
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// assert_eq!(ecoji::transcode_from_base64("aW5wdXQgZGF0YQ==")?, "👶😲🇲👅🍉🔙🌥🌩");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[cfg(feature = "base64")]
+pub fn transcode_from_base64(input: &str) -> io::Result<String> {
+    transcode_from_base64_with(DEFAULT_VERSION, input)
+}
+
+/// Converts standard Base64 text with padding into text encoded with the given version. The data
+/// is converted in a single pass, without holding all of it in memory. ASCII whitespace in the
+/// input, like the line breaks of wrapped Base64 text, is ignored.
+///
+/// Fails with an error of the `io::ErrorKind::InvalidData` kind if the input is not valid
+/// Base64. Requires the `base64` feature.
+#[cfg(feature = "base64")]
+pub fn transcode_from_base64_with(version: &Version, input: &str) -> io::Result<String> {
+    use base64::engine::general_purpose::STANDARD;
+
+    let mut data =
+        base64::read::DecoderReader::new(SkipWhitespace::new(input.as_bytes()), &STANDARD);
+    version.encode_to_string(&mut data)
+}
+
+/// Converts encoded text into standard Base64 text with padding, decoding it starting with the
+/// default version. The data is converted in a single pass, without holding all of it in memory.
+///
+/// Fails under the same conditions as [`decode`](fn.decode.html). Requires the `base64` feature.
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// assert_eq!(ecoji::transcode_to_base64("👶😲⛵👅🍉🧴🦪🦮")?, "aW5wdXQgZGF0YQ==");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[cfg(feature = "base64")]
+pub fn transcode_to_base64(input: &str) -> io::Result<String> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::write::EncoderStringWriter;
+
+    let mut output = EncoderStringWriter::new(&STANDARD);
+    io::copy(
+        &mut DecoderReader::new(DEFAULT_VERSION, input.as_bytes()),
+        &mut output,
+    )?;
+    Ok(output.into_inner())
+}
+
+/// Converts hexadecimal text, with two digits per byte, into text encoded with the
+/// [default version](constant.DEFAULT_VERSION.html); see
+/// [`transcode_from_hex_with`](fn.transcode_from_hex_with.html).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// assert_eq!(ecoji::transcode_from_hex("61 62 63")?, "👖📸🎈☕");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn transcode_from_hex(input: &str) -> io::Result<String> {
+    transcode_from_hex_with(DEFAULT_VERSION, input)
+}
+
+/// Converts hexadecimal text, with two digits per byte, into text encoded with the given version.
+/// The data is converted in a single pass, without holding all of it in memory. Digits may be of
+/// either case, and ASCII whitespace between them is ignored.
+///
+/// Fails with an error of the `io::ErrorKind::InvalidData` kind if the input contains other
+/// characters, or an odd number of digits.
+pub fn transcode_from_hex_with(version: &Version, input: &str) -> io::Result<String> {
+    let mut data = HexReader::new(input.as_bytes());
+    version.encode_to_string(&mut data)
+}
+
+/// Converts encoded text into lowercase hexadecimal text, with two digits per byte, decoding it
+/// starting with the default version. The data is converted in a single pass, without holding all
+/// of it in memory.
+///
+/// Fails under the same conditions as [`decode`](fn.decode.html).
+///
+/// # Examples
+///
+/// ```
+/// # fn test() -> ::std::io::Result<()> {
+/// assert_eq!(ecoji::transcode_to_hex("👖📸🎈☕")?, "616263");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+pub fn transcode_to_hex(input: &str) -> io::Result<String> {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut data = DecoderReader::new(DEFAULT_VERSION, input.as_bytes());
+    let mut output = String::new();
+    let mut buf = [0; 4096];
+    loop {
+        let n = data.read(&mut buf)?;
+        if n == 0 {
+            return Ok(output);
+        }
+        for &b in &buf[..n] {
+            output.push(DIGITS[(b >> 4) as usize] as char);
+            output.push(DIGITS[(b & 0x0f) as usize] as char);
+        }
+    }
+}
+
+/// A reader converting hexadecimal text from the underlying reader, with two digits per byte,
+/// into the bytes it represents. Digits may be of either case, and ASCII whitespace between them
+/// is skipped.
+///
+/// Reading fails with an error of the `io::ErrorKind::InvalidData` kind at the first character
+/// which is not a digit, or at the end of input after an odd number of digits.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut data = Vec::new();
+/// ecoji::HexReader::new("61 62\n63".as_bytes()).read_to_end(&mut data)?;
+///
+/// assert_eq!(data, b"abc");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HexReader<R> {
+    inner: R,
+    /// The first digit of a pair whose second digit hasn't been read yet.
+    high: Option<u8>,
+}
+
+impl<R: Read> HexReader<R> {
+    /// Creates a new reader converting the hexadecimal text read from `inner`.
+    pub fn new(inner: R) -> HexReader<R> {
+        HexReader { inner, high: None }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for HexReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return match self.high {
+                    Some(_) => Err(invalid_data("Odd number of digits in hex input".to_owned())),
+                    None => Ok(0),
+                };
+            }
+
+            // every byte takes at least one input byte, so the output never overtakes the input
+            let mut m = 0;
+            for i in 0..n {
+                let b = buf[i];
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+                let digit = hex_value(b).ok_or_else(|| {
+                    invalid_data(format!(
+                        "Invalid character '{}' in hex input",
+                        ascii::escape_default(b)
+                    ))
+                })?;
+                match self.high.take() {
+                    Some(high) => {
+                        buf[m] = high << 4 | digit;
+                        m += 1;
+                    }
+                    None => self.high = Some(digit),
+                }
+            }
+            if m > 0 {
+                return Ok(m);
+            }
+        }
+    }
+}
+
+/// A reader dropping ASCII whitespace from the underlying reader, e.g. the line breaks of wrapped
+/// Base64 text, which Base64 decoders reject.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Read;
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut text = String::new();
+/// ecoji::SkipWhitespace::new("aW5w\r\ndXQ=\n".as_bytes()).read_to_string(&mut text)?;
+///
+/// assert_eq!(text, "aW5wdXQ=");
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkipWhitespace<R> {
+    inner: R,
+}
+
+impl<R: Read> SkipWhitespace<R> {
+    /// Creates a new reader dropping the whitespace read from `inner`.
+    pub fn new(inner: R) -> SkipWhitespace<R> {
+        SkipWhitespace { inner }
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut m = 0;
+            for i in 0..n {
+                if !buf[i].is_ascii_whitespace() {
+                    buf[m] = buf[i];
+                    m += 1;
+                }
+            }
+            if m > 0 {
+                return Ok(m);
+            }
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::emojis::VERSIONS;
+
+    #[test]
+    fn test_invalid_input() {
+        for input in ["6", "6g", "61 6"] {
+            let e = transcode_from_hex(input).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        }
+        assert!(transcode_to_hex("👖📸🎈x").is_err());
+
+        #[cfg(feature = "base64")]
+        {
+            let e = transcode_from_base64("aW5wd?==").unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            assert!(transcode_to_base64("👖📸🎈x").is_err());
+        }
+    }
+
+    quickcheck! {
+        fn hex_round_trips(input: Vec<u8>) -> bool {
+            let hex: String = input.iter().map(|b| format!("{:02X}\n", b)).collect();
+            VERSIONS.iter().all(|&v| {
+                let encoded = transcode_from_hex_with(v, &hex).unwrap();
+                let expected: String = input.iter().map(|b| format!("{:02x}", b)).collect();
+                encoded == v.encode_to_string(&mut input.as_slice()).unwrap()
+                    && transcode_to_hex(&encoded).unwrap() == expected
+            })
+        }
+    }
+
+    #[cfg(feature = "base64")]
+    quickcheck! {
+        fn base64_round_trips(input: Vec<u8>) -> bool {
+            use base64::Engine;
+
+            let base64 = base64::engine::general_purpose::STANDARD.encode(&input);
+            let wrapped: String = base64
+                .chars()
+                .enumerate()
+                .flat_map(|(i, c)| (i % 76 == 75).then_some('\n').into_iter().chain([c]))
+                .collect();
+            VERSIONS.iter().all(|&v| {
+                let encoded = transcode_from_base64_with(v, &wrapped).unwrap();
+                encoded == v.encode_to_string(&mut input.as_slice()).unwrap()
+                    && transcode_to_base64(&encoded).unwrap() == base64
+            })
+        }
+    }
+}
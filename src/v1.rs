@@ -0,0 +1,74 @@
+//! Encoding and decoding with version 1 of the Ecoji alphabet, the
+//! [default](../constant.DEFAULT_VERSION.html) of this crate.
+//!
+//! Encoded data has the same sort order as the input data, because the final chunk is always
+//! padded to four symbols.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn test() -> ::std::io::Result<()> {
+//! let encoded = ecoji::v1::encode_to_string(&mut "input data".as_bytes())?;
+//! assert_eq!(encoded, "👶😲🇲👅🍉🔙🌥🌩");
+//!
+//! let decoded = ecoji::v1::decode_to_vec(&mut encoded.as_bytes())?;
+//! assert_eq!(decoded, b"input data");
+//! #  Ok(())
+//! # }
+//! # test().unwrap();
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::emojis::{Version, VERSION1};
+
+/// The version 1 alphabet.
+pub const VERSION: &Version = &VERSION1;
+
+/// The padding character, which fills the final chunk up to four symbols.
+pub const PADDING: char = VERSION1.PADDING;
+
+/// Encodes the source with version 1; see
+/// [`Version::encode`](../emojis/struct.Version.html#method.encode).
+pub fn encode<R: Read + ?Sized, W: Write + ?Sized>(
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    VERSION.encode(source, destination)
+}
+
+/// Encodes the source with version 1 into a new string; see
+/// [`Version::encode_to_string`](../emojis/struct.Version.html#method.encode_to_string).
+pub fn encode_to_string<R: Read + ?Sized>(source: &mut R) -> io::Result<String> {
+    VERSION.encode_to_string(source)
+}
+
+/// Decodes the source starting with version 1; see
+/// [`Version::decode`](../emojis/struct.Version.html#method.decode).
+pub fn decode<R: Read + ?Sized, W: Write + ?Sized>(
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    VERSION.decode(source, destination)
+}
+
+/// Decodes the source starting with version 1, accepting only the canonical encoding; see
+/// [`Version::decode_strict`](../emojis/struct.Version.html#method.decode_strict).
+pub fn decode_strict<R: Read + ?Sized, W: Write + ?Sized>(
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    VERSION.decode_strict(source, destination)
+}
+
+/// Decodes the source starting with version 1 into a new string; see
+/// [`Version::decode_to_string`](../emojis/struct.Version.html#method.decode_to_string).
+pub fn decode_to_string<R: Read + ?Sized>(source: &mut R) -> io::Result<String> {
+    VERSION.decode_to_string(source)
+}
+
+/// Decodes the source starting with version 1 into a new byte vector; see
+/// [`Version::decode_to_vec`](../emojis/struct.Version.html#method.decode_to_vec).
+pub fn decode_to_vec<R: Read + ?Sized>(source: &mut R) -> io::Result<Vec<u8>> {
+    VERSION.decode_to_vec(source)
+}
@@ -0,0 +1,76 @@
+//! Encoding and decoding with version 2 of the Ecoji alphabet, the default of the reference
+//! implementation.
+//!
+//! Version 2 replaces emojis of version 1 which are displayed inconsistently or not at all on
+//! some platforms. The final chunk is terminated by a single padding symbol instead of being
+//! padded to four symbols, so unlike with version 1, the sort order of the input data is not
+//! preserved.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn test() -> ::std::io::Result<()> {
+//! let encoded = ecoji::v2::encode_to_string(&mut "input data".as_bytes())?;
+//! assert_eq!(encoded, "👶😲⛵👅🍉🧴🦪🦮");
+//!
+//! let decoded = ecoji::v2::decode_to_vec(&mut encoded.as_bytes())?;
+//! assert_eq!(decoded, b"input data");
+//! #  Ok(())
+//! # }
+//! # test().unwrap();
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::emojis::{Version, VERSION2};
+
+/// The version 2 alphabet.
+pub const VERSION: &Version = &VERSION2;
+
+/// The padding character, which terminates a final chunk of less than four symbols.
+pub const PADDING: char = VERSION2.PADDING;
+
+/// Encodes the source with version 2; see
+/// [`Version::encode`](../emojis/struct.Version.html#method.encode).
+pub fn encode<R: Read + ?Sized, W: Write + ?Sized>(
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    VERSION.encode(source, destination)
+}
+
+/// Encodes the source with version 2 into a new string; see
+/// [`Version::encode_to_string`](../emojis/struct.Version.html#method.encode_to_string).
+pub fn encode_to_string<R: Read + ?Sized>(source: &mut R) -> io::Result<String> {
+    VERSION.encode_to_string(source)
+}
+
+/// Decodes the source starting with version 2; see
+/// [`Version::decode`](../emojis/struct.Version.html#method.decode).
+pub fn decode<R: Read + ?Sized, W: Write + ?Sized>(
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    VERSION.decode(source, destination)
+}
+
+/// Decodes the source starting with version 2, accepting only the canonical encoding; see
+/// [`Version::decode_strict`](../emojis/struct.Version.html#method.decode_strict).
+pub fn decode_strict<R: Read + ?Sized, W: Write + ?Sized>(
+    source: &mut R,
+    destination: &mut W,
+) -> io::Result<usize> {
+    VERSION.decode_strict(source, destination)
+}
+
+/// Decodes the source starting with version 2 into a new string; see
+/// [`Version::decode_to_string`](../emojis/struct.Version.html#method.decode_to_string).
+pub fn decode_to_string<R: Read + ?Sized>(source: &mut R) -> io::Result<String> {
+    VERSION.decode_to_string(source)
+}
+
+/// Decodes the source starting with version 2 into a new byte vector; see
+/// [`Version::decode_to_vec`](../emojis/struct.Version.html#method.decode_to_vec).
+pub fn decode_to_vec<R: Read + ?Sized>(source: &mut R) -> io::Result<Vec<u8>> {
+    VERSION.decode_to_vec(source)
+}
@@ -0,0 +1,136 @@
+//! Validation of encoded data without decoding it into a buffer.
+
+use std::io::{self, Read, Write};
+
+use crate::decode::Settings;
+use crate::emojis::Version;
+
+/// Structure of encoded data which has been checked by
+/// [`Version::validate`](emojis/struct.Version.html#method.validate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidationReport {
+    version_number: usize,
+    symbols: usize,
+    chunks: usize,
+    decoded_len: usize,
+}
+
+impl ValidationReport {
+    /// Returns the number of the version the data was decoded with: the one `validate` was
+    /// called on, unless the data contains characters which only the other version has.
+    pub fn version_number(&self) -> usize {
+        self.version_number
+    }
+
+    /// Returns the number of symbols, i.e. characters other than line breaks.
+    pub fn symbols(&self) -> usize {
+        self.symbols
+    }
+
+    /// Returns the number of encoded chunks, each of which decodes to at most 5 bytes.
+    pub fn chunks(&self) -> usize {
+        self.chunks
+    }
+
+    /// Returns the number of bytes the data decodes to.
+    pub fn decoded_len(&self) -> usize {
+        self.decoded_len
+    }
+}
+
+impl Version {
+    /// Checks that the entire source is the canonical encoding of some data, like
+    /// [`decode_strict`](#method.decode_strict) does, but instead of writing the decoded data
+    /// anywhere, returns a report of its structure. Line breaks are skipped, so that wrapped text
+    /// can be validated as is.
+    ///
+    /// Fails under the same conditions as `decode_strict`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn test() -> ::std::io::Result<()> {
+    /// let report = ecoji::VERSION1.validate(&mut "👶😲🇲👅\n🍉🔙🌥🌩\n".as_bytes())?;
+    ///
+    /// assert_eq!(report.version_number(), 1);
+    /// assert_eq!(report.symbols(), 8);
+    /// assert_eq!(report.chunks(), 2);
+    /// assert_eq!(report.decoded_len(), 10);
+    /// #  Ok(())
+    /// # }
+    /// # test().unwrap();
+    /// ```
+    pub fn validate<R: Read + ?Sized>(&self, source: &mut R) -> io::Result<ValidationReport> {
+        let mut source = SymbolCounter {
+            inner: source,
+            symbols: 0,
+        };
+        let mut destination = ChunkCounter { chunks: 0 };
+        let mut decoder = self;
+        let settings = Settings {
+            strict: true,
+            ..Settings::wrapped()
+        };
+        let decoded_len =
+            self.decode_from(&mut decoder, settings, &mut source, &mut destination)?;
+        Ok(ValidationReport {
+            version_number: decoder.VERSION_NUMBER,
+            symbols: source.symbols,
+            chunks: destination.chunks,
+            decoded_len,
+        })
+    }
+}
+
+/// A reader adapter counting the characters other than line breaks read through it.
+struct SymbolCounter<'a, R: ?Sized> {
+    inner: &'a mut R,
+    symbols: usize,
+}
+
+impl<'a, R: Read + ?Sized> Read for SymbolCounter<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.symbols += buf[..n]
+            .iter()
+            .filter(|&&b| b & 0xc0 != 0x80 && b != b'\n' && b != b'\r')
+            .count();
+        Ok(n)
+    }
+}
+
+/// A writer discarding the data written to it, and counting the chunks, which the decoder writes
+/// one at a time.
+struct ChunkCounter {
+    chunks: usize,
+}
+
+impl Write for ChunkCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !buf.is_empty() {
+            self.chunks += 1;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emojis::VERSIONS;
+
+    quickcheck! {
+        fn validate_agrees_with_decode(input: Vec<u8>) -> bool {
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let report = v.other_version().validate(&mut encoded.as_bytes()).unwrap();
+                report.decoded_len() == input.len()
+                    && report.chunks() == input.len().div_ceil(5)
+                    && report.symbols() == encoded.chars().count()
+            })
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Measuring and wrapping encoded text for display.
+
+use std::io::{self, Write};
+#[cfg(feature = "unicode-width")]
+use std::str;
+
+use crate::decode::is_line_break;
+
+/// Returns the number of symbols in the encoded text, i.e. its characters other than line breaks.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(ecoji::symbol_count("👶😲🇲👅\n🍉🔙🌥🌩\n"), 8);
+/// ```
+pub fn symbol_count(s: &str) -> usize {
+    s.chars().filter(|&c| !is_line_break(c)).count()
+}
+
+/// Returns the number of terminal columns the symbols of the encoded text take when displayed in
+/// a single line, with line breaks ignored. Most emoji are displayed double-width.
+///
+/// Requires the `unicode-width` feature.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(ecoji::display_width("👖📸🎈☕"), 8);
+/// ```
+#[cfg(feature = "unicode-width")]
+pub fn display_width(s: &str) -> usize {
+    s.chars()
+        .filter(|&c| !is_line_break(c))
+        .map(char_width)
+        .sum()
+}
+
+/// Breaks the encoded text into lines of at most the given number of terminal columns, taking
+/// into account that most emoji are displayed double-width. Existing line breaks are removed
+/// first; lines are separated with `\n`, and the last line is not terminated. A width of zero
+/// disables wrapping, and a symbol wider than the line is put on a line of its own.
+///
+/// The result decodes to the same data with line breaks skipped, e.g. by a
+/// [`Codec`](struct.Codec.html) built with
+/// [`Builder::ignore_line_breaks`](struct.Builder.html#method.ignore_line_breaks).
+///
+/// Requires the `unicode-width` feature.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     ecoji::wrap_display("👖📸🎈☕\n👖📸🎈☕", 10),
+///     "👖📸🎈☕👖\n📸🎈☕"
+/// );
+/// ```
+#[cfg(feature = "unicode-width")]
+pub fn wrap_display(s: &str, columns: usize) -> String {
+    let mut output = String::with_capacity(s.len() + s.len() / 4);
+    let mut column = 0;
+    for c in s.chars().filter(|&c| !is_line_break(c)) {
+        let width = char_width(c);
+        if columns > 0 && column > 0 && column + width > columns {
+            output.push('\n');
+            column = 0;
+        }
+        output.push(c);
+        column += width;
+    }
+    output
+}
+
+/// Where a [`LineWrapper`](struct.LineWrapper.html) breaks the lines of encoded text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineWidth {
+    /// After the given number of symbols; zero disables wrapping.
+    Symbols(usize),
+    /// Before a symbol which wouldn't fit into the given number of terminal columns, taking into
+    /// account that most emoji are displayed double-width; zero disables wrapping.
+    ///
+    /// Requires the `unicode-width` feature.
+    #[cfg(feature = "unicode-width")]
+    Columns(usize),
+}
+
+impl LineWidth {
+    /// Returns the width of a line, in symbols or columns, or zero if wrapping is disabled.
+    pub fn limit(self) -> usize {
+        match self {
+            LineWidth::Symbols(limit) => limit,
+            #[cfg(feature = "unicode-width")]
+            LineWidth::Columns(limit) => limit,
+        }
+    }
+}
+
+/// A writer which breaks the encoded text written through it into lines of the given width,
+/// e.g. the output of an [`EncoderWriter`](struct.EncoderWriter.html). Every line, including the
+/// last one once [`finish`](#method.finish) is called, is terminated with `\n`. The text may be
+/// split between writes anywhere, even within a character.
+///
+/// A [`Codec`](struct.Codec.html) configured with
+/// [`Builder::wrap`](struct.Builder.html#method.wrap) wraps its output with this writer.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+///
+/// use ecoji::{LineWidth, LineWrapper};
+///
+/// # fn test() -> ::std::io::Result<()> {
+/// let mut wrapper = LineWrapper::new(Vec::new(), LineWidth::Symbols(3));
+/// wrapper.write_all("👖📸🎈☕".as_bytes())?;
+///
+/// assert_eq!(wrapper.finish()?, "👖📸🎈\n☕\n".as_bytes());
+/// #  Ok(())
+/// # }
+/// # test().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct LineWrapper<W> {
+    inner: W,
+    width: LineWidth,
+    column: usize,
+    /// The beginning of a character split between writes, whose width is not known yet.
+    #[cfg(feature = "unicode-width")]
+    partial: Vec<u8>,
+}
+
+impl<W: Write> LineWrapper<W> {
+    /// Creates a new writer breaking the text written through it into lines of the given width,
+    /// and writing them to `inner`.
+    pub fn new(inner: W, width: LineWidth) -> LineWrapper<W> {
+        LineWrapper {
+            inner,
+            width,
+            column: 0,
+            #[cfg(feature = "unicode-width")]
+            partial: Vec::with_capacity(4),
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Terminates the last line, if wrapping is enabled and anything was written, and returns
+    /// the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        #[cfg(feature = "unicode-width")]
+        self.inner.write_all(&self.partial)?;
+        if self.column > 0 {
+            self.inner.write_all(b"\n")?;
+        }
+        Ok(self.inner)
+    }
+
+    /// Checks whether a character of the given width doesn't fit into the current line.
+    fn breaks_before(&self, width: usize) -> bool {
+        self.column > 0 && self.column + width > self.width.limit()
+    }
+}
+
+impl<W: Write> Write for LineWrapper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.width.limit() == 0 {
+            return self.inner.write(buf);
+        }
+
+        // the part of the buffer following the completed character
+        #[allow(unused_mut)]
+        let mut rest = 0;
+        #[cfg(feature = "unicode-width")]
+        if !self.partial.is_empty() {
+            let missing = char_length(self.partial[0]) - self.partial.len();
+            if buf.len() < missing {
+                self.partial.extend_from_slice(buf);
+                return Ok(buf.len());
+            }
+            self.partial.extend_from_slice(&buf[..missing]);
+            let width = utf8_width(&self.partial);
+            if self.breaks_before(width) {
+                self.inner.write_all(b"\n")?;
+                self.column = 0;
+            }
+            self.column += width;
+            self.inner.write_all(&self.partial)?;
+            self.partial.clear();
+            rest = missing;
+        }
+
+        let mut start = rest;
+        for (i, &b) in buf.iter().enumerate().skip(rest) {
+            // only the first byte of a character starts a new column
+            if b & 0xc0 == 0x80 {
+                continue;
+            }
+            let width = match self.width {
+                LineWidth::Symbols(_) => 1,
+                #[cfg(feature = "unicode-width")]
+                LineWidth::Columns(_) => {
+                    let end = i + char_length(b);
+                    if end > buf.len() {
+                        self.inner.write_all(&buf[start..i])?;
+                        self.partial.extend_from_slice(&buf[i..]);
+                        return Ok(buf.len());
+                    }
+                    utf8_width(&buf[i..end])
+                }
+            };
+            if self.breaks_before(width) {
+                self.inner.write_all(&buf[start..i])?;
+                self.inner.write_all(b"\n")?;
+                start = i;
+                self.column = 0;
+            }
+            self.column += width;
+        }
+        self.inner.write_all(&buf[start..])?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns the length of the UTF-8 sequence starting with the given byte.
+#[cfg(feature = "unicode-width")]
+fn char_length(first: u8) -> usize {
+    match first {
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the number of terminal columns taken by the UTF-8-encoded character.
+#[cfg(feature = "unicode-width")]
+fn utf8_width(bytes: &[u8]) -> usize {
+    str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(1, char_width)
+}
+
+/// Returns the number of terminal columns taken by the character, counting control characters,
+/// which never appear in encoded text, as one.
+#[cfg(feature = "unicode-width")]
+fn char_width(c: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(c).unwrap_or(1)
+}
+
+#[cfg(all(test, feature = "unicode-width"))]
+mod tests {
+    use std::io::Write;
+
+    use super::{display_width, wrap_display, LineWidth, LineWrapper};
+    use crate::codec::Builder;
+    use crate::decode::Settings;
+    use crate::emojis::VERSIONS;
+
+    /// Writes the text through a wrapper of the given width in pieces of `piece` bytes.
+    fn wrap_pieces(text: &str, width: LineWidth, piece: usize) -> String {
+        let mut wrapper = LineWrapper::new(Vec::new(), width);
+        for piece in text.as_bytes().chunks(piece) {
+            wrapper.write_all(piece).unwrap();
+        }
+        String::from_utf8(wrapper.finish().unwrap()).unwrap()
+    }
+
+    quickcheck! {
+        fn wrapped_lines_fit(input: Vec<u8>, columns: u8) -> bool {
+            let columns = columns as usize % 20;
+            VERSIONS.iter().all(|v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let wrapped = wrap_display(&encoded, columns);
+                let lines_fit = wrapped.lines().all(|line| {
+                    columns == 0 || display_width(line) <= columns || line.chars().count() == 1
+                });
+                let mut output = Vec::new();
+                v.decode_from(&mut { v }, Settings::wrapped(), &mut wrapped.as_bytes(), &mut output)
+                    .unwrap();
+                lines_fit && output == input
+            })
+        }
+
+        fn line_wrapper_is_the_same(input: Vec<u8>, limit: u8, piece: u8) -> bool {
+            let (limit, piece) = (limit as usize % 20, piece as usize % 9 + 1);
+            VERSIONS.iter().all(|&v| {
+                let encoded = v.encode_to_string(&mut input.as_slice()).unwrap();
+                let codec = Builder::new().version(v).wrap(limit).build();
+                let by_columns = if limit == 0 || encoded.is_empty() {
+                    encoded.clone()
+                } else {
+                    wrap_display(&encoded, limit) + "\n"
+                };
+                wrap_pieces(&encoded, LineWidth::Symbols(limit), piece)
+                    == codec.encode_to_string(&mut input.as_slice()).unwrap()
+                    && wrap_pieces(&encoded, LineWidth::Columns(limit), piece) == by_columns
+            })
+        }
+    }
+}
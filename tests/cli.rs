@@ -0,0 +1,728 @@
+//! Tests of the command line tool, run as a separate process.
+
+#![cfg(feature = "cli")]
+
+use std::path::Path;
+use std::process;
+
+use assert_cmd::Command;
+
+/// Returns a command running the tool, unaffected by the configuration of the user.
+fn ecoji() -> Command {
+    Command::from_std(ecoji_process())
+}
+
+/// Returns the command of [`ecoji`], for tests which spawn the tool and interact with it while it
+/// runs.
+fn ecoji_process() -> process::Command {
+    let no_config = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-config");
+    let mut command = process::Command::new(assert_cmd::cargo::cargo_bin("ecoji"));
+    command
+        .env("XDG_CONFIG_HOME", &no_config)
+        .env("APPDATA", &no_config)
+        .env_remove("ECOJI_VERSION")
+        .env_remove("ECOJI_WRAP");
+    command
+}
+
+fn encode(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let output = ecoji().args(args).write_stdin(input).assert().success();
+    output.get_output().stdout.clone()
+}
+
+#[test]
+fn test_versions() {
+    let v1 = "👶😲🇲👅🍉🔙🌥🌩";
+    let v2 = "👶😲⛵👅🍉🧴🦪🦮";
+    assert_eq!(encode(&["--v1"], b"input data"), v1.as_bytes());
+    assert_eq!(encode(&["--v2"], b"input data"), v2.as_bytes());
+    assert_eq!(encode(&[], b"input data"), v2.as_bytes());
+    assert_eq!(encode(&["--v1", "-s", "input data"], b""), v1.as_bytes());
+
+    for encoded in [v1, v2] {
+        for args in [&["-d"][..], &["-d", "--v1"], &["-d", "--v2"]] {
+            ecoji()
+                .args(args)
+                .write_stdin(encoded)
+                .assert()
+                .success()
+                .stdout("input data");
+        }
+    }
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let input: Vec<u8> = (0..=255).cycle().take(4099).collect();
+    for version in ["--v1", "--v2"] {
+        for wrap in ["0", "7"] {
+            let encoded = encode(&[version, "-w", wrap], &input);
+            assert!(std::str::from_utf8(&encoded).is_ok());
+            let decoded = encode(&["-d"], &encoded);
+            assert_eq!(decoded, input);
+        }
+    }
+    assert_eq!(encode(&[], b""), b"");
+}
+
+#[test]
+fn test_wrapped_input() {
+    let wrapped = "👶😲🇲👅\r\n🍉🔙\n🌥🌩\n";
+    for args in [&["-d"][..], &["-d", "--strict"], &["-d", "--threads", "2"]] {
+        ecoji()
+            .args(args)
+            .write_stdin(wrapped)
+            .assert()
+            .success()
+            .stdout("input data");
+    }
+    ecoji().arg("check").write_stdin(wrapped).assert().success();
+    ecoji()
+        .args(["-d"])
+        .write_stdin("👶😲🇲👅 🍉🔙🌥🌩")
+        .assert()
+        .code(65);
+}
+
+#[test]
+fn test_exit_codes() {
+    ecoji()
+        .args(["-d"])
+        .write_stdin("👖x")
+        .assert()
+        .code(65)
+        .stderr(predicates::str::starts_with("ecoji: "));
+    ecoji()
+        .args(["-d", "--strict"])
+        .write_stdin("👕☕👕☕")
+        .assert()
+        .code(1);
+    ecoji()
+        .arg(Path::new(env!("CARGO_TARGET_TMPDIR")).join("missing"))
+        .assert()
+        .code(66);
+    ecoji().arg("--bogus").assert().code(2);
+}
+
+#[test]
+fn test_config() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("config");
+    std::fs::create_dir_all(dir.join("ecoji")).unwrap();
+    std::fs::write(dir.join("ecoji").join("config.toml"), "version = 1\n").unwrap();
+    ecoji()
+        .env("XDG_CONFIG_HOME", &dir)
+        .write_stdin("input data")
+        .assert()
+        .success()
+        .stdout("👶😲🇲👅🍉🔙🌥🌩");
+    ecoji()
+        .env("XDG_CONFIG_HOME", &dir)
+        .env("ECOJI_VERSION", "2")
+        .write_stdin("input data")
+        .assert()
+        .success()
+        .stdout("👶😲⛵👅🍉🧴🦪🦮");
+
+    // a file in place of the configuration directory means there is no configuration
+    let file = dir.join("file");
+    std::fs::write(&file, "").unwrap();
+    ecoji()
+        .env("XDG_CONFIG_HOME", &file)
+        .write_stdin("input data")
+        .assert()
+        .success()
+        .stdout("👶😲⛵👅🍉🧴🦪🦮");
+}
+
+#[test]
+fn test_check() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("check.ecoji");
+    std::fs::write(&path, encode(&["--v1", "-w", "4"], b"input data")).unwrap();
+    ecoji()
+        .arg("check")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("version: 1\nsymbols: 8\ndecoded size: 10\n");
+
+    // padding followed by another chunk, which only the strict check rejects
+    ecoji()
+        .arg("check")
+        .write_stdin("👕☕👕☕")
+        .assert()
+        .success();
+    ecoji()
+        .args(["check", "--strict"])
+        .write_stdin("👕☕👕☕")
+        .assert()
+        .code(1)
+        .stderr(predicates::str::starts_with("invalid: "));
+
+    ecoji()
+        .arg("check")
+        .write_stdin("👖📸🎈x")
+        .assert()
+        .code(65)
+        .stdout("")
+        .stderr(predicates::str::starts_with("invalid: "));
+    ecoji()
+        .args(["check", "--json"])
+        .write_stdin("👖📸🎈x")
+        .assert()
+        .code(65)
+        .stdout(predicates::str::contains("\"valid\":false"));
+}
+
+#[test]
+fn test_transcode() {
+    let v1 = "👶😲🇲👅🍉🔙🌥🌩";
+    let v2 = "👶😲⛵👅🍉🧴🦪🦮";
+    for (input, to, output) in [(v1, "v2", v2), (v2, "v1", v1), (v1, "v1", v1)] {
+        ecoji()
+            .args(["transcode", "--to", to])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .stdout(output);
+    }
+
+    // the padding of a short final chunk differs between the versions
+    let data = b"abcdefg";
+    for (from, to, target) in [("--v1", "v2", "--v2"), ("--v2", "v1", "--v1")] {
+        let encoded = encode(&[from, "-w", "3"], data);
+        let transcoded = ecoji()
+            .args(["transcode", "--to", to, "-w", "5"])
+            .write_stdin(encoded)
+            .assert()
+            .success();
+        assert_eq!(
+            transcoded.get_output().stdout,
+            encode(&[target, "-w", "5"], data)
+        );
+    }
+
+    ecoji()
+        .args(["transcode", "--to", "v2"])
+        .write_stdin("👖📸🎈x")
+        .assert()
+        .code(65);
+}
+
+#[test]
+fn test_formats() {
+    let data: Vec<u8> = (0..=255).collect();
+    for format in ["hex", "base64"] {
+        let text = encode(&["-d", "--output-format", format], &encode(&[], &data));
+        let encoded = encode(&["--input-format", format], &text);
+        assert_eq!(encode(&["-d"], &encoded), data);
+    }
+    assert_eq!(
+        encode(&["-d", "--output-format", "hex"], "👖📸🎈☕".as_bytes()),
+        b"616263"
+    );
+    assert_eq!(
+        encode(&["-d", "--output-format", "base64"], "👖📸🎈☕".as_bytes()),
+        b"YWJj"
+    );
+
+    // whitespace between the digits is skipped, like the line breaks of wrapped Base64
+    assert_eq!(
+        encode(&["--input-format", "hex"], b"61 62\n63\n"),
+        "👖📸🎈☕".as_bytes()
+    );
+    assert_eq!(
+        encode(&["--input-format", "base64"], b"aW5w\r\ndXQg\nZGF0\nYQ==\n"),
+        encode(&[], b"input data")
+    );
+
+    for (format, input) in [("hex", "61626"), ("hex", "6g"), ("base64", "YW?j")] {
+        ecoji()
+            .args(["--input-format", format])
+            .write_stdin(input)
+            .assert()
+            .code(65)
+            .stdout("");
+    }
+}
+
+#[test]
+fn test_lines() {
+    let (a, b) = ("👖📸🎈☕", "👶😲⛵👅🍉🧴🦪🦮");
+    for (input, output) in [
+        ("abc\ninput data\n", format!("{}\n{}\n", a, b)),
+        ("abc\ninput data", format!("{}\n{}", a, b)),
+        ("\n", "\n".to_owned()),
+        ("", String::new()),
+    ] {
+        assert_eq!(encode(&["--lines"], input.as_bytes()), output.as_bytes());
+        assert_eq!(
+            encode(&["-d", "--lines"], output.as_bytes()),
+            input.as_bytes()
+        );
+    }
+
+    // with -0, newlines are a part of the records
+    let input = "abc\0input\ndata\0";
+    let encoded = encode(&["--lines", "-0"], input.as_bytes());
+    assert_eq!(encoded.iter().filter(|&&b| b == 0).count(), 2);
+    assert!(!encoded.contains(&b'\n'));
+    assert_eq!(encode(&["-d", "--lines", "-0"], &encoded), input.as_bytes());
+
+    ecoji()
+        .args(["-d", "--lines"])
+        .write_stdin(format!("{}\n👖x\n", a))
+        .assert()
+        .code(65)
+        .stdout("abc\n")
+        .stderr(predicates::str::starts_with("ecoji: line 2: "));
+}
+
+#[test]
+fn test_diff() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let v1 = dir.join("diff-v1");
+    std::fs::write(&v1, encode(&["--v1"], b"input data")).unwrap();
+    let v2 = dir.join("diff-v2");
+    std::fs::write(&v2, encode(&["--v2", "-w", "3"], b"input data")).unwrap();
+
+    ecoji()
+        .arg("diff")
+        .args([&v1, &v2])
+        .assert()
+        .success()
+        .stdout(predicates::str::ends_with("carry the same data\n"));
+    ecoji()
+        .args(["diff", "-"])
+        .arg(&v1)
+        .write_stdin(encode(&[], b"input date"))
+        .assert()
+        .code(1)
+        .stdout(predicates::str::ends_with("differ at offset 9\n"));
+    ecoji()
+        .args(["diff", "-q", "-"])
+        .arg(&v2)
+        .write_stdin("👖x")
+        .assert()
+        .code(65)
+        .stdout("");
+    ecoji().args(["diff", "-", "-"]).assert().code(64);
+}
+
+#[test]
+fn test_spool() {
+    let mut encoded = encode(&[], &[7; 10000]);
+    encoded.push(b'x');
+    let partial = ecoji().args(["-d"]).write_stdin(encoded.clone()).assert();
+    assert!(!partial.code(65).get_output().stdout.is_empty());
+    ecoji()
+        .args(["-d", "--spool"])
+        .write_stdin(encoded.clone())
+        .assert()
+        .code(65)
+        .stdout("");
+
+    encoded.pop();
+    let decoded = encode(&["-d", "--spool"], &encoded);
+    assert_eq!(decoded, [7; 10000]);
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn test_character_encodings() {
+    let utf16: Vec<u8> = "\u{feff}Grüße ♥"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let encoded = encode(&["--from-encoding", "utf-16le"], &utf16);
+    assert_eq!(encoded, encode(&[], "Grüße ♥".as_bytes()));
+
+    let decoded = encode(&["-d", "--to-encoding", "utf-16be"], &encoded);
+    let expected: Vec<u8> = "Grüße ♥"
+        .encode_utf16()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+    assert_eq!(decoded, expected);
+
+    ecoji()
+        .args(["-d", "--to-encoding", "latin1"])
+        .write_stdin(encoded)
+        .assert()
+        .code(65);
+}
+
+/// Reads the output of the tool until it decodes to the expected data.
+fn read_until<R: std::io::Read>(stdout: &mut R, output: &mut Vec<u8>, expected: &[u8]) {
+    let mut buf = [0; 64];
+    loop {
+        let decoded = std::str::from_utf8(output)
+            .ok()
+            .and_then(|s| ecoji::decode_string_with(&ecoji::VERSION2, s).ok());
+        if decoded.as_deref() == Some(expected) {
+            return;
+        }
+        let n = stdout.read(&mut buf).unwrap();
+        assert!(n > 0, "Output ended early");
+        output.extend_from_slice(&buf[..n]);
+    }
+}
+
+#[test]
+fn test_follow() {
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("follow.log");
+    fs::write(&path, b"first").unwrap();
+    let mut child = ecoji_process()
+        .args(["--v2", "--follow"])
+        .arg(&path)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+
+    let mut output = Vec::new();
+    read_until(&mut stdout, &mut output, b"first");
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b" and second").unwrap();
+    // The last byte doesn't fill a chunk, and is padded once the end of the file is reached
+    read_until(&mut stdout, &mut output, b"first and second");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_broken_pipe() {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let mut child = ecoji_process()
+        .arg("/dev/zero")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    stdout.read_exact(&mut [0; 16]).unwrap();
+    drop(stdout);
+
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(128 + 13));
+}
+
+#[test]
+fn test_completions() {
+    for (shell, start) in [("bash", "_ecoji()"), ("zsh", "#compdef ecoji")] {
+        ecoji()
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicates::str::starts_with(start))
+            .stdout(predicates::str::contains("--ignore-garbage"));
+    }
+    ecoji().args(["completions", "cmd"]).assert().code(2);
+}
+
+#[test]
+fn test_generate_man() {
+    ecoji()
+        .arg("--generate-man")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(".TH ecoji 1"))
+        .stdout(predicates::str::contains("ignore\\-garbage"));
+}
+
+#[test]
+fn test_ignore_garbage() {
+    let input = "> 👖📸 🎈!☕";
+    ecoji()
+        .args(["-d", "-i"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("abc")
+        .stderr("ecoji: ignored 4 characters outside of the Ecoji alphabet\n");
+    ecoji()
+        .args(["-d", "-i", "--no-ignore-garbage"])
+        .write_stdin(input)
+        .assert()
+        .code(65);
+}
+
+#[test]
+fn test_threads() {
+    let input: Vec<u8> = (0..=255).cycle().take(300_001).collect();
+    let encoded = encode(&[], &input);
+    assert_eq!(encode(&["-t", "4"], &input), encoded);
+    assert_eq!(encode(&["-d", "-t", "4"], &encoded), input);
+    ecoji().args(["-t", "0"]).assert().code(2);
+}
+
+#[test]
+fn test_self_test() {
+    ecoji()
+        .arg("self-test")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("ok   v2 \"input data\"\n"))
+        .stdout(predicates::str::ends_with(" passed, 0 failed\n"));
+}
+
+#[test]
+fn test_detect() {
+    ecoji()
+        .arg("detect")
+        .write_stdin("👶😲🇲👅\n🍉🔙🌥🌩\n")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("version: 1\nsymbols: 8\n"))
+        .stdout(predicates::str::contains("line width: 4\n"))
+        .stdout(predicates::str::ends_with("canonical: yes\n"));
+    ecoji()
+        .args(["detect", "--json"])
+        .write_stdin("👖📸🎈☕")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\"versions\":[1,2]"))
+        .stdout(predicates::str::contains("\"decoded_size\":3"));
+    ecoji()
+        .arg("detect")
+        .write_stdin("👖x")
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("ecoji: no ("));
+}
+
+#[test]
+fn test_split() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("split");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let input: Vec<u8> = (0..100).collect();
+    ecoji()
+        .args(["--split", "64", "--prefix"])
+        .arg(dir.join("part."))
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .stdout("");
+
+    // every part decodes on its own, and the parts in order restore the input
+    let mut parts = Vec::new();
+    let mut decoded = Vec::new();
+    while dir.join(format!("part.{:03}", parts.len())).exists() {
+        let part = dir.join(format!("part.{:03}", parts.len()));
+        assert!(std::fs::metadata(&part).unwrap().len() <= 64);
+        let output = ecoji().arg("-d").arg(&part).assert().success();
+        decoded.extend_from_slice(&output.get_output().stdout);
+        parts.push(part);
+    }
+    assert!(parts.len() > 1);
+    assert_eq!(decoded, input);
+    ecoji()
+        .arg("-d")
+        .args(&parts)
+        .assert()
+        .success()
+        .stdout(input);
+}
+
+#[test]
+fn test_verbose() {
+    ecoji()
+        .arg("-v")
+        .write_stdin("input data")
+        .assert()
+        .success()
+        .stdout("👶😲⛵👅🍉🧴🦪🦮")
+        .stderr(predicates::str::starts_with(
+            "ecoji: version 2, 1 thread\n\
+             ecoji: read 10 bytes\n\
+             ecoji: wrote 8 symbols (31 bytes)\n\
+             ecoji: no padding, all chunks are complete\n\
+             ecoji: took ",
+        ));
+    ecoji()
+        .args(["-d", "-v"])
+        .write_stdin("👶😲⛵👅🍉🧴🦪🦮")
+        .assert()
+        .success()
+        .stdout("input data")
+        .stderr(predicates::str::contains(
+            "ecoji: read 8 symbols (31 bytes)\necoji: wrote 10 bytes\n",
+        ));
+}
+
+#[test]
+fn test_dry_run() {
+    ecoji()
+        .arg("-n")
+        .write_stdin("input data")
+        .assert()
+        .success()
+        .stdout("input: 10 bytes\noutput: 8 symbols, 31 bytes\n");
+    ecoji()
+        .args(["-d", "-n"])
+        .write_stdin("👶😲⛵👅🍉🧴🦪🦮")
+        .assert()
+        .success()
+        .stdout("input: 8 symbols, 31 bytes\noutput: 10 bytes\n");
+
+    // nothing is written to the output file
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("dry-run.ecoji");
+    let _ = std::fs::remove_file(&path);
+    ecoji()
+        .args(["-n", "-o"])
+        .arg(&path)
+        .write_stdin("abc")
+        .assert()
+        .success()
+        .stdout("input: 3 bytes\noutput: 4 symbols, 15 bytes\n");
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_mmap() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let input: Vec<u8> = (0..=255).cycle().take(100_003).collect();
+    let path = dir.join("mmap.bin");
+    std::fs::write(&path, &input).unwrap();
+    let encoded = encode(&[], &input);
+    ecoji()
+        .arg("--mmap")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(encoded.clone());
+
+    let encoded_path = dir.join("mmap.ecoji");
+    std::fs::write(&encoded_path, &encoded).unwrap();
+    ecoji()
+        .args(["-d", "--mmap"])
+        .arg(&encoded_path)
+        .assert()
+        .success()
+        .stdout(input);
+
+    // the standard input can't be mapped and is read as usual
+    assert_eq!(
+        encode(&["--mmap"], b"input data"),
+        encode(&[], b"input data")
+    );
+}
+
+#[test]
+fn test_each() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("each");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let (a, b) = (dir.join("a"), dir.join("b"));
+    std::fs::write(&a, "abc").unwrap();
+    std::fs::write(&b, "input data").unwrap();
+    ecoji()
+        .arg("--each")
+        .args([&a, &b])
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        std::fs::read_to_string(dir.join("a.ecoji")).unwrap(),
+        "👖📸🎈☕"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("b.ecoji")).unwrap(),
+        "👶😲⛵👅🍉🧴🦪🦮"
+    );
+
+    // existing files are only overwritten with --force
+    ecoji()
+        .arg("--each")
+        .arg(&a)
+        .assert()
+        .code(73)
+        .stderr(predicates::str::ends_with(
+            "a.ecoji: already exists; use --force to overwrite it\n",
+        ));
+    ecoji().args(["--each", "-f"]).arg(&a).assert().success();
+
+    std::fs::remove_file(&a).unwrap();
+    std::fs::remove_file(&b).unwrap();
+    ecoji()
+        .args(["-d", "--each"])
+        .args([dir.join("a.ecoji"), dir.join("b.ecoji")])
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "abc");
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "input data");
+}
+
+#[test]
+fn test_null() {
+    ecoji()
+        .args(["--lines", "-0"])
+        .write_stdin("abc\0input data")
+        .assert()
+        .success()
+        .stdout("👖📸🎈☕\0👶😲⛵👅🍉🧴🦪🦮");
+    ecoji()
+        .args(["-d", "--lines", "-0"])
+        .write_stdin("👖📸🎈☕\0👖x\0")
+        .assert()
+        .code(65)
+        .stdout("abc\0")
+        .stderr(predicates::str::starts_with("ecoji: record 2: "));
+}
+
+#[test]
+fn test_tee() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("tee.ecoji");
+    let input = "👖📸\n🎈☕";
+    ecoji()
+        .args(["-d", "--tee"])
+        .arg(&path)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("abc");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), input);
+}
+
+#[test]
+fn test_hash() {
+    let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    ecoji()
+        .args(["--hash", "sha256"])
+        .write_stdin("abc")
+        .assert()
+        .success()
+        .stdout("👖📸🎈☕")
+        .stderr(format!("ecoji: sha256 {}\n", digest));
+    ecoji()
+        .args(["-d", "--hash", "sha256", "--json"])
+        .write_stdin("👖📸🎈☕")
+        .assert()
+        .success()
+        .stdout("abc")
+        .stderr(format!("{{\"file\":null,\"sha256\":\"{}\"}}\n", digest));
+    ecoji().args(["--hash", "md5"]).assert().code(2);
+}
+
+#[test]
+fn test_error_location() {
+    // the caret is aligned with the offending character in terminal columns, and some symbols,
+    // like regional indicators, are displayed single-width
+    for (input, caret) in [("👖📸x", "        ^\n"), ("👖🇲x", "       ^\n")] {
+        ecoji()
+            .arg("-d")
+            .write_stdin(input)
+            .assert()
+            .code(65)
+            .stderr(predicates::str::ends_with(format!(
+                "  at line 1, column 3:\n    {}\n{}",
+                input, caret
+            )));
+    }
+}
@@ -0,0 +1,100 @@
+//! Conformance with the test vectors of the reference implementation.
+//!
+//! The vectors are read from `tests/vectors`, where `tests/vectors/fetch.sh` puts the test data
+//! of the reference implementation along with an `UPSTREAM` file naming the commit it was taken
+//! from, or from the directory named by the `ECOJI_VECTORS` environment variable. For every
+//! `NAME.ev1` or `NAME.ev2` file holding text encoded with version 1 or 2:
+//!
+//! * if `NAME.in` exists, encoding it must give exactly the encoded text, and decoding the text
+//!   must give it back;
+//! * otherwise, the text is garbage, which the decoder must reject.
+//!
+//! Line breaks in the encoded files are ignored, since the reference command line tool prints
+//! one at the end and may wrap long lines, so that a corpus generated by `examples/golden.rs` and
+//! encoded again by that tool can be checked as well. The test fails if there are no vectors at
+//! all, or if the vectors in `tests/vectors` have not been fetched from the reference
+//! implementation. It is only built with the `conformance` feature, since the vectors need to be
+//! fetched first.
+
+#![cfg(feature = "conformance")]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ecoji::emojis::{Version, VERSIONS};
+
+fn vectors_dir() -> PathBuf {
+    match env::var_os("ECOJI_VECTORS") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors"),
+    }
+}
+
+/// Returns the paths of the encoded files of the version, along with the corresponding inputs.
+fn vectors(version: &Version) -> Vec<(PathBuf, Option<PathBuf>)> {
+    let extension = format!("ev{}", version.VERSION_NUMBER);
+    let mut vectors: Vec<_> = fs::read_dir(vectors_dir())
+        .expect("Cannot read the test vectors")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == extension.as_str()))
+        .map(|path| {
+            let input = path.with_extension("in");
+            let input = if input.exists() { Some(input) } else { None };
+            (path, input)
+        })
+        .collect();
+    vectors.sort();
+    vectors
+}
+
+#[test]
+fn test_vectors() {
+    if env::var_os("ECOJI_VECTORS").is_none() {
+        let upstream = vectors_dir().join("UPSTREAM");
+        assert!(
+            upstream.exists(),
+            "No reference test data in {}, run tests/vectors/fetch.sh first",
+            vectors_dir().display()
+        );
+        eprintln!(
+            "Reference test data: {}",
+            fs::read_to_string(upstream).unwrap().trim()
+        );
+    }
+
+    let mut checked = 0;
+    for version in VERSIONS.iter() {
+        for (path, input) in vectors(version) {
+            let mut encoded = fs::read(&path).unwrap();
+            encoded.retain(|&b| b != b'\n' && b != b'\r');
+            let decoded = version.decode_to_vec(&mut encoded.as_slice());
+
+            match input {
+                Some(input) => {
+                    let input = fs::read(input).unwrap();
+                    let output = version.encode_to_string(&mut input.as_slice()).unwrap();
+                    assert!(
+                        output.as_bytes() == encoded,
+                        "{}: encoded differently: {}",
+                        path.display(),
+                        output
+                    );
+                    assert!(
+                        decoded.as_ref().is_ok_and(|decoded| *decoded == input),
+                        "{}: decoded differently: {:?}",
+                        path.display(),
+                        decoded
+                    );
+                }
+                None => assert!(decoded.is_err(), "{}: garbage was accepted", path.display()),
+            }
+            checked += 1;
+        }
+    }
+    assert!(
+        checked > 0,
+        "No test vectors in {}",
+        vectors_dir().display()
+    );
+}
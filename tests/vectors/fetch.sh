@@ -0,0 +1,31 @@
+#!/bin/sh
+# Fetches the test data of the reference implementation into this directory, and records the
+# repository and commit it was taken from in UPSTREAM.
+#
+# usage: tests/vectors/fetch.sh [REF]
+set -eu
+
+repo=https://github.com/keith-turner/ecoji
+ref=${1:-main}
+dir=$(cd "$(dirname "$0")" && pwd)
+checkout=$(mktemp -d)
+trap 'rm -rf "$checkout"' EXIT
+
+git clone --quiet "$repo" "$checkout"
+git -C "$checkout" checkout --quiet "$ref"
+
+found=0
+for encoded in $(find "$checkout" -name '*.ev1' -o -name '*.ev2'); do
+    cp "$encoded" "$dir/"
+    if [ -f "${encoded%.*}.in" ]; then
+        cp "${encoded%.*}.in" "$dir/"
+    fi
+    found=$((found + 1))
+done
+if [ "$found" -eq 0 ]; then
+    echo "fetch.sh: no .ev1 or .ev2 files in $repo at $ref" >&2
+    exit 1
+fi
+
+echo "$repo $(git -C "$checkout" rev-parse HEAD)" > "$dir/UPSTREAM"
+echo "fetch.sh: $found encoded files from $(cat "$dir/UPSTREAM")"